@@ -0,0 +1,51 @@
+use anchor_lang::{prelude::Pubkey, AccountDeserialize};
+
+use crate::{ClientError, Raffle, Ticket};
+
+/// Source of raw account data, usually an RPC client. `Ok(None)` means the
+/// account does not exist.
+pub trait AccountFetcher {
+    type Error;
+
+    fn fetch_account_data(&self, address: &Pubkey) -> Result<Option<Vec<u8>>, Self::Error>;
+}
+
+/// Decodes a program account, checking its discriminator.
+pub fn decode_account<T: AccountDeserialize>(data: &[u8]) -> anchor_lang::Result<T> {
+    T::try_deserialize(&mut &data[..])
+}
+
+pub fn decode_raffle(data: &[u8]) -> anchor_lang::Result<Raffle> {
+    decode_account(data)
+}
+
+pub fn decode_ticket(data: &[u8]) -> anchor_lang::Result<Ticket> {
+    decode_account(data)
+}
+
+pub fn fetch_account<T, F>(fetcher: &F, address: &Pubkey) -> Result<T, ClientError<F::Error>>
+where
+    T: AccountDeserialize,
+    F: AccountFetcher,
+{
+    let data = fetcher
+        .fetch_account_data(address)
+        .map_err(ClientError::Fetch)?
+        .ok_or(ClientError::AccountNotFound(*address))?;
+
+    decode_account(&data).map_err(ClientError::Decode)
+}
+
+pub fn fetch_raffle<F: AccountFetcher>(
+    fetcher: &F,
+    address: &Pubkey,
+) -> Result<Raffle, ClientError<F::Error>> {
+    fetch_account(fetcher, address)
+}
+
+pub fn fetch_ticket<F: AccountFetcher>(
+    fetcher: &F,
+    address: &Pubkey,
+) -> Result<Ticket, ClientError<F::Error>> {
+    fetch_account(fetcher, address)
+}
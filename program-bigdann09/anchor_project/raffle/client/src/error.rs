@@ -0,0 +1,135 @@
+use std::fmt;
+
+use anchor_lang::{
+    prelude::Pubkey,
+    solana_program::{instruction::InstructionError, program_error::ProgramError},
+};
+
+use crate::RaffleError;
+
+#[derive(Debug)]
+pub enum ClientError<E> {
+    Fetch(E),
+    AccountNotFound(Pubkey),
+    Decode(anchor_lang::error::Error),
+}
+
+impl<E: fmt::Display> fmt::Display for ClientError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientError::Fetch(err) => write!(f, "failed to fetch account: {err}"),
+            ClientError::AccountNotFound(address) => write!(f, "account {address} not found"),
+            ClientError::Decode(err) => write!(f, "failed to decode account: {err}"),
+        }
+    }
+}
+
+impl<E: fmt::Debug + fmt::Display> std::error::Error for ClientError<E> {}
+
+/// Every `RaffleError` in declaration order, so index `i` has code `6000 + i`.
+/// New variants must be appended here as well, which the client tests check
+/// against the program source.
+pub const RAFFLE_ERRORS: &[RaffleError] = &[
+    RaffleError::InvalidTicketPrice,
+    RaffleError::InvalidMaxEntries,
+    RaffleError::RaffleNotActive,
+    RaffleError::RaffleAlreadyClaimed,
+    RaffleError::NoEntries,
+    RaffleError::Unauthorized,
+    RaffleError::WinnerAlreadySelected,
+    RaffleError::RaffleSoldOut,
+    RaffleError::InsufficientFunds,
+    RaffleError::EntriesNotFull,
+    RaffleError::WinnerChosen,
+    RaffleError::WinnerNotChosen,
+    RaffleError::InvalidWinner,
+    RaffleError::MathOverflow,
+    RaffleError::RandomNumberGenerationFailed,
+    RaffleError::RevealRequired,
+    RaffleError::NoCommitment,
+    RaffleError::InvalidReveal,
+    RaffleError::RevealTimeoutNotReached,
+    RaffleError::RevealWindowClosed,
+    RaffleError::OracleRequired,
+    RaffleError::NoOracle,
+    RaffleError::InvalidOracleAccount,
+    RaffleError::StaleRandomness,
+    RaffleError::TokenAccountsRequired,
+    RaffleError::InvalidTicketMint,
+    RaffleError::InvalidEndTime,
+    RaffleError::RaffleEnded,
+    RaffleError::RaffleNotRefundable,
+    RaffleError::RaffleCancelled,
+    RaffleError::InvalidRefundAccounts,
+    RaffleError::InvalidStatusTransition,
+    RaffleError::ExpiryNotReached,
+    RaffleError::InvalidLegacyAccount,
+    RaffleError::InvalidFee,
+    RaffleError::InvalidTokenAccount,
+    RaffleError::InvalidPrizeTiers,
+    RaffleError::NotEnoughEntries,
+    RaffleError::TierAlreadyClaimed,
+    RaffleError::InvalidQuantity,
+    RaffleError::TooManyRanges,
+    RaffleError::WalletCapReached,
+    RaffleError::NotAllowlisted,
+    RaffleError::PrizeAccountsRequired,
+    RaffleError::InvalidPrizeMint,
+    RaffleError::InvalidPrizeAmount,
+    RaffleError::PrizeUnclaimed,
+    RaffleError::RaffleNotSettled,
+    RaffleError::InvalidCrankReward,
+    RaffleError::InvalidSeriesId,
+    RaffleError::InvalidRoundDuration,
+    RaffleError::PreviousRoundRequired,
+    RaffleError::RoundNotFinished,
+    RaffleError::ProtocolPaused,
+    RaffleError::RafflePaused,
+    RaffleError::TransferRestricted,
+    RaffleError::InvalidListingPrice,
+    RaffleError::ListingStale,
+    RaffleError::ListingPriceChanged,
+    RaffleError::RegistryPageFull,
+    RaffleError::RaffleNotListed,
+    RaffleError::RegistryPageRequired,
+    RaffleError::TitleTooLong,
+    RaffleError::DescriptionTooLong,
+    RaffleError::UriTooLong,
+    RaffleError::TooManyTags,
+    RaffleError::TagTooLong,
+    RaffleError::MetadataAccountsRequired,
+    RaffleError::MetadataLocked,
+    RaffleError::InvalidMinEntries,
+    RaffleError::InvalidPricingMode,
+    RaffleError::OpenAccountsRemaining,
+    RaffleError::RandomnessTooEarly,
+    RaffleError::InvalidOracleSlot,
+    RaffleError::RaffleDrawing,
+    RaffleError::NoProceedsToWithdraw,
+    RaffleError::NoPrizeToRoll,
+    RaffleError::UnsupportedMint,
+    RaffleError::InvalidCloseAccounts,
+    RaffleError::InvalidRaffleId,
+];
+
+/// Maps a custom program error code back to the `RaffleError` it came from.
+pub fn decode_error_code(code: u32) -> Option<RaffleError> {
+    RAFFLE_ERRORS
+        .iter()
+        .copied()
+        .find(|&error| u32::from(error) == code)
+}
+
+pub fn decode_program_error(error: &ProgramError) -> Option<RaffleError> {
+    match error {
+        ProgramError::Custom(code) => decode_error_code(*code),
+        _ => None,
+    }
+}
+
+pub fn decode_instruction_error(error: &InstructionError) -> Option<RaffleError> {
+    match error {
+        InstructionError::Custom(code) => decode_error_code(*code),
+        _ => None,
+    }
+}
@@ -0,0 +1,156 @@
+//! Instruction builders for raffles priced in lamports. Raffles priced in SPL
+//! tokens or with an escrowed prize asset take extra accounts that these
+//! builders leave unset.
+
+use anchor_lang::{
+    prelude::Pubkey,
+    solana_program::instruction::Instruction,
+    system_program,
+    InstructionData,
+    ToAccountMetas,
+};
+
+use crate::{
+    config_pda, global_registry_pda, metadata_pda, participant_pda, raffle_pda, registry_page_pda,
+    registry_pda, ticket_pda, CreateRaffleArgs, Raffle, PROGRAM_ID,
+};
+
+/// Creates a raffle and lists it on the global registry, along with its
+/// metadata account when `args.metadata` is set. `raffle_id` must be the
+/// owner's `RaffleRegistry::next_raffle_id`, "0" for their first raffle.
+/// `registry_page` is the `current_page` of the `GlobalRegistry` account, or
+/// zero before the first raffle is created.
+pub fn create_raffle(
+    owner: &Pubkey,
+    raffle_id: &str,
+    args: CreateRaffleArgs,
+    registry_page: u32,
+) -> Instruction {
+    let raffle = raffle_pda(owner, raffle_id).0;
+    let accounts = raffle::accounts::CreateRaffle {
+        owner: *owner,
+        raffle,
+        config: config_pda().0,
+        registry: registry_pda(owner).0,
+        global_registry: global_registry_pda().0,
+        registry_page: registry_page_pda(registry_page).0,
+        metadata: args.metadata.as_ref().map(|_| metadata_pda(&raffle).0),
+        ticket_mint: None,
+        escrow: None,
+        token_program: None,
+        prize_mint: None,
+        owner_prize_account: None,
+        prize_vault: None,
+        prize_token_program: None,
+        system_program: system_program::ID,
+    };
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: accounts.to_account_metas(None),
+        data: raffle::instruction::CreateRaffle {
+            raffle_id: raffle_id.to_string(),
+            args,
+        }
+        .data(),
+    }
+}
+
+/// Buys the next ticket of `raffle`, whose current state is needed to derive
+/// the address of the ticket. Pass an empty `proof` unless the raffle has an
+/// allowlist. The registry page is only passed for the last ticket, which
+/// takes the raffle off the registry.
+pub fn buy_ticket(buyer: &Pubkey, raffle: &Raffle, proof: Vec<[u8; 32]>) -> Instruction {
+    let raffle_address = raffle_pda(&raffle.owner, &raffle.raffle_id).0;
+    let sells_out = raffle.entries + 1 == raffle.max_entries;
+    let accounts = raffle::accounts::BuyTicket {
+        buyer: *buyer,
+        ticket: ticket_pda(&raffle_address, raffle.next_ticket_id()).0,
+        participant: participant_pda(&raffle_address, buyer).0,
+        raffle: raffle_address,
+        config: config_pda().0,
+        registry_page: raffle.registry_page
+            .filter(|_| sells_out)
+            .map(|page| registry_page_pda(page).0),
+        ticket_mint: None,
+        buyer_token_account: None,
+        escrow: None,
+        token_program: None,
+        system_program: system_program::ID,
+    };
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: accounts.to_account_metas(None),
+        data: raffle::instruction::BuyTicket {
+            raffle_id: raffle.raffle_id.clone(),
+            proof,
+        }
+        .data(),
+    }
+}
+
+/// Draws the winners of `raffle`, or expires it if it ended short of its
+/// minimum entries, passing the registry page still listing it.
+pub fn pick_winner(caller: &Pubkey, raffle: &Raffle) -> Instruction {
+    let accounts = raffle::accounts::PickWinner {
+        caller: *caller,
+        raffle: raffle_pda(&raffle.owner, &raffle.raffle_id).0,
+        config: config_pda().0,
+        registry_page: raffle.registry_page.map(|page| registry_page_pda(page).0),
+        ticket_mint: None,
+        caller_token_account: None,
+        escrow: None,
+        token_program: None,
+    };
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: accounts.to_account_metas(None),
+        data: raffle::instruction::PickWinner {
+            raffle_id: raffle.raffle_id.clone(),
+        }
+        .data(),
+    }
+}
+
+/// Claims the prize won by `ticket_id`. `treasury` is the protocol treasury
+/// stored in the program config.
+pub fn claim_prize(
+    winner: &Pubkey,
+    raffle_owner: &Pubkey,
+    raffle_id: &str,
+    ticket_id: u32,
+    treasury: &Pubkey,
+) -> Instruction {
+    let raffle_address = raffle_pda(raffle_owner, raffle_id).0;
+    let accounts = raffle::accounts::ClaimPrize {
+        winner: *winner,
+        raffle: raffle_address,
+        ticket: Some(ticket_pda(&raffle_address, ticket_id).0),
+        entry: None,
+        config: config_pda().0,
+        owner: *raffle_owner,
+        treasury: *treasury,
+        ticket_mint: None,
+        winner_token_account: None,
+        owner_token_account: None,
+        treasury_token_account: None,
+        escrow: None,
+        token_program: None,
+        prize_mint: None,
+        winner_prize_account: None,
+        prize_vault: None,
+        prize_token_program: None,
+        system_program: system_program::ID,
+    };
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: accounts.to_account_metas(None),
+        data: raffle::instruction::ClaimPrize {
+            raffle_id: raffle_id.to_string(),
+        }
+        .data(),
+    }
+}
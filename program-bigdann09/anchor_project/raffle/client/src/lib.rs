@@ -0,0 +1,22 @@
+//! Rust client for the raffle program: PDA derivation, instruction builders,
+//! typed account decoding and error decoding.
+
+pub mod accounts;
+pub mod error;
+pub mod instructions;
+pub mod pda;
+
+pub use accounts::*;
+pub use error::*;
+pub use instructions::*;
+pub use pda::*;
+
+pub use raffle::{
+    error::RaffleError,
+    instructions::{CreateRaffleArgs, CreateSeriesArgs},
+    state::{
+        GlobalRegistry, Listing, PricingMode, RandomnessSource, Raffle, RaffleCategory, RaffleMetadata,
+        RaffleMetadataArgs, RaffleRegistry, RaffleSeries, RaffleStatus, RegistryPage, Ticket,
+    },
+    ID as PROGRAM_ID,
+};
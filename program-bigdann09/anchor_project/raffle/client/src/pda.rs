@@ -0,0 +1,63 @@
+use anchor_lang::prelude::Pubkey;
+
+use crate::{RaffleRegistry, PROGRAM_ID};
+
+pub fn raffle_pda(owner: &Pubkey, raffle_id: &str) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"raffle", owner.as_ref(), raffle_id.as_bytes()],
+        &PROGRAM_ID,
+    )
+}
+
+/// Addresses of every raffle `owner` created with `create_raffle`, oldest
+/// first, derived from the owner's registry counter alone.
+pub fn owner_raffle_pdas(owner: &Pubkey, registry: &RaffleRegistry) -> Vec<Pubkey> {
+    (0..registry.raffles_created)
+        .map(|raffle_id| raffle_pda(owner, &raffle_id.to_string()).0)
+        .collect()
+}
+
+pub fn ticket_pda(raffle: &Pubkey, ticket_id: u32) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"ticket", raffle.as_ref(), &ticket_id.to_le_bytes()],
+        &PROGRAM_ID,
+    )
+}
+
+pub fn config_pda() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"config"], &PROGRAM_ID)
+}
+
+pub fn participant_pda(raffle: &Pubkey, buyer: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"participant", raffle.as_ref(), buyer.as_ref()],
+        &PROGRAM_ID,
+    )
+}
+
+pub fn series_pda(owner: &Pubkey, series_id: &str) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"series", owner.as_ref(), series_id.as_bytes()],
+        &PROGRAM_ID,
+    )
+}
+
+pub fn listing_pda(ticket: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"listing", ticket.as_ref()], &PROGRAM_ID)
+}
+
+pub fn registry_pda(owner: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"registry", owner.as_ref()], &PROGRAM_ID)
+}
+
+pub fn global_registry_pda() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"global_registry"], &PROGRAM_ID)
+}
+
+pub fn registry_page_pda(page: u32) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"registry_page", &page.to_le_bytes()], &PROGRAM_ID)
+}
+
+pub fn metadata_pda(raffle: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"metadata", raffle.as_ref()], &PROGRAM_ID)
+}
@@ -0,0 +1,228 @@
+use std::collections::HashMap;
+
+use anchor_lang::{
+    prelude::Pubkey,
+    solana_program::instruction::InstructionError,
+    AccountSerialize,
+    Discriminator,
+};
+use raffle_client::*;
+
+fn raffle_state(owner: Pubkey, raffle_id: &str, entries: u32) -> Raffle {
+    Raffle {
+        owner,
+        raffle_id: raffle_id.to_string(),
+        ticket_price: 100_000_000,
+        max_entries: 10,
+        entries,
+        status: RaffleStatus::Open,
+        prize_tiers: vec![10_000],
+        winners: Vec::new(),
+        created_at: 0,
+        bump: raffle_pda(&owner, raffle_id).1,
+        randomness_source: RandomnessSource::SlotHash,
+        entropy: [0; 32],
+        sold_out_at: None,
+        ticket_mint: None,
+        end_time: None,
+        creator_fee_bps: 0,
+        protocol_fee_bps: 0,
+        max_tickets_per_wallet: None,
+        allowlist_root: None,
+        prize_mint: None,
+        prize_amount: 0,
+        refunded_entries: 0,
+        crank_reward_bps: 0,
+        crank_reward_paid: 0,
+        paused: false,
+        registry_page: None,
+        min_entries: 0,
+        pricing: PricingMode::Fixed,
+        ticket_sales: 0,
+        open_accounts: 0,
+        last_ticket_slot: 0,
+        proceeds_withdrawn: false,
+    }
+}
+
+fn account_data<T: AccountSerialize>(account: &T) -> Vec<u8> {
+    let mut data = Vec::new();
+    account.try_serialize(&mut data).unwrap();
+    data
+}
+
+struct MapFetcher(HashMap<Pubkey, Vec<u8>>);
+
+impl AccountFetcher for MapFetcher {
+    type Error = String;
+
+    fn fetch_account_data(&self, address: &Pubkey) -> Result<Option<Vec<u8>>, String> {
+        Ok(self.0.get(address).cloned())
+    }
+}
+
+#[test]
+fn buy_ticket_targets_the_next_ticket_address() {
+    let owner = Pubkey::new_unique();
+    let buyer = Pubkey::new_unique();
+    let raffle = raffle_state(owner, "weekly", 3);
+    let raffle_address = raffle_pda(&owner, "weekly").0;
+
+    let ix = buy_ticket(&buyer, &raffle, Vec::new());
+
+    assert_eq!(ix.program_id, PROGRAM_ID);
+    assert_eq!(ix.accounts[0].pubkey, buyer);
+    assert!(ix.accounts[0].is_signer);
+    assert_eq!(ix.accounts[1].pubkey, ticket_pda(&raffle_address, 4).0);
+    assert_eq!(ix.accounts[2].pubkey, participant_pda(&raffle_address, &buyer).0);
+    assert_eq!(ix.accounts[3].pubkey, raffle_address);
+    assert!(ix.accounts[3].is_writable);
+    assert!(ix.data.starts_with(raffle::instruction::BuyTicket::DISCRIMINATOR));
+}
+
+#[test]
+fn buy_ticket_passes_the_registry_page_for_the_last_ticket() {
+    let owner = Pubkey::new_unique();
+    let buyer = Pubkey::new_unique();
+    let mut raffle = raffle_state(owner, "weekly", 8);
+    raffle.registry_page = Some(2);
+
+    let ix = buy_ticket(&buyer, &raffle, Vec::new());
+    assert_eq!(ix.accounts[5].pubkey, PROGRAM_ID);
+
+    raffle.entries = 9;
+    let ix = buy_ticket(&buyer, &raffle, Vec::new());
+    assert_eq!(ix.accounts[5].pubkey, registry_page_pda(2).0);
+    assert!(ix.accounts[5].is_writable);
+}
+
+#[test]
+fn builders_fill_unused_optional_accounts_with_the_program_id() {
+    let owner = Pubkey::new_unique();
+    let raffle = raffle_state(owner, "weekly", 3);
+    let ix = pick_winner(&Pubkey::new_unique(), &raffle);
+
+    assert_eq!(ix.accounts.len(), 8);
+    assert_eq!(ix.accounts[1].pubkey, raffle_pda(&owner, "weekly").0);
+    assert_eq!(ix.accounts[2].pubkey, config_pda().0);
+    assert!(ix.accounts[3..].iter().all(|meta| meta.pubkey == PROGRAM_ID));
+    assert!(ix.data.starts_with(raffle::instruction::PickWinner::DISCRIMINATOR));
+}
+
+#[test]
+fn claim_prize_passes_the_ticket_owner_and_treasury() {
+    let owner = Pubkey::new_unique();
+    let winner = Pubkey::new_unique();
+    let treasury = Pubkey::new_unique();
+    let raffle_address = raffle_pda(&owner, "weekly").0;
+
+    let ix = claim_prize(&winner, &owner, "weekly", 7, &treasury);
+
+    let keys: Vec<Pubkey> = ix.accounts.iter().map(|meta| meta.pubkey).collect();
+    assert_eq!(keys[..7], [
+        winner,
+        raffle_address,
+        ticket_pda(&raffle_address, 7).0,
+        PROGRAM_ID,
+        config_pda().0,
+        owner,
+        treasury,
+    ]);
+}
+
+#[test]
+fn create_raffle_encodes_its_arguments() {
+    let owner = Pubkey::new_unique();
+    let args = CreateRaffleArgs {
+        ticket_price: 100_000_000,
+        pricing: PricingMode::Fixed,
+        max_entries: 10,
+        min_entries: 0,
+        end_time: None,
+        randomness_source: RandomnessSource::SlotHash,
+        creator_fee_bps: 0,
+        prize_tiers: vec![10_000],
+        max_tickets_per_wallet: None,
+        allowlist_root: None,
+        prize_amount: 0,
+        crank_reward_bps: 0,
+        metadata: None,
+    };
+
+    let ix = create_raffle(&owner, "weekly", args.clone(), 2);
+
+    assert_eq!(ix.accounts[1].pubkey, raffle_pda(&owner, "weekly").0);
+    assert_eq!(ix.accounts[3].pubkey, registry_pda(&owner).0);
+    assert_eq!(ix.accounts[5].pubkey, registry_page_pda(2).0);
+    let expected = raffle::instruction::CreateRaffle {
+        raffle_id: "weekly".to_string(),
+        args,
+    };
+    assert_eq!(ix.data, anchor_lang::InstructionData::data(&expected));
+}
+
+#[test]
+fn fetches_and_decodes_typed_accounts() {
+    let owner = Pubkey::new_unique();
+    let raffle_address = raffle_pda(&owner, "weekly").0;
+    let ticket_address = ticket_pda(&raffle_address, 1).0;
+    let ticket = Ticket {
+        raffle: raffle_address,
+        ticket_id: 1,
+        owner: Pubkey::new_unique(),
+        bump: ticket_pda(&raffle_address, 1).1,
+        price_paid: 100_000_000,
+    };
+    let fetcher = MapFetcher(HashMap::from([
+        (raffle_address, account_data(&raffle_state(owner, "weekly", 1))),
+        (ticket_address, account_data(&ticket)),
+    ]));
+
+    let raffle = fetch_raffle(&fetcher, &raffle_address).unwrap();
+    assert_eq!(raffle.raffle_id, "weekly");
+    assert_eq!(raffle.entries, 1);
+    assert_eq!(fetch_ticket(&fetcher, &ticket_address).unwrap().owner, ticket.owner);
+
+    // the discriminator keeps one account type from decoding as another
+    assert!(decode_raffle(&account_data(&ticket)).is_err());
+    assert!(matches!(
+        fetch_ticket(&fetcher, &Pubkey::new_unique()),
+        Err(ClientError::AccountNotFound(_))
+    ));
+}
+
+#[test]
+fn decodes_custom_error_codes() {
+    for (index, error) in RAFFLE_ERRORS.iter().enumerate() {
+        assert_eq!(u32::from(*error), 6000 + index as u32);
+    }
+
+    let code = |error: Option<RaffleError>| error.map(u32::from);
+    assert_eq!(code(decode_error_code(6000)), Some(RaffleError::InvalidTicketPrice.into()));
+    assert_eq!(
+        code(decode_instruction_error(&InstructionError::Custom(
+            RaffleError::WalletCapReached.into()
+        ))),
+        Some(RaffleError::WalletCapReached.into())
+    );
+    assert!(decode_error_code(5999).is_none());
+    assert!(decode_error_code(6000 + RAFFLE_ERRORS.len() as u32).is_none());
+    assert!(decode_instruction_error(&InstructionError::InvalidArgument).is_none());
+}
+
+#[test]
+fn decodes_every_program_error() {
+    // variant names of `RaffleError` in declaration order, read from the program
+    // source so a variant missing from `RAFFLE_ERRORS` fails here
+    let variants: Vec<&str> = include_str!("../../programs/raffle/src/error.rs")
+        .lines()
+        .map(|line| line.trim().trim_end_matches(','))
+        .filter(|name| name.starts_with(char::is_uppercase) && name.chars().all(char::is_alphanumeric))
+        .collect();
+
+    assert_eq!(RAFFLE_ERRORS.len(), variants.len());
+    for (index, name) in variants.into_iter().enumerate() {
+        let error = decode_error_code(6000 + index as u32).unwrap();
+        assert_eq!(format!("{error:?}"), name);
+    }
+}
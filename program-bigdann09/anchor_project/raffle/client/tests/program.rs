@@ -0,0 +1,2351 @@
+//! Runs the raffle program in process through the `svm` harness. The clock
+//! is moved explicitly, so time based rules are tested deterministically and
+//! no validator or network is involved.
+
+mod svm;
+
+use std::collections::HashMap;
+
+use anchor_lang::{
+    error::ErrorCode,
+    prelude::*,
+    solana_program::{hash::hash, instruction::{AccountMeta, Instruction}, program_pack::Pack},
+    system_program,
+    Discriminator,
+    InstructionData,
+    Space,
+    ToAccountMetas,
+};
+use raffle::{
+    events::{
+        PauseUpdated, PrizeClaimed, PrizeReclaimed, ProceedsWithdrawn, RaffleCancelled, RaffleCreated, RaffleExpired, Refunded,
+        RoundStarted,
+        TicketListed, TicketPurchased, TicketTransferred, WinnerPicked,
+    },
+    instructions::{LegacyRaffle, LegacyTicket},
+    merkle::MerkleTree,
+    state::{Config, Entry, OracleConfig, Participant},
+    DRAW_TIMEOUT,
+    MAX_DESCRIPTION_LEN,
+    MAX_TAGS,
+    MAX_TAG_LEN,
+    MAX_TITLE_LEN,
+    MAX_URI_LEN,
+    RAFFLES_PER_PAGE,
+    REVEAL_TIMEOUT,
+};
+use raffle_client::*;
+use svm::{program_data_address, Account, Receipt, Svm};
+
+const SOL: u64 = 1_000_000_000;
+const TICKET_PRICE: u64 = SOL / 10;
+const PROTOCOL_FEE_BPS: u16 = 100;
+
+type TxResult = std::result::Result<Receipt, ProgramError>;
+
+struct Test {
+    svm: Svm,
+    admin: Pubkey,
+    treasury: Pubkey,
+    owner: Pubkey,
+    buyers: Vec<Pubkey>,
+    // token account of each wallet for the last mint created
+    token_accounts: HashMap<Pubkey, Pubkey>,
+    // sequential ids of the raffles created by the owner, by the name tests give them
+    raffle_ids: HashMap<String, String>,
+}
+
+fn setup() -> Test {
+    let mut svm = Svm::new();
+    let admin = Pubkey::new_unique();
+    let treasury = Pubkey::new_unique();
+    let owner = Pubkey::new_unique();
+    let buyers: Vec<Pubkey> = (0..4).map(|_| Pubkey::new_unique()).collect();
+
+    for wallet in [admin, treasury, owner].iter().chain(&buyers) {
+        svm.airdrop(wallet, 10 * SOL);
+    }
+
+    svm.set_upgrade_authority(Some(admin));
+    svm.process(&initialize_config(admin, treasury)).unwrap();
+
+    Test { svm, admin, treasury, owner, buyers, token_accounts: HashMap::new(), raffle_ids: HashMap::new() }
+}
+
+fn initialize_config(admin: Pubkey, treasury: Pubkey) -> Instruction {
+    let accounts = raffle::accounts::InitializeConfig {
+        admin,
+        config: config_pda().0,
+        program_data: program_data_address(),
+        system_program: system_program::ID,
+    };
+
+    instruction(accounts, raffle::instruction::InitializeConfig {
+        treasury,
+        protocol_fee_bps: PROTOCOL_FEE_BPS,
+    })
+}
+
+fn args(max_entries: u32) -> CreateRaffleArgs {
+    CreateRaffleArgs {
+        ticket_price: TICKET_PRICE,
+        pricing: PricingMode::Fixed,
+        max_entries,
+        min_entries: 0,
+        end_time: None,
+        randomness_source: RandomnessSource::SlotHash,
+        creator_fee_bps: 0,
+        prize_tiers: vec![10_000],
+        max_tickets_per_wallet: None,
+        allowlist_root: None,
+        prize_amount: 0,
+        crank_reward_bps: 0,
+        metadata: None,
+    }
+}
+
+fn metadata_args() -> RaffleMetadataArgs {
+    RaffleMetadataArgs {
+        title: "Weekly draw".to_string(),
+        description: "One winner takes the ticket pool".to_string(),
+        uri: "https://arweave.net/weekly.png".to_string(),
+        category: RaffleCategory::Gaming,
+        tags: vec!["weekly".to_string(), "sol".to_string()],
+    }
+}
+
+fn instruction(accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+fn entry_pda(raffle: &Pubkey, owner: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"entry", raffle.as_ref(), owner.as_ref()], &PROGRAM_ID).0
+}
+
+fn escrow_pda(raffle: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"escrow", raffle.as_ref()], &PROGRAM_ID).0
+}
+
+fn prize_vault_pda(raffle: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"prize_vault", raffle.as_ref()], &PROGRAM_ID).0
+}
+
+fn rent(space: usize) -> u64 {
+    Rent::default().minimum_balance(space)
+}
+
+#[track_caller]
+fn assert_error(result: TxResult, error: impl Into<u32>) {
+    assert_eq!(result.unwrap_err(), ProgramError::Custom(error.into()));
+}
+
+impl Test {
+    /// Id of the raffle a test calls `name`. Raffles not created through
+    /// `create_raffle`, like legacy raffles and series rounds, keep their name.
+    fn raffle_id(&self, name: &str) -> String {
+        self.raffle_ids.get(name).cloned().unwrap_or_else(|| name.to_string())
+    }
+
+    /// Gives `name` the next id in the owner's registry, which the raffle
+    /// created under that name must use.
+    fn next_raffle_id(&mut self, name: &str) -> String {
+        let registry = registry_pda(&self.owner).0;
+        let raffle_id = self.svm
+            .account(&registry)
+            .map_or("0".to_string(), |_| self.svm.get::<RaffleRegistry>(&registry).next_raffle_id());
+        self.raffle_ids.insert(name.to_string(), raffle_id.clone());
+
+        raffle_id
+    }
+
+    fn raffle_address(&self, raffle_id: &str) -> Pubkey {
+        raffle_pda(&self.owner, &self.raffle_id(raffle_id)).0
+    }
+
+    fn raffle(&self, raffle_id: &str) -> Raffle {
+        self.svm.get(&self.raffle_address(raffle_id))
+    }
+
+    /// Page of the global registry new raffles are listed on.
+    fn current_registry_page(&self) -> u32 {
+        self.svm
+            .account(&global_registry_pda().0)
+            .map_or(0, |_| self.svm.get::<GlobalRegistry>(&global_registry_pda().0).current_page)
+    }
+
+    fn registry_page(&self, page: u32) -> RegistryPage {
+        self.svm.get(&registry_page_pda(page).0)
+    }
+
+    /// Address of the registry page still listing the raffle, if any.
+    fn listed_on(&self, raffle_id: &str) -> Option<Pubkey> {
+        self.raffle(raffle_id).registry_page.map(|page| registry_page_pda(page).0)
+    }
+
+    fn create_raffle(&mut self, raffle_id: &str, args: CreateRaffleArgs) -> TxResult {
+        let raffle_id = self.next_raffle_id(raffle_id);
+        let registry_page = self.current_registry_page();
+        self.svm.process(&create_raffle(&self.owner, &raffle_id, args, registry_page))
+    }
+
+    fn buy(&mut self, buyer: Pubkey, raffle_id: &str) -> TxResult {
+        let raffle = self.raffle(raffle_id);
+        self.svm.process(&buy_ticket(&buyer, &raffle, Vec::new()))
+    }
+
+    fn buy_tickets(&mut self, buyer: Pubkey, raffle_id: &str, quantity: u32) -> TxResult {
+        let raffle = self.raffle_address(raffle_id);
+        let accounts = raffle::accounts::BuyTickets {
+            buyer,
+            entry: entry_pda(&raffle, &buyer),
+            participant: participant_pda(&raffle, &buyer).0,
+            raffle,
+            config: config_pda().0,
+            registry_page: self.listed_on(raffle_id),
+            ticket_mint: None,
+            buyer_token_account: None,
+            escrow: None,
+            token_program: None,
+            system_program: system_program::ID,
+        };
+
+        self.svm.process(&instruction(accounts, raffle::instruction::BuyTickets {
+            raffle_id: self.raffle_id(raffle_id),
+            quantity,
+            proof: Vec::new(),
+        }))
+    }
+
+    /// Creates a raffle and has the first `max_entries` buyers sell it out.
+    fn sold_out_raffle(&mut self, raffle_id: &str, args: CreateRaffleArgs) {
+        let max_entries = args.max_entries as usize;
+        self.create_raffle(raffle_id, args).unwrap();
+        for buyer in self.buyers.clone().into_iter().take(max_entries) {
+            self.buy(buyer, raffle_id).unwrap();
+        }
+    }
+
+    fn pick_winner(&mut self, caller: Pubkey, raffle_id: &str) -> TxResult {
+        let raffle = self.raffle(raffle_id);
+        self.svm.process(&pick_winner(&caller, &raffle))
+    }
+
+    /// Returns the winning ticket of `tier` and the wallet holding it.
+    fn winner(&self, raffle_id: &str, tier: usize) -> (u32, Pubkey) {
+        let ticket_id = self.raffle(raffle_id).winners[tier].ticket_id;
+        let ticket: Ticket = self.svm.get(&ticket_pda(&self.raffle_address(raffle_id), ticket_id).0);
+
+        (ticket_id, ticket.owner)
+    }
+
+    fn claim(&mut self, winner: Pubkey, raffle_id: &str, ticket_id: u32) -> TxResult {
+        self.svm.process(&claim_prize(&winner, &self.owner, &self.raffle_id(raffle_id), ticket_id, &self.treasury))
+    }
+
+    fn cancel(&mut self, raffle_id: &str) -> TxResult {
+        let accounts = raffle::accounts::CancelRaffle {
+            owner: self.owner,
+            raffle: self.raffle_address(raffle_id),
+            registry_page: self.listed_on(raffle_id),
+        };
+
+        self.svm.process(&instruction(accounts, raffle::instruction::CancelRaffle {
+            raffle_id: self.raffle_id(raffle_id),
+        }))
+    }
+
+    fn refund_ticket(&mut self, owner: Pubkey, raffle_id: &str, ticket_id: u32) -> TxResult {
+        let raffle = self.raffle_address(raffle_id);
+        let accounts = raffle::accounts::RefundTicket {
+            owner,
+            raffle,
+            ticket: Some(ticket_pda(&raffle, ticket_id).0),
+            entry: None,
+            ticket_mint: None,
+            owner_token_account: None,
+            escrow: None,
+            token_program: None,
+            system_program: system_program::ID,
+        };
+
+        self.svm.process(&instruction(accounts, raffle::instruction::RefundTicket {
+            raffle_id: self.raffle_id(raffle_id),
+        }))
+    }
+
+    fn close_ticket(&mut self, owner: Pubkey, raffle_id: &str, ticket_id: u32) -> TxResult {
+        let raffle = self.raffle_address(raffle_id);
+        let accounts = raffle::accounts::CloseTicket {
+            owner,
+            raffle,
+            ticket: ticket_pda(&raffle, ticket_id).0,
+            ticket_mint: None,
+            owner_token_account: None,
+            escrow: None,
+            token_program: None,
+        };
+
+        self.svm.process(&instruction(accounts, raffle::instruction::CloseTicket {
+            raffle_id: self.raffle_id(raffle_id),
+        }))
+    }
+
+    fn close_entry(&mut self, owner: Pubkey, raffle_id: &str) -> TxResult {
+        let raffle = self.raffle_address(raffle_id);
+        let accounts = raffle::accounts::CloseEntry {
+            owner,
+            raffle,
+            entry: entry_pda(&raffle, &owner),
+            ticket_mint: None,
+            owner_token_account: None,
+            escrow: None,
+            token_program: None,
+        };
+
+        self.svm.process(&instruction(accounts, raffle::instruction::CloseEntry {
+            raffle_id: self.raffle_id(raffle_id),
+        }))
+    }
+
+    fn close_participant(&mut self, owner: Pubkey, raffle_id: &str) -> TxResult {
+        let raffle = self.raffle_address(raffle_id);
+        let accounts = raffle::accounts::CloseParticipant {
+            owner,
+            raffle,
+            participant: participant_pda(&raffle, &owner).0,
+        };
+
+        self.svm.process(&instruction(accounts, raffle::instruction::CloseParticipant {
+            raffle_id: self.raffle_id(raffle_id),
+        }))
+    }
+
+    fn close_raffle(&mut self, raffle_id: &str) -> TxResult {
+        let accounts = raffle::accounts::CloseRaffle {
+            owner: self.owner,
+            raffle: self.raffle_address(raffle_id),
+            registry_page: self.listed_on(raffle_id),
+            metadata: metadata_pda(&self.raffle_address(raffle_id)).0,
+            ticket_mint: None,
+            owner_token_account: None,
+            escrow: None,
+            token_program: None,
+        };
+
+        self.svm.process(&instruction(accounts, raffle::instruction::CloseRaffle {
+            raffle_id: self.raffle_id(raffle_id),
+        }))
+    }
+
+    fn expire_raffle(&mut self, raffle_id: &str) -> TxResult {
+        let accounts = raffle::accounts::ExpireRaffle {
+            caller: self.buyers[0],
+            raffle: self.raffle_address(raffle_id),
+            config: config_pda().0,
+            registry_page: self.listed_on(raffle_id),
+        };
+
+        self.svm.process(&instruction(accounts, raffle::instruction::ExpireRaffle {
+            raffle_id: self.raffle_id(raffle_id),
+        }))
+    }
+
+    fn reveal_winner(&mut self, raffle_id: &str, secret: [u8; 32]) -> TxResult {
+        let accounts = raffle::accounts::RevealWinner {
+            owner: self.owner,
+            raffle: self.raffle_address(raffle_id),
+            config: config_pda().0,
+            registry_page: self.listed_on(raffle_id),
+        };
+
+        self.svm.process(&instruction(accounts, raffle::instruction::RevealWinner {
+            raffle_id: self.raffle_id(raffle_id),
+            secret,
+        }))
+    }
+
+    fn expire_reveal(&mut self, raffle_id: &str) -> TxResult {
+        let accounts = raffle::accounts::ExpireReveal {
+            caller: self.buyers[0],
+            raffle: self.raffle_address(raffle_id),
+            config: config_pda().0,
+            registry_page: self.listed_on(raffle_id),
+            ticket_mint: None,
+            caller_token_account: None,
+            escrow: None,
+            token_program: None,
+        };
+
+        self.svm.process(&instruction(accounts, raffle::instruction::ExpireReveal {
+            raffle_id: self.raffle_id(raffle_id),
+        }))
+    }
+
+    fn transfer_ticket(&mut self, owner: Pubkey, raffle_id: &str, ticket_id: u32, new_owner: Pubkey) -> TxResult {
+        let raffle = self.raffle_address(raffle_id);
+        let accounts = raffle::accounts::TransferTicket {
+            owner,
+            raffle,
+            config: config_pda().0,
+            ticket: ticket_pda(&raffle, ticket_id).0,
+            listing: listing_pda(&ticket_pda(&raffle, ticket_id).0).0,
+        };
+
+        self.svm.process(&instruction(accounts, raffle::instruction::TransferTicket {
+            raffle_id: self.raffle_id(raffle_id),
+            new_owner,
+        }))
+    }
+
+    fn list_ticket(&mut self, owner: Pubkey, raffle_id: &str, ticket_id: u32, price: u64) -> TxResult {
+        let raffle = self.raffle_address(raffle_id);
+        let ticket = ticket_pda(&raffle, ticket_id).0;
+        let accounts = raffle::accounts::ListTicket {
+            owner,
+            raffle,
+            config: config_pda().0,
+            ticket,
+            listing: listing_pda(&ticket).0,
+            system_program: system_program::ID,
+        };
+
+        self.svm.process(&instruction(accounts, raffle::instruction::ListTicket {
+            raffle_id: self.raffle_id(raffle_id),
+            price,
+        }))
+    }
+
+    fn buy_listed_ticket(
+        &mut self,
+        buyer: Pubkey,
+        seller: Pubkey,
+        raffle_id: &str,
+        ticket_id: u32,
+        price: u64,
+    ) -> TxResult {
+        let raffle = self.raffle_address(raffle_id);
+        let ticket = ticket_pda(&raffle, ticket_id).0;
+        let accounts = raffle::accounts::BuyListedTicket {
+            buyer,
+            seller,
+            raffle,
+            config: config_pda().0,
+            ticket,
+            listing: listing_pda(&ticket).0,
+            system_program: system_program::ID,
+        };
+
+        self.svm.process(&instruction(accounts, raffle::instruction::BuyListedTicket {
+            raffle_id: self.raffle_id(raffle_id),
+            price,
+        }))
+    }
+
+    fn update_metadata(&mut self, owner: Pubkey, raffle_id: &str, args: RaffleMetadataArgs) -> TxResult {
+        let raffle = raffle_pda(&owner, &self.raffle_id(raffle_id)).0;
+        let accounts = raffle::accounts::UpdateMetadata {
+            owner,
+            raffle,
+            metadata: metadata_pda(&raffle).0,
+            system_program: system_program::ID,
+        };
+
+        self.svm.process(&instruction(accounts, raffle::instruction::UpdateMetadata {
+            raffle_id: self.raffle_id(raffle_id),
+            args,
+        }))
+    }
+
+    fn set_protocol_paused(&mut self, admin: Pubkey, paused: bool) -> TxResult {
+        let accounts = raffle::accounts::SetProtocolPaused { admin, config: config_pda().0 };
+
+        self.svm.process(&instruction(accounts, raffle::instruction::SetProtocolPaused { paused }))
+    }
+
+    /// Stores a sold out raffle in the layout used before the lifecycle
+    /// status was added, drawn with ticket 2 as the unclaimed winner.
+    fn set_legacy_raffle(&mut self, raffle_id: &str) {
+        let (raffle_address, bump) = raffle_pda(&self.owner, raffle_id);
+        let legacy_len = 8 + LegacyRaffle::INIT_SPACE;
+        let mut data = Raffle::DISCRIMINATOR.to_vec();
+        self.owner.serialize(&mut data).unwrap();
+        raffle_id.to_string().serialize(&mut data).unwrap();
+        (TICKET_PRICE, 3u32, 3u32, false, false, Some(2u32), 42i64, bump).serialize(&mut data).unwrap();
+        data.resize(legacy_len, 0);
+        self.svm.set_account(raffle_address, Account {
+            lamports: rent(legacy_len) + 3 * TICKET_PRICE,
+            data,
+            owner: PROGRAM_ID,
+            executable: false,
+        });
+    }
+
+    fn set_raffle_paused(&mut self, owner: Pubkey, raffle_id: &str, paused: bool) -> TxResult {
+        let accounts = raffle::accounts::SetRafflePaused {
+            owner,
+            raffle: raffle_pda(&owner, &self.raffle_id(raffle_id)).0,
+        };
+
+        self.svm.process(&instruction(accounts, raffle::instruction::SetRafflePaused {
+            raffle_id: self.raffle_id(raffle_id),
+            paused,
+        }))
+    }
+
+    /// Creates a mint of `token_program` and gives every wallet of the test a
+    /// token account holding `amount` tokens.
+    fn create_mint(&mut self, token_program: Pubkey, amount: u64) -> Pubkey {
+        let mint = self.svm.create_mint(&token_program, &self.admin, 6);
+        for wallet in [self.owner, self.treasury].into_iter().chain(self.buyers.clone()) {
+            let account = self.svm.create_token_account(&mint, &wallet, amount);
+            self.token_accounts.insert(wallet, account);
+        }
+
+        mint
+    }
+
+    fn token_balance(&self, wallet: &Pubkey) -> u64 {
+        self.svm.token_balance(&self.token_accounts[wallet])
+    }
+
+    fn token_program(&self, mint: &Pubkey) -> Pubkey {
+        self.svm.account(mint).unwrap().owner
+    }
+
+    /// Creates a raffle priced in `ticket_mint` tokens and or escrowing
+    /// `args.prize_amount` tokens of `prize_mint`.
+    fn create_token_raffle(
+        &mut self,
+        raffle_id: &str,
+        args: CreateRaffleArgs,
+        ticket_mint: Option<Pubkey>,
+        prize_mint: Option<Pubkey>,
+    ) -> TxResult {
+        self.next_raffle_id(raffle_id);
+        let raffle = self.raffle_address(raffle_id);
+        let accounts = raffle::accounts::CreateRaffle {
+            owner: self.owner,
+            raffle,
+            config: config_pda().0,
+            registry: registry_pda(&self.owner).0,
+            global_registry: global_registry_pda().0,
+            registry_page: registry_page_pda(self.current_registry_page()).0,
+            metadata: None,
+            ticket_mint,
+            escrow: ticket_mint.map(|_| escrow_pda(&raffle)),
+            token_program: ticket_mint.map(|mint| self.token_program(&mint)),
+            prize_mint,
+            owner_prize_account: prize_mint.map(|_| self.token_accounts[&self.owner]),
+            prize_vault: prize_mint.map(|_| prize_vault_pda(&raffle)),
+            prize_token_program: prize_mint.map(|mint| self.token_program(&mint)),
+            system_program: system_program::ID,
+        };
+
+        self.svm.process(&instruction(accounts, raffle::instruction::CreateRaffle {
+            raffle_id: self.raffle_id(raffle_id),
+            args,
+        }))
+    }
+
+    fn buy_with_tokens(&mut self, buyer: Pubkey, raffle_id: &str) -> TxResult {
+        let raffle = self.raffle(raffle_id);
+        let raffle_address = self.raffle_address(raffle_id);
+        let mint = raffle.ticket_mint.unwrap();
+        let accounts = raffle::accounts::BuyTicket {
+            buyer,
+            ticket: ticket_pda(&raffle_address, raffle.next_ticket_id()).0,
+            participant: participant_pda(&raffle_address, &buyer).0,
+            raffle: raffle_address,
+            config: config_pda().0,
+            registry_page: self.listed_on(raffle_id),
+            ticket_mint: Some(mint),
+            buyer_token_account: Some(self.token_accounts[&buyer]),
+            escrow: Some(escrow_pda(&raffle_address)),
+            token_program: Some(self.token_program(&mint)),
+            system_program: system_program::ID,
+        };
+
+        self.svm.process(&instruction(accounts, raffle::instruction::BuyTicket {
+            raffle_id: self.raffle_id(raffle_id),
+            proof: Vec::new(),
+        }))
+    }
+
+    /// Claims with the token accounts of the ticket mint and prize mint the
+    /// raffle uses.
+    fn claim_with_tokens(&mut self, winner: Pubkey, raffle_id: &str, ticket_id: u32) -> TxResult {
+        let raffle = self.raffle(raffle_id);
+        let raffle_address = self.raffle_address(raffle_id);
+        let (ticket_mint, prize_mint) = (raffle.ticket_mint, raffle.prize_mint);
+        let accounts = raffle::accounts::ClaimPrize {
+            winner,
+            raffle: raffle_address,
+            ticket: Some(ticket_pda(&raffle_address, ticket_id).0),
+            entry: None,
+            config: config_pda().0,
+            owner: self.owner,
+            treasury: self.treasury,
+            ticket_mint,
+            winner_token_account: ticket_mint.map(|_| self.token_accounts[&winner]),
+            owner_token_account: ticket_mint.map(|_| self.token_accounts[&self.owner]),
+            treasury_token_account: ticket_mint.map(|_| self.token_accounts[&self.treasury]),
+            escrow: ticket_mint.map(|_| escrow_pda(&raffle_address)),
+            token_program: ticket_mint.map(|mint| self.token_program(&mint)),
+            prize_mint,
+            winner_prize_account: prize_mint.map(|_| self.token_accounts[&winner]),
+            prize_vault: prize_mint.map(|_| prize_vault_pda(&raffle_address)),
+            prize_token_program: prize_mint.map(|mint| self.token_program(&mint)),
+            system_program: system_program::ID,
+        };
+
+        self.svm.process(&instruction(accounts, raffle::instruction::ClaimPrize {
+            raffle_id: self.raffle_id(raffle_id),
+        }))
+    }
+
+    fn withdraw_proceeds(&mut self, raffle_id: &str) -> TxResult {
+        let raffle_address = self.raffle_address(raffle_id);
+        let ticket_mint = self.raffle(raffle_id).ticket_mint;
+        let accounts = raffle::accounts::WithdrawProceeds {
+            owner: self.owner,
+            raffle: raffle_address,
+            config: config_pda().0,
+            treasury: self.treasury,
+            ticket_mint,
+            owner_token_account: ticket_mint.map(|_| self.token_accounts[&self.owner]),
+            treasury_token_account: ticket_mint.map(|_| self.token_accounts[&self.treasury]),
+            escrow: ticket_mint.map(|_| escrow_pda(&raffle_address)),
+            token_program: ticket_mint.map(|mint| self.token_program(&mint)),
+        };
+
+        self.svm.process(&instruction(accounts, raffle::instruction::WithdrawProceeds {
+            raffle_id: self.raffle_id(raffle_id),
+        }))
+    }
+
+    fn reclaim_prize(&mut self, raffle_id: &str) -> TxResult {
+        let raffle_address = self.raffle_address(raffle_id);
+        let prize_mint = self.raffle(raffle_id).prize_mint;
+        let accounts = raffle::accounts::ReclaimPrize {
+            owner: self.owner,
+            raffle: raffle_address,
+            prize_mint,
+            owner_prize_account: prize_mint.map(|_| self.token_accounts[&self.owner]),
+            prize_vault: prize_mint.map(|_| prize_vault_pda(&raffle_address)),
+            prize_token_program: prize_mint.map(|mint| self.token_program(&mint)),
+        };
+
+        self.svm.process(&instruction(accounts, raffle::instruction::ReclaimPrize {
+            raffle_id: self.raffle_id(raffle_id),
+        }))
+    }
+
+    fn refund_with_tokens(&mut self, owner: Pubkey, raffle_id: &str, ticket_id: u32) -> TxResult {
+        let raffle_address = self.raffle_address(raffle_id);
+        let mint = self.raffle(raffle_id).ticket_mint.unwrap();
+        let accounts = raffle::accounts::RefundTicket {
+            owner,
+            raffle: raffle_address,
+            ticket: Some(ticket_pda(&raffle_address, ticket_id).0),
+            entry: None,
+            ticket_mint: Some(mint),
+            owner_token_account: Some(self.token_accounts[&owner]),
+            escrow: Some(escrow_pda(&raffle_address)),
+            token_program: Some(self.token_program(&mint)),
+            system_program: system_program::ID,
+        };
+
+        self.svm.process(&instruction(accounts, raffle::instruction::RefundTicket {
+            raffle_id: self.raffle_id(raffle_id),
+        }))
+    }
+}
+
+#[test]
+fn create_raffle_stores_the_raffle() {
+    let mut test = setup();
+    let end_time = test.svm.clock().unix_timestamp + 3600;
+
+    let receipt = test
+        .create_raffle("weekly", CreateRaffleArgs { end_time: Some(end_time), ..args(3) })
+        .unwrap();
+
+    let raffle = test.raffle("weekly");
+    assert_eq!(raffle.owner, test.owner);
+    assert_eq!(raffle.status, RaffleStatus::Open);
+    assert_eq!(raffle.max_entries, 3);
+    assert_eq!(raffle.end_time, Some(end_time));
+    assert_eq!(raffle.protocol_fee_bps, PROTOCOL_FEE_BPS);
+    assert_eq!(raffle.created_at, test.svm.clock().unix_timestamp);
+    assert_eq!(test.svm.lamports(&test.raffle_address("weekly")), rent(8 + Raffle::INIT_SPACE));
+
+    let events = receipt.events::<RaffleCreated>();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].raffle, test.raffle_address("weekly"));
+    assert_eq!(events[0].raffle_id, "0");
+}
+
+#[test]
+fn create_raffle_rejects_invalid_arguments() {
+    let mut test = setup();
+    let now = test.svm.clock().unix_timestamp;
+
+    let cases = [
+        (CreateRaffleArgs { ticket_price: 0, ..args(3) }, RaffleError::InvalidTicketPrice),
+        (args(0), RaffleError::InvalidMaxEntries),
+        (CreateRaffleArgs { min_entries: 4, ..args(3) }, RaffleError::InvalidMinEntries),
+        (CreateRaffleArgs { max_tickets_per_wallet: Some(0), ..args(3) }, RaffleError::InvalidMaxEntries),
+        (CreateRaffleArgs { prize_tiers: vec![6_000, 3_000], ..args(3) }, RaffleError::InvalidPrizeTiers),
+        (CreateRaffleArgs { prize_tiers: vec![5_000, 5_000], ..args(1) }, RaffleError::InvalidPrizeTiers),
+        (CreateRaffleArgs { creator_fee_bps: 9_950, ..args(3) }, RaffleError::InvalidFee),
+        (CreateRaffleArgs { crank_reward_bps: 101, ..args(3) }, RaffleError::InvalidCrankReward),
+        (CreateRaffleArgs { end_time: Some(now), ..args(3) }, RaffleError::InvalidEndTime),
+        (CreateRaffleArgs { prize_amount: 1, ..args(3) }, RaffleError::PrizeAccountsRequired),
+        (
+            CreateRaffleArgs { pricing: PricingMode::Stepwise { step: 0, increment: 1 }, ..args(3) },
+            RaffleError::InvalidPricingMode,
+        ),
+        (
+            CreateRaffleArgs { pricing: PricingMode::EarlyBird { window: 3600, discount_bps: 10_000 }, ..args(3) },
+            RaffleError::InvalidPricingMode,
+        ),
+    ];
+    for (args, error) in cases {
+        assert_error(test.create_raffle("weekly", args), error);
+    }
+    assert!(test.svm.account(&test.raffle_address("weekly")).is_none());
+}
+
+#[test]
+fn create_raffle_requires_the_next_raffle_id() {
+    let mut test = setup();
+    test.create_raffle("weekly", args(3)).unwrap();
+    let page = test.current_registry_page();
+    let create = |test: &mut Test, raffle_id: &str| {
+        test.svm.process(&create_raffle(&test.owner, raffle_id, args(3), page))
+    };
+
+    // the system program refuses to create an account that already exists
+    assert_eq!(create(&mut test, "0").unwrap_err(), ProgramError::Custom(0));
+    // and ids can not skip ahead or leave the sequence
+    assert_error(create(&mut test, "2"), RaffleError::InvalidRaffleId);
+    assert_error(create(&mut test, "weekly"), RaffleError::InvalidRaffleId);
+    create(&mut test, "1").unwrap();
+}
+
+#[test]
+fn buy_ticket_pays_the_ticket_price_into_the_raffle() {
+    let mut test = setup();
+    let buyer = test.buyers[0];
+    test.create_raffle("weekly", args(3)).unwrap();
+    let raffle_address = test.raffle_address("weekly");
+    let raffle_balance = test.svm.lamports(&raffle_address);
+
+    let receipt = test.buy(buyer, "weekly").unwrap();
+
+    let ticket: Ticket = test.svm.get(&ticket_pda(&raffle_address, 1).0);
+    assert_eq!(ticket.ticket_id, 1);
+    assert_eq!(ticket.owner, buyer);
+    let participant: Participant = test.svm.get(&participant_pda(&raffle_address, &buyer).0);
+    assert_eq!(participant.tickets_bought, 1);
+    assert_eq!(test.raffle("weekly").entries, 1);
+    assert_eq!(test.svm.lamports(&raffle_address), raffle_balance + TICKET_PRICE);
+    let rents = rent(8 + Ticket::INIT_SPACE) + rent(8 + Participant::INIT_SPACE);
+    assert_eq!(test.svm.lamports(&buyer), 10 * SOL - TICKET_PRICE - rents);
+
+    let events = receipt.events::<TicketPurchased>();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].first_ticket_id, 1);
+    assert_eq!(events[0].amount, TICKET_PRICE);
+}
+
+#[test]
+fn buy_ticket_fails_once_sold_out() {
+    let mut test = setup();
+    test.sold_out_raffle("weekly", args(2));
+
+    let raffle = test.raffle("weekly");
+    assert_eq!(raffle.status, RaffleStatus::Drawing);
+    assert_eq!(raffle.sold_out_at, Some(test.svm.clock().unix_timestamp));
+    assert_error(test.buy(test.buyers[2], "weekly"), RaffleError::RaffleNotActive);
+}
+
+#[test]
+fn buy_tickets_fails_when_more_tickets_are_asked_than_left() {
+    let mut test = setup();
+    test.create_raffle("weekly", args(3)).unwrap();
+    test.buy(test.buyers[0], "weekly").unwrap();
+
+    assert_error(test.buy_tickets(test.buyers[1], "weekly", 3), RaffleError::RaffleSoldOut);
+    assert_error(test.buy_tickets(test.buyers[1], "weekly", 0), RaffleError::InvalidQuantity);
+
+    test.buy_tickets(test.buyers[1], "weekly", 2).unwrap();
+    let entry: Entry = test.svm.get(&entry_pda(&test.raffle_address("weekly"), &test.buyers[1]));
+    assert!(entry.contains(2) && entry.contains(3));
+    assert_eq!(test.raffle("weekly").status, RaffleStatus::Drawing);
+}
+
+#[test]
+fn buy_ticket_fails_without_funds() {
+    let mut test = setup();
+    test.create_raffle("weekly", args(3)).unwrap();
+    // enough for the ticket and participant rent, but not the ticket price
+    let buyer = Pubkey::new_unique();
+    let rents = rent(8 + Ticket::INIT_SPACE) + rent(8 + Participant::INIT_SPACE);
+    test.svm.airdrop(&buyer, rents + TICKET_PRICE - 1);
+
+    assert_error(test.buy(buyer, "weekly"), RaffleError::InsufficientFunds);
+    assert_eq!(test.svm.lamports(&buyer), rents + TICKET_PRICE - 1);
+    assert_eq!(test.raffle("weekly").entries, 0);
+}
+
+#[test]
+fn buy_ticket_fails_after_the_end_time() {
+    let mut test = setup();
+    let end_time = test.svm.clock().unix_timestamp + 3600;
+    test.create_raffle("weekly", CreateRaffleArgs { end_time: Some(end_time), ..args(3) }).unwrap();
+
+    test.svm.warp_to(end_time - 1);
+    test.buy(test.buyers[0], "weekly").unwrap();
+
+    test.svm.warp_to(end_time);
+    assert_error(test.buy(test.buyers[1], "weekly"), RaffleError::RaffleEnded);
+}
+
+#[test]
+fn buy_ticket_enforces_the_wallet_cap() {
+    let mut test = setup();
+    let buyer = test.buyers[0];
+    test.create_raffle("weekly", CreateRaffleArgs { max_tickets_per_wallet: Some(2), ..args(5) })
+        .unwrap();
+
+    test.buy(buyer, "weekly").unwrap();
+    assert_error(test.buy_tickets(buyer, "weekly", 2), RaffleError::WalletCapReached);
+    test.buy(buyer, "weekly").unwrap();
+    assert_error(test.buy(buyer, "weekly"), RaffleError::WalletCapReached);
+}
+
+#[test]
+fn buy_ticket_checks_the_allowlist() {
+    let mut test = setup();
+    let allowed = test.buyers[0];
+    let tree = MerkleTree::new(&[allowed, test.buyers[1]]);
+    test.create_raffle("weekly", CreateRaffleArgs { allowlist_root: Some(tree.root()), ..args(3) })
+        .unwrap();
+    let raffle = test.raffle("weekly");
+
+    let outsider = test.buyers[2];
+    let proof = tree.proof(&allowed).unwrap();
+    assert_error(
+        test.svm.process(&buy_ticket(&outsider, &raffle, proof.clone())),
+        RaffleError::NotAllowlisted,
+    );
+    test.svm.process(&buy_ticket(&allowed, &raffle, proof)).unwrap();
+}
+
+#[test]
+fn buy_tickets_follows_the_linear_price_curve() {
+    let mut test = setup();
+    let increment = TICKET_PRICE / 10;
+    test.create_raffle("weekly", CreateRaffleArgs { pricing: PricingMode::Linear { increment }, ..args(4) })
+        .unwrap();
+    let raffle_address = test.raffle_address("weekly");
+
+    test.buy(test.buyers[0], "weekly").unwrap();
+    let receipt = test.buy_tickets(test.buyers[1], "weekly", 2).unwrap();
+
+    let ticket: Ticket = test.svm.get(&ticket_pda(&raffle_address, 1).0);
+    assert_eq!(ticket.price_paid, TICKET_PRICE);
+    let bulk_amount = 2 * TICKET_PRICE + 3 * increment;
+    let entry: Entry = test.svm.get(&entry_pda(&raffle_address, &test.buyers[1]));
+    assert_eq!(entry.amount_paid, bulk_amount);
+    assert_eq!(receipt.events::<TicketPurchased>()[0].amount, bulk_amount);
+    assert_eq!(test.raffle("weekly").ticket_sales, TICKET_PRICE + bulk_amount);
+}
+
+#[test]
+fn buy_tickets_follows_the_stepwise_price_curve() {
+    let mut test = setup();
+    let increment = TICKET_PRICE / 2;
+    let pricing = PricingMode::Stepwise { step: 2, increment };
+    test.create_raffle("weekly", CreateRaffleArgs { pricing, ..args(5) }).unwrap();
+
+    // tickets 1 and 2 sell at the base price, 3 and 4 one step higher
+    test.buy_tickets(test.buyers[0], "weekly", 3).unwrap();
+    test.buy(test.buyers[1], "weekly").unwrap();
+    test.buy(test.buyers[2], "weekly").unwrap();
+
+    let raffle_address = test.raffle_address("weekly");
+    let entry: Entry = test.svm.get(&entry_pda(&raffle_address, &test.buyers[0]));
+    assert_eq!(entry.amount_paid, 3 * TICKET_PRICE + increment);
+    let ticket: Ticket = test.svm.get(&ticket_pda(&raffle_address, 5).0);
+    assert_eq!(ticket.price_paid, TICKET_PRICE + 2 * increment);
+}
+
+#[test]
+fn buy_ticket_applies_the_early_bird_discount_within_its_window() {
+    let mut test = setup();
+    let pricing = PricingMode::EarlyBird { window: 3600, discount_bps: 2_000 };
+    test.create_raffle("weekly", CreateRaffleArgs { pricing, ..args(3) }).unwrap();
+    let created_at = test.raffle("weekly").created_at;
+    let raffle_address = test.raffle_address("weekly");
+
+    test.svm.warp_to(created_at + 3599);
+    test.buy(test.buyers[0], "weekly").unwrap();
+    test.svm.warp_to(created_at + 3600);
+    test.buy(test.buyers[1], "weekly").unwrap();
+
+    let early: Ticket = test.svm.get(&ticket_pda(&raffle_address, 1).0);
+    let late: Ticket = test.svm.get(&ticket_pda(&raffle_address, 2).0);
+    assert_eq!((early.price_paid, late.price_paid), (TICKET_PRICE * 8 / 10, TICKET_PRICE));
+}
+
+#[test]
+fn refunds_return_the_price_each_ticket_was_bought_at() {
+    let mut test = setup();
+    let increment = TICKET_PRICE / 10;
+    test.create_raffle("weekly", CreateRaffleArgs { pricing: PricingMode::Linear { increment }, ..args(3) })
+        .unwrap();
+    test.buy(test.buyers[0], "weekly").unwrap();
+    test.buy(test.buyers[1], "weekly").unwrap();
+    test.cancel("weekly").unwrap();
+
+    let balance = test.svm.lamports(&test.buyers[1]);
+    let receipt = test.refund_ticket(test.buyers[1], "weekly", 2).unwrap();
+
+    assert_eq!(receipt.events::<Refunded>()[0].amount, TICKET_PRICE + increment);
+    assert_eq!(
+        test.svm.lamports(&test.buyers[1]),
+        balance + TICKET_PRICE + increment + rent(8 + Ticket::INIT_SPACE)
+    );
+}
+
+#[test]
+fn claim_prize_pays_out_the_ticket_sales() {
+    let mut test = setup();
+    let increment = TICKET_PRICE / 10;
+    test.sold_out_raffle("weekly", CreateRaffleArgs { pricing: PricingMode::Linear { increment }, ..args(3) });
+    test.pick_winner(test.owner, "weekly").unwrap();
+    let (ticket_id, winner) = test.winner("weekly", 0);
+    let balance = test.svm.lamports(&winner);
+
+    test.claim(winner, "weekly", ticket_id).unwrap();
+
+    let pool = 3 * TICKET_PRICE + 3 * increment;
+    let protocol_amount = pool * PROTOCOL_FEE_BPS as u64 / 10_000;
+    assert_eq!(test.svm.lamports(&winner), balance + pool - protocol_amount);
+    assert_eq!(test.svm.lamports(&test.raffle_address("weekly")), rent(8 + Raffle::INIT_SPACE));
+}
+
+#[test]
+fn pick_winner_requires_a_drawable_raffle() {
+    let mut test = setup();
+    test.create_raffle("weekly", args(2)).unwrap();
+    test.buy(test.buyers[0], "weekly").unwrap();
+
+    assert_error(test.pick_winner(test.owner, "weekly"), RaffleError::EntriesNotFull);
+
+    test.buy(test.buyers[1], "weekly").unwrap();
+    let receipt = test.pick_winner(test.owner, "weekly").unwrap();
+
+    let raffle = test.raffle("weekly");
+    assert_eq!(raffle.status, RaffleStatus::Drawn);
+    assert_eq!(receipt.events::<WinnerPicked>()[0].winning_ticket_ids, raffle.winning_ticket_ids());
+    assert_error(test.pick_winner(test.owner, "weekly"), RaffleError::WinnerChosen);
+}
+
+#[test]
+fn pick_winner_pays_the_crank_reward_to_any_caller() {
+    let mut test = setup();
+    test.sold_out_raffle("weekly", CreateRaffleArgs { crank_reward_bps: 100, ..args(2) });
+    let caller = Pubkey::new_unique();
+    test.svm.airdrop(&caller, SOL);
+
+    test.pick_winner(caller, "weekly").unwrap();
+
+    let reward = 2 * TICKET_PRICE / 100;
+    assert_eq!(test.svm.lamports(&caller), SOL + reward);
+    assert_eq!(test.raffle("weekly").crank_reward_paid, reward);
+}
+
+#[test]
+fn pick_winner_draws_an_undersold_raffle_after_the_end_time() {
+    let mut test = setup();
+    let end_time = test.svm.clock().unix_timestamp + 3600;
+    test.create_raffle("weekly", CreateRaffleArgs { end_time: Some(end_time), ..args(4) }).unwrap();
+    test.buy(test.buyers[0], "weekly").unwrap();
+
+    test.svm.warp_to(end_time - 1);
+    assert_error(test.pick_winner(test.owner, "weekly"), RaffleError::EntriesNotFull);
+
+    test.svm.warp_to(end_time);
+    test.pick_winner(test.owner, "weekly").unwrap();
+    assert_eq!(test.winner("weekly", 0), (1, test.buyers[0]));
+}
+
+#[test]
+fn claim_prize_splits_the_pool_between_winner_creator_and_treasury() {
+    let mut test = setup();
+    test.sold_out_raffle("weekly", CreateRaffleArgs { creator_fee_bps: 500, ..args(3) });
+    test.pick_winner(test.owner, "weekly").unwrap();
+    let (ticket_id, winner) = test.winner("weekly", 0);
+    let balances = [winner, test.owner, test.treasury].map(|wallet| test.svm.lamports(&wallet));
+
+    let receipt = test.claim(winner, "weekly", ticket_id).unwrap();
+
+    let pool = 3 * TICKET_PRICE;
+    let creator_amount = pool * 500 / 10_000;
+    let protocol_amount = pool * PROTOCOL_FEE_BPS as u64 / 10_000;
+    let winner_amount = pool - creator_amount - protocol_amount;
+    assert_eq!(test.svm.lamports(&winner), balances[0] + winner_amount);
+    assert_eq!(test.svm.lamports(&test.owner), balances[1] + creator_amount);
+    assert_eq!(test.svm.lamports(&test.treasury), balances[2] + protocol_amount);
+    assert_eq!(test.svm.lamports(&test.raffle_address("weekly")), rent(8 + Raffle::INIT_SPACE));
+    assert_eq!(test.raffle("weekly").status, RaffleStatus::Claimed);
+
+    let event = &receipt.events::<PrizeClaimed>()[0];
+    assert_eq!((event.ticket_id, event.winner), (ticket_id, winner));
+    assert_eq!(event.winner_amount, winner_amount);
+}
+
+#[test]
+fn claim_prize_rejects_a_wrong_winner() {
+    let mut test = setup();
+    test.sold_out_raffle("weekly", args(3));
+    test.pick_winner(test.owner, "weekly").unwrap();
+    let (ticket_id, winner) = test.winner("weekly", 0);
+    let loser_ticket = ticket_id % 3 + 1;
+    let loser = test.buyers[loser_ticket as usize - 1];
+
+    // a losing ticket, and the winning ticket claimed by someone else
+    assert_error(test.claim(loser, "weekly", loser_ticket), RaffleError::InvalidWinner);
+    assert_error(test.claim(loser, "weekly", ticket_id), RaffleError::InvalidWinner);
+
+    test.claim(winner, "weekly", ticket_id).unwrap();
+}
+
+#[test]
+fn claim_prize_rejects_a_double_claim() {
+    let mut test = setup();
+    test.sold_out_raffle("weekly", CreateRaffleArgs { prize_tiers: vec![7_000, 3_000], ..args(3) });
+    test.pick_winner(test.owner, "weekly").unwrap();
+    let (first_ticket, first_winner) = test.winner("weekly", 0);
+    let (second_ticket, second_winner) = test.winner("weekly", 1);
+
+    test.claim(first_winner, "weekly", first_ticket).unwrap();
+    assert_error(test.claim(first_winner, "weekly", first_ticket), RaffleError::TierAlreadyClaimed);
+
+    test.claim(second_winner, "weekly", second_ticket).unwrap();
+    assert_eq!(test.raffle("weekly").status, RaffleStatus::Claimed);
+    assert_error(test.claim(second_winner, "weekly", second_ticket), RaffleError::RaffleAlreadyClaimed);
+}
+
+#[test]
+fn claim_prize_requires_a_drawn_raffle() {
+    let mut test = setup();
+    test.sold_out_raffle("weekly", args(2));
+
+    assert_error(test.claim(test.buyers[0], "weekly", 1), RaffleError::WinnerNotChosen);
+}
+
+#[test]
+fn reveal_winner_must_happen_within_the_reveal_window() {
+    let mut test = setup();
+    let secret = [7; 32];
+    let commit_reveal = RandomnessSource::CommitReveal { commitment: hash(&secret).to_bytes() };
+    test.sold_out_raffle("weekly", CreateRaffleArgs { randomness_source: commit_reveal.clone(), ..args(2) });
+    test.sold_out_raffle("monthly", CreateRaffleArgs { randomness_source: commit_reveal, ..args(2) });
+    let sold_out_at = test.svm.clock().unix_timestamp;
+
+    assert_error(test.pick_winner(test.owner, "weekly"), RaffleError::RevealRequired);
+    assert_error(test.reveal_winner("weekly", [8; 32]), RaffleError::InvalidReveal);
+
+    test.svm.warp_to(sold_out_at + REVEAL_TIMEOUT);
+    test.reveal_winner("weekly", secret).unwrap();
+    assert_eq!(test.raffle("weekly").status, RaffleStatus::Drawn);
+
+    test.svm.warp_by(1);
+    assert_error(test.reveal_winner("monthly", secret), RaffleError::RevealWindowClosed);
+}
+
+#[test]
+fn expire_reveal_draws_without_the_secret_after_the_timeout() {
+    let mut test = setup();
+    let randomness_source = RandomnessSource::CommitReveal { commitment: hash(&[7; 32]).to_bytes() };
+    test.sold_out_raffle("weekly", CreateRaffleArgs { randomness_source, crank_reward_bps: 100, ..args(2) });
+    let sold_out_at = test.svm.clock().unix_timestamp;
+
+    test.svm.warp_to(sold_out_at + REVEAL_TIMEOUT);
+    assert_error(test.expire_reveal("weekly"), RaffleError::RevealTimeoutNotReached);
+    let balance = test.svm.lamports(&test.buyers[0]);
+
+    // the draw does not depend on when it is submitted
+    let raffle = test.raffle("weekly");
+    let seed = hash(&[&raffle.entropy[..], &hash(&[7; 32]).to_bytes()[..]].concat()).to_bytes();
+    test.svm.warp_by(1000);
+    let receipt = test.expire_reveal("weekly").unwrap();
+    let raffle = test.raffle("weekly");
+    assert_eq!(raffle.status, RaffleStatus::Drawn);
+    assert_eq!(raffle.winning_ticket_ids(), vec![raffle.draw_ticket(&seed)]);
+    assert_eq!(receipt.events::<WinnerPicked>().len(), 1);
+
+    // whoever submits the draw earns the crank reward
+    let reward = 2 * TICKET_PRICE / 100;
+    assert_eq!(test.svm.lamports(&test.buyers[0]), balance + reward);
+    assert_eq!(raffle.crank_reward_paid, reward);
+
+    // slot hash raffles have nothing to reveal
+    test.sold_out_raffle("monthly", args(1));
+    assert_error(test.expire_reveal("monthly"), RaffleError::NoCommitment);
+}
+
+#[test]
+fn commit_reveal_raffles_can_not_be_expired_instead_of_revealed() {
+    let mut test = setup();
+    let secret = [7; 32];
+    let end_time = test.svm.clock().unix_timestamp + 3600;
+    let args = CreateRaffleArgs {
+        end_time: Some(end_time),
+        randomness_source: RandomnessSource::CommitReveal { commitment: hash(&secret).to_bytes() },
+        ..args(3)
+    };
+    test.create_raffle("weekly", args.clone()).unwrap();
+    test.create_raffle("daily", args).unwrap();
+    for raffle_id in ["weekly", "daily"] {
+        test.buy(test.buyers[0], raffle_id).unwrap();
+    }
+
+    // the draw timeout ends on the last second the owner may still reveal
+    test.svm.warp_to(end_time + DRAW_TIMEOUT);
+    assert_error(test.expire_raffle("weekly"), RaffleError::RevealRequired);
+    test.reveal_winner("weekly", secret).unwrap();
+    assert_eq!(test.raffle("weekly").status, RaffleStatus::Drawn);
+
+    test.svm.warp_by(1);
+    assert_error(test.expire_raffle("daily"), RaffleError::RevealRequired);
+    test.expire_reveal("daily").unwrap();
+    assert_eq!(test.raffle("daily").status, RaffleStatus::Drawn);
+}
+
+#[test]
+fn consume_randomness_rejects_stale_oracle_values() {
+    let mut test = setup();
+    let oracle_program = Pubkey::new_unique();
+    let oracle = Pubkey::new_unique();
+    let randomness_source = RandomnessSource::Oracle(OracleConfig {
+        program: oracle_program,
+        account: oracle,
+        randomness_offset: 8,
+        slot_offset: 40,
+        max_staleness: 10,
+    });
+    test.sold_out_raffle("weekly", CreateRaffleArgs { randomness_source, crank_reward_bps: 100, ..args(3) });
+
+    let mut randomness = [0; 32];
+    randomness[..8].copy_from_slice(&5u64.to_le_bytes());
+    let set_oracle = |svm: &mut Svm, slot: u64| {
+        let data = [&[0; 8][..], &randomness, &slot.to_le_bytes()].concat();
+        svm.set_account(oracle, Account { lamports: SOL, data, owner: oracle_program, executable: false });
+    };
+    let consume = |test: &mut Test| {
+        let accounts = raffle::accounts::ConsumeRandomness {
+            caller: test.buyers[3],
+            raffle: test.raffle_address("weekly"),
+            config: config_pda().0,
+            oracle,
+            registry_page: test.listed_on("weekly"),
+            ticket_mint: None,
+            caller_token_account: None,
+            escrow: None,
+            token_program: None,
+        };
+        test.svm.process(&instruction(accounts, raffle::instruction::ConsumeRandomness {
+            raffle_id: test.raffle_id("weekly"),
+        }))
+    };
+
+    assert_error(test.pick_winner(test.owner, "weekly"), RaffleError::OracleRequired);
+
+    test.svm.warp_by(20);
+    let slot = test.svm.clock().slot;
+    set_oracle(&mut test.svm, slot + 1);
+    assert_error(consume(&mut test), RaffleError::InvalidOracleSlot);
+
+    // buyers could have seen a value published by the last purchase
+    let last_ticket_slot = test.raffle("weekly").last_ticket_slot;
+    set_oracle(&mut test.svm, last_ticket_slot);
+    assert_error(consume(&mut test), RaffleError::RandomnessTooEarly);
+
+    set_oracle(&mut test.svm, slot - 11);
+    assert_error(consume(&mut test), RaffleError::StaleRandomness);
+
+    set_oracle(&mut test.svm, slot - 10);
+    let balance = test.svm.lamports(&test.buyers[3]);
+    consume(&mut test).unwrap();
+    // 5 % 3 entries picks the third ticket
+    assert_eq!(test.raffle("weekly").winning_ticket_ids(), vec![3]);
+
+    // whoever consumes the oracle value earns the crank reward
+    let reward = 3 * TICKET_PRICE / 100;
+    assert_eq!(test.svm.lamports(&test.buyers[3]), balance + reward);
+    assert_eq!(test.raffle("weekly").crank_reward_paid, reward);
+}
+
+#[test]
+fn stuck_oracle_draws_expire_after_the_draw_timeout() {
+    let mut test = setup();
+    let randomness_source = RandomnessSource::Oracle(OracleConfig {
+        program: Pubkey::new_unique(),
+        account: Pubkey::new_unique(),
+        randomness_offset: 8,
+        slot_offset: 40,
+        max_staleness: 10,
+    });
+    test.sold_out_raffle("weekly", CreateRaffleArgs { randomness_source, ..args(3) });
+    test.sold_out_raffle("daily", args(3));
+    let sold_out_at = test.raffle("weekly").sold_out_at.unwrap();
+
+    // a sold out oracle raffle can only be drawn, not cancelled
+    assert_error(test.cancel("weekly"), RaffleError::RaffleDrawing);
+
+    test.svm.warp_to(sold_out_at + DRAW_TIMEOUT - 1);
+    assert_error(test.expire_raffle("weekly"), RaffleError::ExpiryNotReached);
+
+    test.svm.warp_to(sold_out_at + DRAW_TIMEOUT);
+    test.expire_raffle("weekly").unwrap();
+    assert_eq!(test.raffle("weekly").status, RaffleStatus::Expired);
+    test.refund_ticket(test.buyers[0], "weekly", 1).unwrap();
+
+    // raffles drawn from slot hashes can always be drawn by anyone
+    assert_error(test.expire_raffle("daily"), RaffleError::RaffleNotActive);
+}
+
+#[test]
+fn expire_raffle_waits_for_the_draw_timeout() {
+    let mut test = setup();
+    let end_time = test.svm.clock().unix_timestamp + 3600;
+    test.create_raffle("weekly", CreateRaffleArgs { end_time: Some(end_time), ..args(3) }).unwrap();
+    test.create_raffle("empty", CreateRaffleArgs { end_time: Some(end_time), ..args(3) }).unwrap();
+    test.buy(test.buyers[0], "weekly").unwrap();
+
+    // unsold raffles expire at their end time, others get time to be drawn
+    test.svm.warp_to(end_time);
+    test.expire_raffle("empty").unwrap();
+    assert_error(test.expire_raffle("weekly"), RaffleError::ExpiryNotReached);
+
+    test.svm.warp_to(end_time + DRAW_TIMEOUT);
+    let receipt = test.expire_raffle("weekly").unwrap();
+    let events = receipt.events::<RaffleExpired>();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].raffle, test.raffle_address("weekly"));
+    assert_eq!(events[0].entries, 1);
+    assert_eq!(test.raffle("weekly").status, RaffleStatus::Expired);
+    assert_error(test.pick_winner(test.owner, "weekly"), RaffleError::RaffleNotActive);
+
+    let balance = test.svm.lamports(&test.buyers[0]);
+    test.refund_ticket(test.buyers[0], "weekly", 1).unwrap();
+    assert_eq!(
+        test.svm.lamports(&test.buyers[0]),
+        balance + TICKET_PRICE + rent(8 + Ticket::INIT_SPACE)
+    );
+}
+
+#[test]
+fn pick_winner_draws_once_min_entries_sold_by_the_end_time() {
+    let mut test = setup();
+    let end_time = test.svm.clock().unix_timestamp + 3600;
+    test.create_raffle("weekly", CreateRaffleArgs { end_time: Some(end_time), min_entries: 2, ..args(4) })
+        .unwrap();
+    test.buy(test.buyers[0], "weekly").unwrap();
+    test.buy(test.buyers[1], "weekly").unwrap();
+
+    assert_error(test.pick_winner(test.owner, "weekly"), RaffleError::EntriesNotFull);
+
+    test.svm.warp_to(end_time);
+    let receipt = test.pick_winner(test.owner, "weekly").unwrap();
+    assert_eq!(receipt.events::<WinnerPicked>()[0].entries, 2);
+    assert_eq!(test.raffle("weekly").status, RaffleStatus::Drawn);
+}
+
+#[test]
+fn raffles_short_of_min_entries_become_refundable() {
+    let mut test = setup();
+    let end_time = test.svm.clock().unix_timestamp + 3600;
+    let args = CreateRaffleArgs { end_time: Some(end_time), min_entries: 3, ..args(4) };
+    test.create_raffle("weekly", args.clone()).unwrap();
+    test.create_raffle("daily", args).unwrap();
+    for raffle_id in ["weekly", "daily"] {
+        test.buy(test.buyers[0], raffle_id).unwrap();
+        test.buy(test.buyers[1], raffle_id).unwrap();
+    }
+
+    // the draw expires the raffle instead of picking a winner
+    test.svm.warp_to(end_time);
+    let receipt = test.pick_winner(test.buyers[3], "weekly").unwrap();
+    assert!(receipt.events::<WinnerPicked>().is_empty());
+    assert_eq!(receipt.events::<RaffleExpired>()[0].entries, 2);
+    assert_eq!(test.raffle("weekly").status, RaffleStatus::Expired);
+    test.refund_ticket(test.buyers[0], "weekly", 1).unwrap();
+
+    // nor does it have to wait for the draw timeout to be expired directly
+    test.expire_raffle("daily").unwrap();
+    assert_eq!(test.raffle("daily").status, RaffleStatus::Expired);
+}
+
+#[test]
+fn expire_raffle_requires_an_end_time() {
+    let mut test = setup();
+    test.create_raffle("weekly", args(3)).unwrap();
+
+    test.svm.warp_by(365 * 24 * 60 * 60);
+    assert_error(test.expire_raffle("weekly"), RaffleError::ExpiryNotReached);
+}
+
+#[test]
+fn cancel_raffle_refunds_every_ticket_once() {
+    let mut test = setup();
+    test.create_raffle("weekly", args(4)).unwrap();
+    test.buy(test.buyers[0], "weekly").unwrap();
+    test.buy_tickets(test.buyers[1], "weekly", 2).unwrap();
+
+    assert_error(test.refund_ticket(test.buyers[0], "weekly", 1), RaffleError::RaffleNotRefundable);
+
+    let receipt = test.cancel("weekly").unwrap();
+    assert_eq!(receipt.events::<RaffleCancelled>()[0].entries, 3);
+    assert_error(test.cancel("weekly"), RaffleError::RaffleCancelled);
+    assert_error(test.buy(test.buyers[2], "weekly"), RaffleError::RaffleNotActive);
+
+    let balance = test.svm.lamports(&test.buyers[0]);
+    let receipt = test.refund_ticket(test.buyers[0], "weekly", 1).unwrap();
+    assert_eq!(receipt.events::<Refunded>()[0].amount, TICKET_PRICE);
+    assert_eq!(
+        test.svm.lamports(&test.buyers[0]),
+        balance + TICKET_PRICE + rent(8 + Ticket::INIT_SPACE)
+    );
+    // the ticket was closed by its refund
+    assert_error(
+        test.refund_ticket(test.buyers[0], "weekly", 1),
+        ErrorCode::AccountNotInitialized,
+    );
+
+    // anyone may crank the refund of the bulk entry
+    let raffle = test.raffle_address("weekly");
+    let owner = test.buyers[1];
+    let mut crank = instruction(
+        raffle::accounts::CrankRefunds {
+            caller: test.buyers[3],
+            raffle,
+            ticket_mint: None,
+            escrow: None,
+            token_program: None,
+        },
+        raffle::instruction::CrankRefunds { raffle_id: test.raffle_id("weekly") },
+    );
+    crank.accounts.extend([
+        AccountMeta::new(entry_pda(&raffle, &owner), false),
+        AccountMeta::new(owner, false),
+    ]);
+    let balance = test.svm.lamports(&owner);
+    test.svm.process(&crank).unwrap();
+    assert_eq!(
+        test.svm.lamports(&owner),
+        balance + 2 * TICKET_PRICE + rent(8 + Entry::INIT_SPACE)
+    );
+
+    let raffle = test.raffle("weekly");
+    assert_eq!(raffle.refunded_entries, raffle.entries);
+    assert!(raffle.is_settled());
+}
+
+#[test]
+fn cancel_raffle_is_rejected_once_drawn() {
+    let mut test = setup();
+    test.sold_out_raffle("weekly", args(2));
+    test.pick_winner(test.owner, "weekly").unwrap();
+
+    assert_error(test.cancel("weekly"), RaffleError::WinnerChosen);
+}
+
+#[test]
+fn cancel_raffle_after_the_end_time_depends_on_the_randomness_source() {
+    let mut test = setup();
+    let end_time = test.svm.clock().unix_timestamp + 3600;
+    let commit_reveal = RandomnessSource::CommitReveal { commitment: hash(&[7; 32]).to_bytes() };
+    test.create_raffle("weekly", CreateRaffleArgs { end_time: Some(end_time), ..args(3) }).unwrap();
+    test.create_raffle(
+        "daily",
+        CreateRaffleArgs { end_time: Some(end_time), randomness_source: commit_reveal, ..args(3) }
+    ).unwrap();
+    for raffle_id in ["weekly", "daily"] {
+        test.buy(test.buyers[0], raffle_id).unwrap();
+    }
+
+    // the owner of a slot hash raffle may still draw or cancel once it ended
+    test.svm.warp_to(end_time);
+    test.cancel("weekly").unwrap();
+    test.refund_ticket(test.buyers[0], "weekly", 1).unwrap();
+
+    // while a committed owner already knows who won
+    assert_error(test.cancel("daily"), RaffleError::RaffleDrawing);
+}
+
+#[test]
+fn close_accounts_once_the_raffle_is_settled() {
+    let mut test = setup();
+    test.sold_out_raffle("weekly", CreateRaffleArgs { prize_tiers: vec![5_000, 5_000], ..args(3) });
+    test.pick_winner(test.owner, "weekly").unwrap();
+    let (ticket_id, winner) = test.winner("weekly", 0);
+    let (other_ticket, other_winner) = test.winner("weekly", 1);
+    let loser_ticket = 6 - ticket_id - other_ticket;
+    let loser = test.buyers[loser_ticket as usize - 1];
+
+    assert_error(test.close_ticket(winner, "weekly", ticket_id), RaffleError::PrizeUnclaimed);
+    assert_error(test.close_raffle("weekly"), RaffleError::RaffleNotSettled);
+
+    test.claim(winner, "weekly", ticket_id).unwrap();
+    test.claim(other_winner, "weekly", other_ticket).unwrap();
+    for (owner, ticket_id) in [(winner, ticket_id), (other_winner, other_ticket), (loser, loser_ticket)] {
+        test.close_ticket(owner, "weekly", ticket_id).unwrap();
+        assert!(test.svm.account(&ticket_pda(&test.raffle_address("weekly"), ticket_id).0).is_none());
+    }
+
+    // participants have to be closed too, so none outlive the raffle
+    assert_error(test.close_raffle("weekly"), RaffleError::OpenAccountsRemaining);
+    for buyer in test.buyers.clone().into_iter().take(3) {
+        test.close_participant(buyer, "weekly").unwrap();
+    }
+    assert_eq!(test.raffle("weekly").open_accounts, 0);
+
+    let balance = test.svm.lamports(&test.owner);
+    test.close_raffle("weekly").unwrap();
+    assert!(test.svm.account(&test.raffle_address("weekly")).is_none());
+    assert_eq!(test.svm.lamports(&test.owner), balance + rent(8 + Raffle::INIT_SPACE));
+}
+
+#[test]
+fn anyone_may_close_the_accounts_of_a_settled_raffle() {
+    let mut test = setup();
+    test.sold_out_raffle("weekly", args(2));
+    test.sold_out_raffle("daily", args(2));
+    let raffle = test.raffle_address("weekly");
+    let crank = |test: &mut Test, accounts: &[(Pubkey, Pubkey)]| {
+        let mut crank = instruction(
+            raffle::accounts::CrankCloseAccounts { caller: test.buyers[3], raffle },
+            raffle::instruction::CrankCloseAccounts { raffle_id: test.raffle_id("weekly") },
+        );
+        for &(account, owner) in accounts {
+            crank.accounts.extend([AccountMeta::new(account, false), AccountMeta::new(owner, false)]);
+        }
+        test.svm.process(&crank)
+    };
+    let buyer_accounts = |buyer: usize| {
+        let owner = test.buyers[buyer];
+        [
+            (ticket_pda(&raffle, buyer as u32 + 1).0, owner),
+            (participant_pda(&raffle, &owner).0, owner),
+        ]
+    };
+    let accounts = [buyer_accounts(0), buyer_accounts(1)].concat();
+
+    test.pick_winner(test.owner, "weekly").unwrap();
+    assert_error(crank(&mut test, &accounts), RaffleError::RaffleNotSettled);
+    let (ticket_id, winner) = test.winner("weekly", 0);
+    test.claim(winner, "weekly", ticket_id).unwrap();
+
+    // rent only ever goes back to the account owner, and only for this raffle
+    let (buyer, stranger) = (test.buyers[0], test.buyers[3]);
+    assert_error(crank(&mut test, &[(accounts[0].0, stranger)]), RaffleError::InvalidCloseAccounts);
+    let other_ticket = ticket_pda(&test.raffle_address("daily"), 1).0;
+    assert_error(crank(&mut test, &[(other_ticket, buyer)]), RaffleError::InvalidCloseAccounts);
+    assert_error(crank(&mut test, &[]), RaffleError::InvalidCloseAccounts);
+
+    let balance = test.svm.lamports(&test.buyers[0]);
+    crank(&mut test, &accounts).unwrap();
+    assert_eq!(
+        test.svm.lamports(&test.buyers[0]),
+        balance + rent(8 + Ticket::INIT_SPACE) + rent(8 + Participant::INIT_SPACE)
+    );
+    assert!(accounts.iter().all(|(account, _)| test.svm.account(account).is_none()));
+    assert_eq!(test.raffle("weekly").open_accounts, 0);
+    test.close_raffle("weekly").unwrap();
+}
+
+#[test]
+fn close_entry_waits_for_the_prizes_it_won() {
+    let mut test = setup();
+    let buyer = test.buyers[0];
+    test.create_raffle("weekly", args(2)).unwrap();
+    test.buy_tickets(buyer, "weekly", 2).unwrap();
+    assert_eq!(test.raffle("weekly").open_accounts, 2);
+    assert_error(test.close_entry(buyer, "weekly"), RaffleError::WinnerNotChosen);
+    assert_error(test.close_participant(buyer, "weekly"), RaffleError::WinnerNotChosen);
+
+    test.pick_winner(test.owner, "weekly").unwrap();
+    assert_error(test.close_entry(buyer, "weekly"), RaffleError::PrizeUnclaimed);
+
+    let raffle = test.raffle_address("weekly");
+    let accounts = raffle::accounts::ClaimPrize {
+        winner: buyer,
+        raffle,
+        ticket: None,
+        entry: Some(entry_pda(&raffle, &buyer)),
+        config: config_pda().0,
+        owner: test.owner,
+        treasury: test.treasury,
+        ticket_mint: None,
+        winner_token_account: None,
+        owner_token_account: None,
+        treasury_token_account: None,
+        escrow: None,
+        token_program: None,
+        prize_mint: None,
+        winner_prize_account: None,
+        prize_vault: None,
+        prize_token_program: None,
+        system_program: system_program::ID,
+    };
+    test.svm.process(&instruction(accounts, raffle::instruction::ClaimPrize {
+        raffle_id: test.raffle_id("weekly"),
+    }))
+    .unwrap();
+
+    let balance = test.svm.lamports(&buyer);
+    test.close_entry(buyer, "weekly").unwrap();
+    test.close_participant(buyer, "weekly").unwrap();
+    assert_eq!(
+        test.svm.lamports(&buyer),
+        balance + rent(8 + Entry::INIT_SPACE) + rent(8 + Participant::INIT_SPACE)
+    );
+
+    test.close_raffle("weekly").unwrap();
+    assert!(test.svm.account(&entry_pda(&raffle, &buyer)).is_none());
+}
+
+#[test]
+fn initialize_config_requires_the_upgrade_authority() {
+    let mut svm = Svm::new();
+    let (authority, intruder) = (Pubkey::new_unique(), Pubkey::new_unique());
+    for wallet in [authority, intruder] {
+        svm.airdrop(&wallet, SOL);
+    }
+
+    // immutable programs have no authority that could initialize the config
+    assert_error(svm.process(&initialize_config(authority, authority)), RaffleError::Unauthorized);
+
+    svm.set_upgrade_authority(Some(authority));
+    assert_error(svm.process(&initialize_config(intruder, intruder)), RaffleError::Unauthorized);
+    svm.process(&initialize_config(authority, authority)).unwrap();
+    let config: Config = svm.get(&config_pda().0);
+    assert_eq!(config.admin, authority);
+}
+
+#[test]
+fn update_config_requires_the_admin() {
+    let mut test = setup();
+    let update = |admin: Pubkey, protocol_fee_bps: u16| {
+        let accounts = raffle::accounts::UpdateConfig { admin, config: config_pda().0 };
+        instruction(accounts, raffle::instruction::UpdateConfig {
+            admin,
+            treasury: Pubkey::new_unique(),
+            protocol_fee_bps,
+        })
+    };
+
+    assert_error(test.svm.process(&update(test.buyers[0], 200)), ErrorCode::ConstraintHasOne);
+    assert_error(test.svm.process(&update(test.admin, 10_001)), RaffleError::InvalidFee);
+
+    test.svm.process(&update(test.admin, 200)).unwrap();
+    let config: Config = test.svm.get(&config_pda().0);
+    assert_eq!(config.protocol_fee_bps, 200);
+
+    // new raffles snapshot the updated fee
+    test.create_raffle("weekly", args(3)).unwrap();
+    assert_eq!(test.raffle("weekly").protocol_fee_bps, 200);
+}
+
+#[test]
+fn transferred_tickets_pay_out_to_their_new_owner() {
+    let mut test = setup();
+    let (seller, holder) = (test.buyers[0], test.buyers[3]);
+    let end_time = test.svm.clock().unix_timestamp + 3600;
+    test.create_raffle("weekly", CreateRaffleArgs { end_time: Some(end_time), ..args(3) }).unwrap();
+    test.buy(seller, "weekly").unwrap();
+    test.buy(seller, "weekly").unwrap();
+
+    assert_error(test.transfer_ticket(holder, "weekly", 1, holder), ErrorCode::ConstraintHasOne);
+    let receipt = test.transfer_ticket(seller, "weekly", 1, holder).unwrap();
+    let event = &receipt.events::<TicketTransferred>()[0];
+    assert_eq!((event.ticket_id, event.from, event.to, event.price), (1, seller, holder, 0));
+    test.transfer_ticket(seller, "weekly", 2, holder).unwrap();
+
+    // tickets are frozen once sales close
+    test.svm.warp_to(end_time);
+    assert_error(test.transfer_ticket(holder, "weekly", 1, seller), RaffleError::RaffleNotActive);
+    test.pick_winner(test.owner, "weekly").unwrap();
+
+    let (ticket_id, winner) = test.winner("weekly", 0);
+    assert_eq!(winner, holder);
+    assert_error(test.claim(seller, "weekly", ticket_id), RaffleError::InvalidWinner);
+    test.claim(holder, "weekly", ticket_id).unwrap();
+}
+
+#[test]
+fn transfers_are_rejected_for_restricted_raffles() {
+    let mut test = setup();
+    let buyer = test.buyers[0];
+    test.create_raffle("weekly", CreateRaffleArgs { max_tickets_per_wallet: Some(1), ..args(3) }).unwrap();
+    test.buy(buyer, "weekly").unwrap();
+
+    assert_error(test.transfer_ticket(buyer, "weekly", 1, test.buyers[1]), RaffleError::TransferRestricted);
+    assert_error(test.list_ticket(buyer, "weekly", 1, TICKET_PRICE), RaffleError::TransferRestricted);
+}
+
+#[test]
+fn listed_tickets_are_bought_atomically() {
+    let mut test = setup();
+    let (seller, buyer) = (test.buyers[0], test.buyers[1]);
+    let price = 2 * TICKET_PRICE;
+    test.create_raffle("weekly", args(3)).unwrap();
+    test.buy(seller, "weekly").unwrap();
+    let ticket = ticket_pda(&test.raffle_address("weekly"), 1).0;
+
+    assert_error(test.list_ticket(buyer, "weekly", 1, price), ErrorCode::ConstraintHasOne);
+    assert_error(test.list_ticket(seller, "weekly", 1, 0), RaffleError::InvalidListingPrice);
+    let receipt = test.list_ticket(seller, "weekly", 1, price).unwrap();
+    assert_eq!(receipt.events::<TicketListed>()[0].price, price);
+
+    assert_error(
+        test.buy_listed_ticket(buyer, seller, "weekly", 1, TICKET_PRICE),
+        RaffleError::ListingPriceChanged,
+    );
+    assert_error(
+        test.buy_listed_ticket(buyer, test.buyers[2], "weekly", 1, price),
+        ErrorCode::ConstraintHasOne,
+    );
+
+    let (seller_balance, buyer_balance) = (test.svm.lamports(&seller), test.svm.lamports(&buyer));
+    let receipt = test.buy_listed_ticket(buyer, seller, "weekly", 1, price).unwrap();
+    assert_eq!(receipt.events::<TicketTransferred>()[0].price, price);
+    assert_eq!(
+        test.svm.lamports(&seller),
+        seller_balance + price + rent(8 + Listing::INIT_SPACE)
+    );
+    assert_eq!(test.svm.lamports(&buyer), buyer_balance - price);
+    assert_eq!(test.svm.get::<Ticket>(&ticket).owner, buyer);
+    assert!(test.svm.account(&listing_pda(&ticket).0).is_none());
+}
+
+#[test]
+fn transfers_close_the_ticket_listing() {
+    let mut test = setup();
+    let (seller, buyer, holder) = (test.buyers[0], test.buyers[1], test.buyers[2]);
+    test.create_raffle("weekly", args(3)).unwrap();
+    test.buy(seller, "weekly").unwrap();
+    let listing = listing_pda(&ticket_pda(&test.raffle_address("weekly"), 1).0).0;
+    test.list_ticket(seller, "weekly", 1, TICKET_PRICE).unwrap();
+
+    let balance = test.svm.lamports(&seller);
+    test.transfer_ticket(seller, "weekly", 1, holder).unwrap();
+    assert_eq!(test.svm.lamports(&seller), balance + rent(8 + Listing::INIT_SPACE));
+    assert!(test.svm.account(&listing).is_none());
+
+    // the new holder can list the ticket, and the old listing does not come
+    // back with the ticket
+    test.list_ticket(holder, "weekly", 1, 2 * TICKET_PRICE).unwrap();
+    test.transfer_ticket(holder, "weekly", 1, seller).unwrap();
+    assert_error(
+        test.buy_listed_ticket(buyer, seller, "weekly", 1, TICKET_PRICE),
+        ErrorCode::AccountNotInitialized,
+    );
+
+    // listings are only cancelled by their seller
+    test.list_ticket(seller, "weekly", 1, TICKET_PRICE).unwrap();
+    let cancel = |seller: Pubkey| {
+        instruction(raffle::accounts::CancelListing { seller, listing }, raffle::instruction::CancelListing {})
+    };
+    assert_error(test.svm.process(&cancel(buyer)), ErrorCode::ConstraintHasOne);
+
+    let balance = test.svm.lamports(&seller);
+    test.svm.process(&cancel(seller)).unwrap();
+    assert_eq!(test.svm.lamports(&seller), balance + rent(8 + Listing::INIT_SPACE));
+    assert!(test.svm.account(&listing).is_none());
+}
+
+#[test]
+fn create_raffle_counts_and_lists_raffles() {
+    let mut test = setup();
+    test.create_raffle("0", args(3)).unwrap();
+    test.create_raffle("1", args(3)).unwrap();
+
+    let registry: RaffleRegistry = test.svm.get(&registry_pda(&test.owner).0);
+    assert_eq!((registry.owner, registry.raffles_created), (test.owner, 2));
+    assert_eq!(registry.next_raffle_id(), "2");
+    assert_eq!(test.raffle("1").registry_page, Some(0));
+    assert_eq!(
+        test.registry_page(0).raffles,
+        vec![test.raffle_address("0"), test.raffle_address("1")]
+    );
+}
+
+#[test]
+fn owner_raffles_are_enumerated_from_the_registry_counter() {
+    let mut test = setup();
+    for raffle_id in ["weekly", "daily", "monthly"] {
+        test.create_raffle(raffle_id, args(3)).unwrap();
+    }
+    test.create_series("series", series_args()).unwrap();
+    test.start_next_round("series", None, false).unwrap();
+
+    let registry: RaffleRegistry = test.svm.get(&registry_pda(&test.owner).0);
+    let raffles = owner_raffle_pdas(&test.owner, &registry);
+    assert_eq!(
+        raffles,
+        vec![test.raffle_address("weekly"), test.raffle_address("daily"), test.raffle_address("monthly")]
+    );
+    assert!(raffles.iter().all(|raffle| test.svm.account(raffle).is_some()));
+}
+
+#[test]
+fn registry_pages_fill_up_in_order() {
+    let mut test = setup();
+    for raffle_id in 0..=RAFFLES_PER_PAGE {
+        test.create_raffle(&raffle_id.to_string(), args(3)).unwrap();
+    }
+
+    assert_eq!(test.current_registry_page(), 1);
+    assert_eq!(test.registry_page(0).raffles.len(), RAFFLES_PER_PAGE);
+    let last_raffle = test.raffle_address(&RAFFLES_PER_PAGE.to_string());
+    assert_eq!(test.registry_page(1).raffles, vec![last_raffle]);
+    assert_eq!(test.raffle(&RAFFLES_PER_PAGE.to_string()).registry_page, Some(1));
+}
+
+#[test]
+fn raffles_are_unlisted_once_they_stop_selling_tickets() {
+    let mut test = setup();
+    let end_time = test.svm.clock().unix_timestamp + 3600;
+    test.create_raffle("cancelled", args(3)).unwrap();
+    test.create_raffle("closed", args(1)).unwrap();
+    test.create_raffle("drawn", CreateRaffleArgs { end_time: Some(end_time), ..args(3) }).unwrap();
+    test.create_raffle("expired", CreateRaffleArgs { end_time: Some(end_time), ..args(3) }).unwrap();
+
+    // the purchase selling the raffle out takes it off the registry
+    let raffle = test.raffle("closed");
+    let mut buy = buy_ticket(&test.buyers[0], &raffle, Vec::new());
+    buy.accounts[5] = AccountMeta::new_readonly(PROGRAM_ID, false);
+    assert_error(test.svm.process(&buy), RaffleError::RegistryPageRequired);
+    test.buy(test.buyers[0], "closed").unwrap();
+    assert_eq!(test.raffle("closed").registry_page, None);
+
+    // the registry page has to be passed while the raffle is listed
+    let accounts = raffle::accounts::CancelRaffle {
+        owner: test.owner,
+        raffle: test.raffle_address("cancelled"),
+        registry_page: None,
+    };
+    assert_error(
+        test.svm.process(&instruction(accounts, raffle::instruction::CancelRaffle {
+            raffle_id: test.raffle_id("cancelled"),
+        })),
+        RaffleError::RegistryPageRequired,
+    );
+
+    test.cancel("cancelled").unwrap();
+    assert_eq!(test.raffle("cancelled").registry_page, None);
+    test.close_raffle("cancelled").unwrap();
+
+    // so do draws and expiries of raffles that did not sell out
+    test.buy(test.buyers[0], "drawn").unwrap();
+    test.svm.warp_to(end_time);
+    test.pick_winner(test.owner, "drawn").unwrap();
+    assert_eq!(test.registry_page(0).raffles, vec![test.raffle_address("expired")]);
+    test.expire_raffle("expired").unwrap();
+    assert!(test.registry_page(0).raffles.is_empty());
+
+    test.pick_winner(test.owner, "closed").unwrap();
+    let (ticket_id, winner) = test.winner("closed", 0);
+    test.claim(winner, "closed", ticket_id).unwrap();
+    test.close_ticket(winner, "closed", ticket_id).unwrap();
+    test.close_participant(winner, "closed").unwrap();
+    test.close_raffle("closed").unwrap();
+}
+
+#[test]
+fn create_raffle_stores_its_metadata() {
+    let mut test = setup();
+    test.create_raffle("weekly", CreateRaffleArgs { metadata: Some(metadata_args()), ..args(3) }).unwrap();
+
+    let metadata: RaffleMetadata = test.svm.get(&metadata_pda(&test.raffle_address("weekly")).0);
+    assert_eq!(metadata.raffle, test.raffle_address("weekly"));
+    assert_eq!(metadata.title, "Weekly draw");
+    assert_eq!(metadata.uri, "https://arweave.net/weekly.png");
+    assert_eq!(metadata.category, RaffleCategory::Gaming);
+    assert_eq!(metadata.tags, vec!["weekly", "sol"]);
+}
+
+#[test]
+fn close_raffle_closes_its_metadata() {
+    let mut test = setup();
+    let args = CreateRaffleArgs { metadata: Some(metadata_args()), ..args(3) };
+    test.create_raffle("weekly", args.clone()).unwrap();
+    let metadata = metadata_pda(&test.raffle_address("weekly")).0;
+    test.cancel("weekly").unwrap();
+
+    let balance = test.svm.lamports(&test.owner);
+    test.close_raffle("weekly").unwrap();
+    assert!(test.svm.account(&metadata).is_none());
+    assert_eq!(
+        test.svm.lamports(&test.owner),
+        balance + rent(8 + Raffle::INIT_SPACE) + rent(8 + RaffleMetadata::INIT_SPACE)
+    );
+}
+
+#[test]
+fn create_raffle_rejects_oversized_metadata() {
+    let mut test = setup();
+    let long = |len: usize| "x".repeat(len);
+
+    let cases = [
+        (RaffleMetadataArgs { title: long(MAX_TITLE_LEN + 1), ..metadata_args() }, RaffleError::TitleTooLong),
+        (
+            RaffleMetadataArgs { description: long(MAX_DESCRIPTION_LEN + 1), ..metadata_args() },
+            RaffleError::DescriptionTooLong,
+        ),
+        (RaffleMetadataArgs { uri: long(MAX_URI_LEN + 1), ..metadata_args() }, RaffleError::UriTooLong),
+        (RaffleMetadataArgs { tags: vec![long(1); MAX_TAGS + 1], ..metadata_args() }, RaffleError::TooManyTags),
+        (RaffleMetadataArgs { tags: vec![long(MAX_TAG_LEN + 1)], ..metadata_args() }, RaffleError::TagTooLong),
+    ];
+    for (metadata, error) in cases {
+        assert_error(
+            test.create_raffle("weekly", CreateRaffleArgs { metadata: Some(metadata), ..args(3) }),
+            error,
+        );
+    }
+
+    let full = RaffleMetadataArgs {
+        title: long(MAX_TITLE_LEN),
+        description: long(MAX_DESCRIPTION_LEN),
+        uri: long(MAX_URI_LEN),
+        category: RaffleCategory::Other,
+        tags: vec![long(MAX_TAG_LEN); MAX_TAGS],
+    };
+    test.create_raffle("weekly", CreateRaffleArgs { metadata: Some(full), ..args(3) }).unwrap();
+}
+
+#[test]
+fn update_metadata_is_owner_only_until_the_first_sale() {
+    let mut test = setup();
+    let owner = test.owner;
+    test.create_raffle("weekly", args(3)).unwrap();
+
+    // the raffle address is derived from the signer, so nobody else can find it
+    assert_error(
+        test.update_metadata(test.buyers[0], "weekly", metadata_args()),
+        ErrorCode::AccountNotInitialized,
+    );
+
+    // raffles created without metadata get their account on the first update
+    test.update_metadata(owner, "weekly", metadata_args()).unwrap();
+    let renamed = RaffleMetadataArgs { title: "Renamed".to_string(), tags: Vec::new(), ..metadata_args() };
+    test.update_metadata(owner, "weekly", renamed).unwrap();
+    let metadata: RaffleMetadata = test.svm.get(&metadata_pda(&test.raffle_address("weekly")).0);
+    assert_eq!(metadata.title, "Renamed");
+    assert!(metadata.tags.is_empty());
+
+    test.buy(test.buyers[0], "weekly").unwrap();
+    assert_error(test.update_metadata(owner, "weekly", metadata_args()), RaffleError::MetadataLocked);
+}
+
+#[test]
+fn protocol_pause_halts_raffles_but_not_payouts() {
+    let mut test = setup();
+    test.sold_out_raffle("drawn", args(2));
+    test.create_raffle("open", args(3)).unwrap();
+    test.buy(test.buyers[0], "open").unwrap();
+
+    assert_error(test.set_protocol_paused(test.buyers[0], true), ErrorCode::ConstraintHasOne);
+    let receipt = test.set_protocol_paused(test.admin, true).unwrap();
+    let events = receipt.events::<PauseUpdated>();
+    assert_eq!((events[0].raffle, events[0].paused), (None, true));
+
+    assert_error(test.create_raffle("weekly", args(3)), RaffleError::ProtocolPaused);
+    assert_error(test.buy(test.buyers[1], "open"), RaffleError::ProtocolPaused);
+    assert_error(test.buy_tickets(test.buyers[1], "open", 2), RaffleError::ProtocolPaused);
+    assert_error(test.pick_winner(test.owner, "drawn"), RaffleError::ProtocolPaused);
+    assert_error(test.create_series("weekly", series_args()), RaffleError::ProtocolPaused);
+
+    // funds can still leave a paused protocol
+    test.cancel("open").unwrap();
+    test.refund_ticket(test.buyers[0], "open", 1).unwrap();
+
+    test.set_protocol_paused(test.admin, false).unwrap();
+    test.pick_winner(test.owner, "drawn").unwrap();
+    let (ticket_id, winner) = test.winner("drawn", 0);
+    test.set_protocol_paused(test.admin, true).unwrap();
+    test.claim(winner, "drawn", ticket_id).unwrap();
+    assert_eq!(test.raffle("drawn").status, RaffleStatus::Claimed);
+}
+
+#[test]
+fn raffle_pause_is_owner_only_and_scoped_to_the_raffle() {
+    let mut test = setup();
+    test.create_raffle("weekly", args(3)).unwrap();
+    test.create_raffle("daily", args(3)).unwrap();
+    test.buy(test.buyers[0], "weekly").unwrap();
+
+    let owner = test.owner;
+    // the raffle address is derived from the signer, so nobody else can find it
+    assert_error(test.set_raffle_paused(test.buyers[0], "weekly", true), ErrorCode::AccountNotInitialized);
+    let receipt = test.set_raffle_paused(owner, "weekly", true).unwrap();
+    let events = receipt.events::<PauseUpdated>();
+    assert_eq!((events[0].raffle, events[0].paused), (Some(test.raffle_address("weekly")), true));
+    assert!(test.raffle("weekly").paused);
+
+    assert_error(test.buy(test.buyers[1], "weekly"), RaffleError::RafflePaused);
+    test.buy(test.buyers[1], "daily").unwrap();
+
+    test.set_raffle_paused(owner, "weekly", false).unwrap();
+    test.buy(test.buyers[1], "weekly").unwrap();
+
+    test.set_raffle_paused(owner, "weekly", true).unwrap();
+    test.cancel("weekly").unwrap();
+    test.refund_ticket(test.buyers[0], "weekly", 1).unwrap();
+    assert_error(test.set_raffle_paused(owner, "weekly", false), RaffleError::RaffleNotActive);
+}
+
+#[test]
+fn raffle_pause_can_not_hold_back_a_sold_out_draw() {
+    let mut test = setup();
+    test.sold_out_raffle("weekly", args(2));
+
+    assert_error(test.set_raffle_paused(test.owner, "weekly", true), RaffleError::RaffleNotActive);
+    test.pick_winner(test.buyers[3], "weekly").unwrap();
+}
+
+#[test]
+fn raffle_pause_can_not_hold_back_an_ended_draw() {
+    let mut test = setup();
+    let end_time = test.svm.clock().unix_timestamp + 3600;
+    test.create_raffle("weekly", CreateRaffleArgs { end_time: Some(end_time), ..args(3) }).unwrap();
+    test.create_raffle("daily", CreateRaffleArgs { end_time: Some(end_time), ..args(3) }).unwrap();
+    for raffle_id in ["weekly", "daily"] {
+        test.buy(test.buyers[0], raffle_id).unwrap();
+    }
+    test.set_raffle_paused(test.owner, "daily", true).unwrap();
+
+    test.svm.warp_to(end_time);
+    assert_error(test.set_raffle_paused(test.owner, "weekly", true), RaffleError::RaffleNotActive);
+    test.pick_winner(test.buyers[3], "weekly").unwrap();
+
+    // a pause set before the end time holds back the expiry as well as the draw
+    test.svm.warp_to(end_time + DRAW_TIMEOUT);
+    assert_error(test.pick_winner(test.buyers[3], "daily"), RaffleError::RafflePaused);
+    assert_error(test.expire_raffle("daily"), RaffleError::RafflePaused);
+
+    test.set_raffle_paused(test.owner, "daily", false).unwrap();
+    test.pick_winner(test.buyers[3], "daily").unwrap();
+}
+
+#[test]
+fn migrate_raffle_upgrades_legacy_accounts() {
+    let mut test = setup();
+    let raffle_address = test.raffle_address("legacy");
+    test.set_legacy_raffle("legacy");
+
+    let migrate = |owner: Pubkey| {
+        let accounts = raffle::accounts::MigrateRaffle {
+            owner,
+            raffle: raffle_address,
+            system_program: system_program::ID,
+        };
+        instruction(accounts, raffle::instruction::MigrateRaffle { raffle_id: "legacy".to_string() })
+    };
+    test.svm.process(&migrate(test.owner)).unwrap();
+
+    let raffle = test.raffle("legacy");
+    assert_eq!(raffle.status, RaffleStatus::Drawn);
+    assert_eq!(raffle.winning_ticket_ids(), vec![2]);
+    assert_eq!(raffle.created_at, 42);
+    assert_eq!(raffle.ticket_sales, 3 * TICKET_PRICE);
+    assert_eq!(
+        test.svm.lamports(&raffle_address),
+        rent(8 + Raffle::INIT_SPACE) + 3 * TICKET_PRICE
+    );
+    assert_error(test.svm.process(&migrate(test.owner)), RaffleError::InvalidLegacyAccount);
+}
+
+#[test]
+fn migrate_ticket_lets_legacy_winners_claim() {
+    let mut test = setup();
+    let winner = test.buyers[1];
+    let raffle_address = test.raffle_address("legacy");
+    test.set_legacy_raffle("legacy");
+    let accounts = raffle::accounts::MigrateRaffle {
+        owner: test.owner,
+        raffle: raffle_address,
+        system_program: system_program::ID,
+    };
+    test.svm
+        .process(&instruction(accounts, raffle::instruction::MigrateRaffle { raffle_id: "legacy".to_string() }))
+        .unwrap();
+
+    let (ticket, bump) = ticket_pda(&raffle_address, 2);
+    let legacy_len = 8 + LegacyTicket::INIT_SPACE;
+    let mut data = Ticket::DISCRIMINATOR.to_vec();
+    (raffle_address, 2u32, winner, bump).serialize(&mut data).unwrap();
+    assert_eq!(data.len(), legacy_len);
+    test.svm.set_account(ticket, Account { lamports: rent(legacy_len), data, owner: PROGRAM_ID, executable: false });
+    assert_error(test.claim(winner, "legacy", 2), ErrorCode::AccountDidNotDeserialize);
+
+    let migrate = |payer: Pubkey| {
+        let accounts = raffle::accounts::MigrateTicket {
+            payer,
+            raffle: raffle_address,
+            ticket,
+            system_program: system_program::ID,
+        };
+        instruction(accounts, raffle::instruction::MigrateTicket { raffle_id: "legacy".to_string(), ticket_id: 2 })
+    };
+    test.svm.process(&migrate(test.buyers[0])).unwrap();
+    let migrated: Ticket = test.svm.get(&ticket);
+    assert_eq!((migrated.owner, migrated.price_paid), (winner, TICKET_PRICE));
+    assert_eq!(test.svm.lamports(&ticket), rent(8 + Ticket::INIT_SPACE));
+    assert_error(test.svm.process(&migrate(test.buyers[0])), RaffleError::InvalidLegacyAccount);
+
+    let balance = test.svm.lamports(&winner);
+    test.claim(winner, "legacy", 2).unwrap();
+    assert_eq!(test.svm.lamports(&winner), balance + 3 * TICKET_PRICE);
+}
+
+
+fn series_args() -> CreateSeriesArgs {
+    CreateSeriesArgs {
+        ticket_price: TICKET_PRICE,
+        max_entries: 2,
+        round_duration: Some(7 * 24 * 60 * 60),
+        prize_amount: 0,
+        creator_fee_bps: 500,
+        prize_tiers: vec![10_000],
+        max_tickets_per_wallet: None,
+        crank_reward_bps: 0,
+    }
+}
+
+impl Test {
+    fn create_series(&mut self, series_id: &str, args: CreateSeriesArgs) -> TxResult {
+        self.create_prize_series(series_id, args, None)
+    }
+
+    /// Creates a series whose rounds each escrow `args.prize_amount` tokens of
+    /// `prize_mint`.
+    fn create_prize_series(&mut self, series_id: &str, args: CreateSeriesArgs, prize_mint: Option<Pubkey>) -> TxResult {
+        let accounts = raffle::accounts::CreateSeries {
+            owner: self.owner,
+            series: series_pda(&self.owner, series_id).0,
+            config: config_pda().0,
+            ticket_mint: None,
+            prize_mint,
+            system_program: system_program::ID,
+        };
+
+        self.svm.process(&instruction(accounts, raffle::instruction::CreateSeries {
+            series_id: series_id.to_string(),
+            args,
+        }))
+    }
+
+    fn start_next_round(&mut self, series_id: &str, previous_round: Option<u32>, roll_prize: bool) -> TxResult {
+        let series = series_pda(&self.owner, series_id).0;
+        let RaffleSeries { rounds, prize_mint, .. } = self.svm.get(&series);
+        let round = self.raffle_address(&format!("{series_id}-{}", rounds + 1));
+        let previous_round = previous_round
+            .map(|round| self.raffle_address(&format!("{series_id}-{round}")));
+        let accounts = raffle::accounts::StartNextRound {
+            owner: self.owner,
+            series,
+            round,
+            previous_round,
+            config: config_pda().0,
+            global_registry: global_registry_pda().0,
+            registry_page: registry_page_pda(self.current_registry_page()).0,
+            ticket_mint: None,
+            escrow: None,
+            token_program: None,
+            prize_mint,
+            owner_prize_account: prize_mint.map(|_| self.token_accounts[&self.owner]),
+            previous_prize_vault: previous_round
+                .filter(|_| roll_prize && prize_mint.is_some())
+                .map(|previous| prize_vault_pda(&previous)),
+            prize_vault: prize_mint.map(|_| prize_vault_pda(&round)),
+            prize_token_program: prize_mint.map(|mint| self.token_program(&mint)),
+            system_program: system_program::ID,
+        };
+
+        self.svm.process(&instruction(accounts, raffle::instruction::StartNextRound {
+            series_id: series_id.to_string(),
+            roll_prize,
+        }))
+    }
+}
+
+#[test]
+fn create_series_validates_the_template() {
+    let mut test = setup();
+
+    let cases = [
+        ("", series_args(), RaffleError::InvalidSeriesId),
+        ("a-very-long-series", series_args(), RaffleError::InvalidSeriesId),
+        ("weekly", CreateSeriesArgs { ticket_price: 0, ..series_args() }, RaffleError::InvalidTicketPrice),
+        ("weekly", CreateSeriesArgs { round_duration: Some(0), ..series_args() }, RaffleError::InvalidRoundDuration),
+        ("weekly", CreateSeriesArgs { prize_tiers: vec![9_000], ..series_args() }, RaffleError::InvalidPrizeTiers),
+        ("weekly", CreateSeriesArgs { creator_fee_bps: 9_901, ..series_args() }, RaffleError::InvalidFee),
+        ("weekly", CreateSeriesArgs { prize_amount: 1, ..series_args() }, RaffleError::PrizeAccountsRequired),
+    ];
+    for (series_id, args, error) in cases {
+        assert_error(test.create_series(series_id, args), error);
+    }
+
+    test.create_series("weekly", series_args()).unwrap();
+    let series: RaffleSeries = test.svm.get(&series_pda(&test.owner, "weekly").0);
+    assert_eq!((series.owner, series.rounds), (test.owner, 0));
+}
+
+#[test]
+fn start_next_round_opens_rounds_from_the_template() {
+    let mut test = setup();
+    test.create_series("weekly", series_args()).unwrap();
+
+    let receipt = test.start_next_round("weekly", None, false).unwrap();
+
+    let round = test.raffle("weekly-1");
+    let now = test.svm.clock().unix_timestamp;
+    assert_eq!(round.owner, test.owner);
+    assert_eq!(round.status, RaffleStatus::Open);
+    assert_eq!((round.ticket_price, round.max_entries), (TICKET_PRICE, 2));
+    assert_eq!(round.end_time, Some(now + 7 * 24 * 60 * 60));
+    assert_eq!((round.creator_fee_bps, round.protocol_fee_bps), (500, PROTOCOL_FEE_BPS));
+    let event = &receipt.events::<RoundStarted>()[0];
+    assert_eq!((event.round, event.raffle), (1, test.raffle_address("weekly-1")));
+    assert_eq!(receipt.events::<RaffleCreated>()[0].raffle_id, "weekly-1");
+    // and are counted and listed like any other raffle
+    // rounds are derived from their series, not counted as the owner's raffles
+    assert!(test.svm.account(&registry_pda(&test.owner).0).is_none());
+    assert_eq!(round.registry_page, Some(0));
+    assert_eq!(test.registry_page(0).raffles, vec![test.raffle_address("weekly-1")]);
+
+    // rounds are regular raffles
+    test.buy(test.buyers[0], "weekly-1").unwrap();
+    test.buy(test.buyers[1], "weekly-1").unwrap();
+    test.pick_winner(test.owner, "weekly-1").unwrap();
+    let (ticket_id, winner) = test.winner("weekly-1", 0);
+    test.claim(winner, "weekly-1", ticket_id).unwrap();
+
+    // the pool of a drawn round went to its winner
+    assert_error(test.start_next_round("weekly", Some(1), true), RaffleError::NoPrizeToRoll);
+    test.start_next_round("weekly", Some(1), false).unwrap();
+    assert_eq!(test.raffle("weekly-2").status, RaffleStatus::Open);
+    assert_eq!(test.svm.get::<RaffleSeries>(&series_pda(&test.owner, "weekly").0).rounds, 2);
+}
+
+#[test]
+fn start_next_round_waits_for_the_latest_round() {
+    let mut test = setup();
+    test.create_series("weekly", series_args()).unwrap();
+    test.start_next_round("weekly", None, false).unwrap();
+
+    assert_error(test.start_next_round("weekly", None, false), RaffleError::PreviousRoundRequired);
+    assert_error(test.start_next_round("weekly", Some(1), false), RaffleError::RoundNotFinished);
+
+    // an undersold round finishes once it expires
+    let end_time = test.raffle("weekly-1").end_time.unwrap();
+    test.buy(test.buyers[0], "weekly-1").unwrap();
+    test.svm.warp_to(end_time + DRAW_TIMEOUT);
+    test.expire_raffle("weekly-1").unwrap();
+
+    // ticket pools are refunded, only escrowed prizes roll over
+    assert_error(test.start_next_round("weekly", Some(1), true), RaffleError::NoPrizeToRoll);
+    test.start_next_round("weekly", Some(1), false).unwrap();
+    assert_eq!(test.raffle("weekly-2").created_at, end_time + DRAW_TIMEOUT);
+}
+
+#[test]
+fn start_next_round_rolls_the_prize_of_a_cancelled_round() {
+    let mut test = setup();
+    let prize_mint = test.create_mint(spl_token::ID, 1_000);
+    let args = CreateSeriesArgs { prize_amount: 400, ..series_args() };
+    test.create_prize_series("weekly", args, Some(prize_mint)).unwrap();
+    test.start_next_round("weekly", None, false).unwrap();
+    let first_vault = prize_vault_pda(&test.raffle_address("weekly-1"));
+    assert_eq!(test.svm.token_balance(&first_vault), 400);
+
+    test.cancel("weekly-1").unwrap();
+    let receipt = test.start_next_round("weekly", Some(1), true).unwrap();
+
+    // the new round escrows the template prize on top of the rolled one
+    let second_vault = prize_vault_pda(&test.raffle_address("weekly-2"));
+    assert_eq!(receipt.events::<RoundStarted>()[0].rolled_prize_amount, 400);
+    assert_eq!(test.raffle("weekly-2").prize_amount, 800);
+    assert_eq!(test.svm.token_balance(&second_vault), 800);
+    assert_eq!(test.token_balance(&test.owner), 200);
+    assert!(test.svm.account(&first_vault).is_none());
+    assert_eq!(test.raffle("weekly-1").prize_amount, 0);
+}
+
+#[test]
+fn reclaim_prize_returns_the_escrow_of_refundable_raffles() {
+    let mut test = setup();
+    let prize_mint = test.create_mint(spl_token::ID, 1_000);
+    let prize_args = CreateRaffleArgs { prize_amount: 1_000, ..args(2) };
+    test.create_token_raffle("weekly", prize_args, None, Some(prize_mint)).unwrap();
+    test.create_raffle("daily", args(2)).unwrap();
+    test.buy(test.buyers[0], "weekly").unwrap();
+
+    assert_error(test.reclaim_prize("weekly"), RaffleError::RaffleNotRefundable);
+    test.cancel("daily").unwrap();
+    assert_error(test.reclaim_prize("daily"), RaffleError::PrizeAccountsRequired);
+
+    test.cancel("weekly").unwrap();
+    let owner_balance = test.svm.lamports(&test.owner);
+    let receipt = test.reclaim_prize("weekly").unwrap();
+
+    let event = &receipt.events::<PrizeReclaimed>()[0];
+    assert_eq!((event.creator, event.mint, event.amount), (test.owner, prize_mint, 1_000));
+    assert_eq!(test.token_balance(&test.owner), 1_000);
+    let prize_vault = prize_vault_pda(&test.raffle_address("weekly"));
+    assert!(test.svm.account(&prize_vault).is_none());
+    assert_eq!(test.svm.lamports(&test.owner), owner_balance + rent(spl_token::state::Account::LEN));
+    assert_eq!(test.raffle("weekly").prize_amount, 0);
+    // the vault is gone, so the prize can not be reclaimed twice
+    assert_error(test.reclaim_prize("weekly"), ErrorCode::AccountNotInitialized);
+}
+
+#[test]
+fn token_raffles_pay_out_of_the_escrow() {
+    let mut test = setup();
+    let mint = test.create_mint(spl_token::ID, SOL);
+    let args = CreateRaffleArgs { creator_fee_bps: 500, ..args(2) };
+    test.create_token_raffle("weekly", args, Some(mint), None).unwrap();
+    let escrow = escrow_pda(&test.raffle_address("weekly"));
+    for buyer in test.buyers.clone().into_iter().take(2) {
+        test.buy_with_tokens(buyer, "weekly").unwrap();
+        assert_eq!(test.token_balance(&buyer), SOL - TICKET_PRICE);
+    }
+    assert_eq!(test.svm.token_balance(&escrow), 2 * TICKET_PRICE);
+
+    test.pick_winner(test.buyers[3], "weekly").unwrap();
+    let (ticket_id, winner) = test.winner("weekly", 0);
+    test.claim_with_tokens(winner, "weekly", ticket_id).unwrap();
+
+    let pool = 2 * TICKET_PRICE;
+    let creator_amount = pool * 500 / 10_000;
+    let protocol_amount = pool * PROTOCOL_FEE_BPS as u64 / 10_000;
+    assert_eq!(test.token_balance(&winner), SOL - TICKET_PRICE + pool - creator_amount - protocol_amount);
+    assert_eq!(test.token_balance(&test.owner), SOL + creator_amount);
+    assert_eq!(test.token_balance(&test.treasury), SOL + protocol_amount);
+    assert_eq!(test.svm.token_balance(&escrow), 0);
+}
+
+#[test]
+fn token_2022_raffles_refund_out_of_the_escrow() {
+    let mut test = setup();
+    let buyer = test.buyers[0];
+    let mint = test.create_mint(spl_token_2022::ID, SOL);
+    test.create_token_raffle("weekly", args(3), Some(mint), None).unwrap();
+    test.buy_with_tokens(buyer, "weekly").unwrap();
+    assert_eq!(test.token_balance(&buyer), SOL - TICKET_PRICE);
+
+    test.cancel("weekly").unwrap();
+    test.refund_with_tokens(buyer, "weekly", 1).unwrap();
+
+    assert_eq!(test.token_balance(&buyer), SOL);
+    assert_eq!(test.svm.token_balance(&escrow_pda(&test.raffle_address("weekly"))), 0);
+}
+
+#[test]
+fn transfer_fee_mints_are_rejected() {
+    let mut test = setup();
+    let fee_mint = test.svm.create_transfer_fee_mint(&test.admin, 6);
+    assert_error(
+        test.create_token_raffle("weekly", args(3), Some(fee_mint), None),
+        RaffleError::UnsupportedMint
+    );
+    let owner_account = test.svm.create_token_account(&fee_mint, &test.owner, 1_000);
+    test.token_accounts.insert(test.owner, owner_account);
+    let prize_args = CreateRaffleArgs { prize_amount: 1_000, ..args(2) };
+    assert_error(
+        test.create_token_raffle("weekly", prize_args, None, Some(fee_mint)),
+        RaffleError::UnsupportedMint
+    );
+
+    let mint = test.create_mint(spl_token_2022::ID, SOL);
+    test.create_token_raffle("weekly", args(3), Some(mint), None).unwrap();
+}
+
+#[test]
+fn escrowed_prizes_are_released_to_the_winner() {
+    let mut test = setup();
+    let prize_mint = test.create_mint(spl_token::ID, 1_000);
+    let args = CreateRaffleArgs { prize_amount: 1_000, ..args(2) };
+    test.create_token_raffle("weekly", args, None, Some(prize_mint)).unwrap();
+    let prize_vault = prize_vault_pda(&test.raffle_address("weekly"));
+    assert_eq!(test.token_balance(&test.owner), 0);
+    assert_eq!(test.svm.token_balance(&prize_vault), 1_000);
+
+    for buyer in test.buyers.clone().into_iter().take(2) {
+        test.buy(buyer, "weekly").unwrap();
+    }
+    test.pick_winner(test.buyers[3], "weekly").unwrap();
+    let (ticket_id, winner) = test.winner("weekly", 0);
+    let owner_balance = test.svm.lamports(&test.owner);
+    test.claim_with_tokens(winner, "weekly", ticket_id).unwrap();
+
+    // the winner takes the prize asset and the creator the ticket sales
+    let pool = 2 * TICKET_PRICE;
+    let protocol_amount = pool * PROTOCOL_FEE_BPS as u64 / 10_000;
+    assert_eq!(test.token_balance(&winner), 2_000);
+    assert!(test.svm.account(&prize_vault).is_none());
+    let vault_rent = rent(spl_token::state::Account::LEN);
+    assert_eq!(test.svm.lamports(&test.owner), owner_balance + pool - protocol_amount + vault_rent);
+    assert_eq!(test.raffle("weekly").prize_amount, 0);
+}
+
+#[test]
+fn creators_withdraw_proceeds_without_waiting_for_the_winner() {
+    let mut test = setup();
+    let prize_mint = test.create_mint(spl_token::ID, 1_000);
+    let prize_args = CreateRaffleArgs { prize_amount: 1_000, ..args(2) };
+    test.create_token_raffle("weekly", prize_args, None, Some(prize_mint)).unwrap();
+    test.create_raffle("daily", args(2)).unwrap();
+    for buyer in test.buyers.clone().into_iter().take(2) {
+        test.buy(buyer, "weekly").unwrap();
+        test.buy(buyer, "daily").unwrap();
+    }
+
+    assert_error(test.withdraw_proceeds("weekly"), RaffleError::WinnerNotChosen);
+    test.pick_winner(test.buyers[3], "weekly").unwrap();
+    test.pick_winner(test.buyers[3], "daily").unwrap();
+    // the winner of the ticket pool is paid the proceeds
+    assert_error(test.withdraw_proceeds("daily"), RaffleError::NoProceedsToWithdraw);
+
+    let owner_balance = test.svm.lamports(&test.owner);
+    let treasury_balance = test.svm.lamports(&test.treasury);
+    let receipt = test.withdraw_proceeds("weekly").unwrap();
+    let pool = 2 * TICKET_PRICE;
+    let protocol_amount = pool * PROTOCOL_FEE_BPS as u64 / 10_000;
+    assert_eq!(receipt.events::<ProceedsWithdrawn>()[0].creator_amount, pool - protocol_amount);
+    assert_eq!(test.svm.lamports(&test.owner), owner_balance + pool - protocol_amount);
+    assert_eq!(test.svm.lamports(&test.treasury), treasury_balance + protocol_amount);
+    assert_error(test.withdraw_proceeds("weekly"), RaffleError::NoProceedsToWithdraw);
+
+    // the claim then only releases the prize asset
+    let (ticket_id, winner) = test.winner("weekly", 0);
+    let owner_balance = test.svm.lamports(&test.owner);
+    let receipt = test.claim_with_tokens(winner, "weekly", ticket_id).unwrap();
+    assert_eq!(receipt.events::<PrizeClaimed>()[0].creator_amount, 0);
+    assert_eq!(test.token_balance(&winner), 2_000);
+    assert_eq!(test.svm.lamports(&test.owner), owner_balance + rent(spl_token::state::Account::LEN));
+    assert_eq!(test.raffle("weekly").status, RaffleStatus::Claimed);
+}
@@ -0,0 +1,591 @@
+//! Minimal in-process runtime for the raffle program. Instructions are
+//! serialized into the loader's input layout and dispatched straight to
+//! `raffle::entry`, while syscall stubs stand in for the clock and rent
+//! sysvars, event logs and cross-program invocations. Invocations of the
+//! system program are handled here, and those of the SPL token and Token-2022
+//! programs run their native processors.
+//!
+//! Account changes are checked at every program boundary the way the runtime
+//! checks them: only the owning program may change an account's data or owner
+//! or debit its lamports, any change needs the account to be writable,
+//! invocations can not gain signer or writable privileges, and accounts must
+//! not be left short of rent at the end of the transaction.
+//!
+//! The program runs natively rather than as its SBF build, so compute limits,
+//! the loader's realloc bounds and the runtime's own CPI account checks are
+//! not exercised here. This stands in for LiteSVM until the suite can load
+//! the compiled `.so` files of the raffle, mock oracle and SPL token programs.
+
+use std::{cell::RefCell, collections::HashMap, mem::take, sync::Once};
+
+use anchor_lang::{
+    prelude::*,
+    solana_program::{
+        account_info::MAX_PERMITTED_DATA_INCREASE,
+        entrypoint::{deserialize, ProgramResult, BPF_ALIGN_OF_U128, NON_DUP_MARKER},
+        instruction::Instruction,
+        program_pack::Pack,
+        program_stubs::{set_syscall_stubs, SyscallStubs},
+    },
+    system_program,
+    AccountDeserialize,
+    Discriminator,
+    Event,
+};
+
+// custom errors returned by the system program
+const ACCOUNT_ALREADY_IN_USE: u32 = 0;
+const RESULT_WITH_NEGATIVE_LAMPORTS: u32 = 1;
+
+static INSTALL_STUBS: Once = Once::new();
+
+thread_local! {
+    // the stubs are installed process wide, so state lives per test thread
+    static RUNTIME: RefCell<Runtime> = RefCell::new(Runtime::default());
+}
+
+#[derive(Default)]
+struct Runtime {
+    // programs currently executing, the innermost invocation last
+    call_stack: Vec<Pubkey>,
+    clock: Clock,
+    events: Vec<Vec<u8>>,
+    // account states at the last program boundary
+    snapshots: HashMap<Pubkey, Snapshot>,
+}
+
+/// State of an account the runtime compares against to find out what the
+/// running program changed.
+#[derive(Clone, PartialEq)]
+struct Snapshot {
+    lamports: u64,
+    data: Vec<u8>,
+    owner: Pubkey,
+    executable: bool,
+}
+
+impl Snapshot {
+    fn of(info: &AccountInfo) -> Self {
+        Self {
+            lamports: info.lamports(),
+            data: info.data.borrow().to_vec(),
+            owner: *info.owner,
+            executable: info.executable,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Account {
+    pub lamports: u64,
+    pub data: Vec<u8>,
+    pub owner: Pubkey,
+    pub executable: bool,
+}
+
+/// Event data emitted by a successful instruction.
+#[derive(Debug)]
+pub struct Receipt {
+    events: Vec<Vec<u8>>,
+}
+
+impl Receipt {
+    pub fn events<T: Event + Discriminator>(&self) -> Vec<T> {
+        self.events
+            .iter()
+            .filter_map(|data| data.strip_prefix(T::DISCRIMINATOR))
+            .map(|mut data| T::deserialize(&mut data).unwrap())
+            .collect()
+    }
+}
+
+pub struct Svm {
+    accounts: HashMap<Pubkey, Account>,
+    clock: Clock,
+}
+
+impl Svm {
+    pub fn new() -> Self {
+        INSTALL_STUBS.call_once(|| {
+            set_syscall_stubs(Box::new(Stubs));
+        });
+
+        let mut svm = Self {
+            accounts: HashMap::new(),
+            clock: Clock {
+                slot: 1,
+                unix_timestamp: 1_700_000_000,
+                ..Clock::default()
+            },
+        };
+        for program in [system_program::ID, spl_token::ID, spl_token_2022::ID] {
+            svm.set_account(program, Account {
+                lamports: 1,
+                executable: true,
+                ..Account::default()
+            });
+        }
+        // the raffle program is deployed through the upgradeable loader,
+        // pointing at its program data account
+        let program_data = program_data_address();
+        let data = [&2u32.to_le_bytes()[..], program_data.as_ref()].concat();
+        svm.set_account(raffle::ID, Account {
+            lamports: Rent::default().minimum_balance(data.len()),
+            data,
+            owner: ProgramData::owner(),
+            executable: true,
+        });
+        svm.set_upgrade_authority(None);
+
+        svm
+    }
+
+    /// Rewrites the raffle's program data account with `authority` as the
+    /// upgrade authority, or as immutable when `None`.
+    pub fn set_upgrade_authority(&mut self, authority: Option<Pubkey>) {
+        let mut data = [&3u32.to_le_bytes()[..], &0u64.to_le_bytes()].concat();
+        match authority {
+            Some(authority) => data.extend([&[1][..], authority.as_ref()].concat()),
+            None => data.extend([0; 33]),
+        }
+
+        self.set_account(program_data_address(), Account {
+            lamports: Rent::default().minimum_balance(data.len()),
+            data,
+            owner: ProgramData::owner(),
+            executable: false,
+        });
+    }
+
+    pub fn airdrop(&mut self, address: &Pubkey, lamports: u64) {
+        self.accounts.entry(*address).or_default().lamports += lamports;
+    }
+
+    pub fn set_account(&mut self, address: Pubkey, account: Account) {
+        self.accounts.insert(address, account);
+    }
+
+    pub fn account(&self, address: &Pubkey) -> Option<&Account> {
+        self.accounts.get(address)
+    }
+
+    pub fn lamports(&self, address: &Pubkey) -> u64 {
+        self.account(address).map_or(0, |account| account.lamports)
+    }
+
+    /// Deserializes the Anchor account at `address`, panicking if it is missing.
+    pub fn get<T: AccountDeserialize>(&self, address: &Pubkey) -> T {
+        let account = self.account(address).expect("account does not exist");
+        T::try_deserialize(&mut account.data.as_slice()).unwrap()
+    }
+
+    /// Creates an initialized mint of `token_program` with no supply.
+    pub fn create_mint(&mut self, token_program: &Pubkey, authority: &Pubkey, decimals: u8) -> Pubkey {
+        let mint = spl_token::state::Mint {
+            mint_authority: Some(*authority).into(),
+            supply: 0,
+            decimals,
+            is_initialized: true,
+            freeze_authority: None.into(),
+        };
+        let mut data = vec![0; spl_token::state::Mint::LEN];
+        spl_token::state::Mint::pack(mint, &mut data).unwrap();
+
+        let address = Pubkey::new_unique();
+        self.set_account(address, Account {
+            lamports: Rent::default().minimum_balance(data.len()),
+            data,
+            owner: *token_program,
+            executable: false,
+        });
+
+        address
+    }
+
+    /// Creates a Token-2022 mint carrying a transfer fee configuration.
+    pub fn create_transfer_fee_mint(&mut self, authority: &Pubkey, decimals: u8) -> Pubkey {
+        use spl_token_2022::{
+            extension::{
+                transfer_fee::TransferFeeConfig, BaseStateWithExtensionsMut, ExtensionType,
+                StateWithExtensionsMut,
+            },
+            state::Mint,
+        };
+
+        let len = ExtensionType::try_calculate_account_len::<Mint>(&[ExtensionType::TransferFeeConfig])
+            .unwrap();
+        let mut data = vec![0; len];
+        let mut state = StateWithExtensionsMut::<Mint>::unpack_uninitialized(&mut data).unwrap();
+        let fee_config = state.init_extension::<TransferFeeConfig>(true).unwrap();
+        fee_config.newer_transfer_fee.transfer_fee_basis_points = 100.into();
+        state.base = Mint {
+            mint_authority: Some(*authority).into(),
+            supply: 0,
+            decimals,
+            is_initialized: true,
+            freeze_authority: None.into(),
+        };
+        state.pack_base();
+        state.init_account_type().unwrap();
+
+        let address = Pubkey::new_unique();
+        self.set_account(address, Account {
+            lamports: Rent::default().minimum_balance(data.len()),
+            data,
+            owner: spl_token_2022::ID,
+            executable: false,
+        });
+
+        address
+    }
+
+    /// Creates a token account of `owner` holding `amount` freshly minted tokens.
+    pub fn create_token_account(&mut self, mint: &Pubkey, owner: &Pubkey, amount: u64) -> Pubkey {
+        use spl_token_2022::{extension::StateWithExtensionsMut, state::Mint};
+
+        let mint_account = self.accounts.get_mut(mint).expect("mint does not exist");
+        let mut state = StateWithExtensionsMut::<Mint>::unpack(&mut mint_account.data).unwrap();
+        state.base.supply += amount;
+        state.pack_base();
+        let token_program = mint_account.owner;
+
+        let account = spl_token::state::Account {
+            mint: *mint,
+            owner: *owner,
+            amount,
+            state: spl_token::state::AccountState::Initialized,
+            ..Default::default()
+        };
+        let mut data = vec![0; spl_token::state::Account::LEN];
+        spl_token::state::Account::pack(account, &mut data).unwrap();
+
+        let address = Pubkey::new_unique();
+        self.set_account(address, Account {
+            lamports: Rent::default().minimum_balance(data.len()),
+            data,
+            owner: token_program,
+            executable: false,
+        });
+
+        address
+    }
+
+    /// Token balance of the token account at `address`, zero once it is closed.
+    pub fn token_balance(&self, address: &Pubkey) -> u64 {
+        use spl_token_2022::{extension::StateWithExtensions, state::Account};
+
+        self.account(address).map_or(0, |account| {
+            StateWithExtensions::<Account>::unpack(&account.data).unwrap().base.amount
+        })
+    }
+
+    pub fn clock(&self) -> &Clock {
+        &self.clock
+    }
+
+    /// Moves the clock to `unix_timestamp`, advancing the slot with it.
+    pub fn warp_to(&mut self, unix_timestamp: i64) {
+        self.clock.slot += (unix_timestamp - self.clock.unix_timestamp).max(1) as u64;
+        self.clock.unix_timestamp = unix_timestamp;
+    }
+
+    pub fn warp_by(&mut self, seconds: i64) {
+        self.warp_to(self.clock.unix_timestamp + seconds);
+    }
+
+    /// Runs `instruction` as a transaction of its own. Account changes are
+    /// only committed when it succeeds, and accounts left without lamports
+    /// are removed like the runtime does.
+    pub fn process(&mut self, instruction: &Instruction) -> std::result::Result<Receipt, ProgramError> {
+        assert_eq!(instruction.program_id, raffle::ID, "only the raffle program can be invoked");
+
+        let (mut input, offsets) = self.serialize(instruction);
+        let result = {
+            let (program_id, accounts, data) = unsafe { deserialize(input.as_mut_ptr().cast()) };
+            let initial: Vec<Snapshot> = accounts.iter().map(Snapshot::of).collect();
+            RUNTIME.with_borrow_mut(|runtime| {
+                runtime.call_stack = vec![instruction.program_id];
+                runtime.clock = self.clock.clone();
+                runtime.events.clear();
+                runtime.snapshots = accounts.iter().map(|info| (*info.key, Snapshot::of(info))).collect();
+            });
+
+            raffle::entry(program_id, &accounts, data)
+                .and_then(|()| check_changes(program_id, &accounts))
+                .and_then(|()| check_rent(&initial, &accounts))
+        };
+        let events = RUNTIME.with_borrow_mut(|runtime| take(&mut runtime.events));
+        result?;
+
+        let bytes = unsafe {
+            std::slice::from_raw_parts(input.as_ptr().cast::<u8>(), input.len() * 8)
+        };
+        let before: u64 = offsets.iter().map(|(address, _)| self.lamports(address)).sum();
+        let mut after = 0;
+        for (address, offset) in offsets {
+            let account = read_account(bytes, offset);
+            after += account.lamports;
+            if account.lamports == 0 {
+                self.accounts.remove(&address);
+            } else {
+                self.accounts.insert(address, account);
+            }
+        }
+        assert_eq!(before, after, "instruction created or destroyed lamports");
+
+        Ok(Receipt { events })
+    }
+
+    /// Writes the accounts and data of `instruction` in the layout the loader
+    /// hands to a program entrypoint. Returns the buffer, aligned to eight
+    /// bytes, and the offset of each unique account's key within it.
+    fn serialize(&self, instruction: &Instruction) -> (Vec<u64>, Vec<(Pubkey, usize)>) {
+        let metas = &instruction.accounts;
+        let mut input = Vec::new();
+        let mut offsets = Vec::new();
+
+        input.extend_from_slice(&(metas.len() as u64).to_le_bytes());
+        for (index, meta) in metas.iter().enumerate() {
+            if let Some(original) = metas[..index].iter().position(|other| other.pubkey == meta.pubkey) {
+                input.push(original as u8);
+                input.extend_from_slice(&[0; 7]);
+                continue;
+            }
+
+            let same_key = metas.iter().filter(|other| other.pubkey == meta.pubkey);
+            let is_signer = same_key.clone().any(|other| other.is_signer);
+            let is_writable = same_key.clone().any(|other| other.is_writable);
+            let account = self.account(&meta.pubkey).cloned().unwrap_or_default();
+
+            input.extend_from_slice(&[NON_DUP_MARKER, is_signer as u8, is_writable as u8, account.executable as u8]);
+            // original data length, filled in by `deserialize`
+            input.extend_from_slice(&[0; 4]);
+            offsets.push((meta.pubkey, input.len()));
+            input.extend_from_slice(meta.pubkey.as_ref());
+            input.extend_from_slice(account.owner.as_ref());
+            input.extend_from_slice(&account.lamports.to_le_bytes());
+            input.extend_from_slice(&(account.data.len() as u64).to_le_bytes());
+            input.extend_from_slice(&account.data);
+            input.resize(input.len() + MAX_PERMITTED_DATA_INCREASE, 0);
+            input.resize(input.len().next_multiple_of(BPF_ALIGN_OF_U128), 0);
+            input.extend_from_slice(&u64::MAX.to_le_bytes());
+        }
+        input.extend_from_slice(&(instruction.data.len() as u64).to_le_bytes());
+        input.extend_from_slice(&instruction.data);
+        input.extend_from_slice(instruction.program_id.as_ref());
+
+        let mut aligned = vec![0u64; input.len().div_ceil(8)];
+        for (word, chunk) in aligned.iter_mut().zip(input.chunks(8)) {
+            let mut bytes = [0; 8];
+            bytes[..chunk.len()].copy_from_slice(chunk);
+            *word = u64::from_le_bytes(bytes);
+        }
+
+        (aligned, offsets)
+    }
+}
+
+pub fn program_data_address() -> Pubkey {
+    Pubkey::find_program_address(&[raffle::ID.as_ref()], &ProgramData::owner()).0
+}
+
+/// Reads back an account serialized at `offset` after the program ran.
+fn read_account(bytes: &[u8], offset: usize) -> Account {
+    let u64_at = |at: usize| u64::from_le_bytes(bytes[at..at + 8].try_into().unwrap());
+    let executable = bytes[offset - 5] != 0;
+    let owner = Pubkey::try_from(&bytes[offset + 32..offset + 64]).unwrap();
+    let lamports = u64_at(offset + 64);
+    let data_len = u64_at(offset + 72) as usize;
+    let data = bytes[offset + 80..offset + 80 + data_len].to_vec();
+
+    Account { lamports, data, owner, executable }
+}
+
+struct Stubs;
+
+impl SyscallStubs for Stubs {
+    fn sol_log_data(&self, fields: &[&[u8]]) {
+        RUNTIME.with_borrow_mut(|runtime| {
+            runtime.events.extend(fields.iter().map(|field| field.to_vec()))
+        });
+    }
+
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        let clock = RUNTIME.with_borrow(|runtime| runtime.clock.clone());
+        unsafe { var_addr.cast::<Clock>().write_unaligned(clock) };
+        0
+    }
+
+    fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+        unsafe { var_addr.cast::<Rent>().write_unaligned(Rent::default()) };
+        0
+    }
+
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        account_infos: &[AccountInfo],
+        signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        let caller = RUNTIME.with_borrow(|runtime| *runtime.call_stack.last().unwrap());
+        let signers = signers_seeds
+            .iter()
+            .map(|seeds| Pubkey::create_program_address(seeds, &caller))
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|_| ProgramError::InvalidSeeds)?;
+
+        // the callee sees the privileges of the instruction, which must not
+        // exceed what the caller holds
+        let accounts = instruction
+            .accounts
+            .iter()
+            .map(|meta| {
+                let info = account_infos
+                    .iter()
+                    .find(|info| *info.key == meta.pubkey)
+                    .ok_or(ProgramError::NotEnoughAccountKeys)?;
+                if meta.is_signer && !info.is_signer && !signers.contains(info.key) {
+                    return Err(ProgramError::MissingRequiredSignature);
+                }
+                if meta.is_writable && !info.is_writable {
+                    return Err(ProgramError::Immutable);
+                }
+                Ok(AccountInfo {
+                    is_signer: meta.is_signer,
+                    is_writable: meta.is_writable,
+                    ..info.clone()
+                })
+            })
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        check_changes(&caller, account_infos)?;
+        RUNTIME.with_borrow_mut(|runtime| runtime.call_stack.push(instruction.program_id));
+        let program_id = &instruction.program_id;
+        let result = match *program_id {
+            system_program::ID => process_system_instruction(&instruction.data, &accounts),
+            spl_token::ID => spl_token::processor::Processor::process(program_id, &accounts, &instruction.data),
+            spl_token_2022::ID => {
+                spl_token_2022::processor::Processor::process(program_id, &accounts, &instruction.data)
+            }
+            _ => Err(ProgramError::IncorrectProgramId),
+        };
+        RUNTIME.with_borrow_mut(|runtime| runtime.call_stack.pop());
+        result?;
+
+        check_changes(program_id, &accounts)
+    }
+}
+
+/// Checks the changes `program` made to `accounts` since the last program
+/// boundary against the runtime's rules, then records their current state as
+/// the next boundary. Writes to read-only accounts fail with `Immutable`, and
+/// writes only the owner may make fail with `IllegalOwner`.
+fn check_changes(program: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    RUNTIME.with_borrow_mut(|runtime| {
+        for info in accounts {
+            let after = Snapshot::of(info);
+            let before = runtime.snapshots.get(info.key).expect("account is not part of the transaction");
+            if *before == after {
+                continue;
+            }
+            if !info.is_writable || before.executable != after.executable {
+                return Err(ProgramError::Immutable);
+            }
+            let owned = before.owner == *program;
+            let debited = after.lamports < before.lamports;
+            if !owned && (before.data != after.data || before.owner != after.owner || debited) {
+                return Err(ProgramError::IllegalOwner);
+            }
+            // an account only changes hands once its data is cleared
+            if before.owner != after.owner && after.data.iter().any(|&byte| byte != 0) {
+                return Err(ProgramError::IllegalOwner);
+            }
+            runtime.snapshots.insert(*info.key, after);
+        }
+        Ok(())
+    })
+}
+
+/// Fails the transaction if it leaves a writable account short of rent,
+/// unless the account already was and neither grew nor gained lamports.
+fn check_rent(initial: &[Snapshot], accounts: &[AccountInfo]) -> ProgramResult {
+    let rent = Rent::default();
+    let rent_paying = |account: &Snapshot| {
+        account.lamports > 0 && !rent.is_exempt(account.lamports, account.data.len())
+    };
+
+    for (before, info) in initial.iter().zip(accounts).filter(|(_, info)| info.is_writable) {
+        let after = Snapshot::of(info);
+        let still_paying = rent_paying(before)
+            && before.data.len() == after.data.len()
+            && after.lamports <= before.lamports;
+        if rent_paying(&after) && !still_paying {
+            return Err(ProgramError::AccountNotRentExempt);
+        }
+    }
+
+    Ok(())
+}
+
+/// Handles the system program instructions the raffle program invokes.
+fn process_system_instruction(data: &[u8], accounts: &[AccountInfo]) -> ProgramResult {
+    let u64_at = |at: usize| {
+        data.get(at..at + 8)
+            .map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap()))
+            .ok_or(ProgramError::InvalidInstructionData)
+    };
+    let pubkey_at = |at: usize| {
+        data.get(at..at + 32)
+            .map(|bytes| Pubkey::try_from(bytes).unwrap())
+            .ok_or(ProgramError::InvalidInstructionData)
+    };
+    let tag = data
+        .get(..4)
+        .map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()))
+        .ok_or(ProgramError::InvalidInstructionData)?;
+
+    match (tag, accounts) {
+        // CreateAccount
+        (0, [from, to]) => {
+            if to.lamports() > 0 {
+                return Err(ProgramError::Custom(ACCOUNT_ALREADY_IN_USE));
+            }
+            transfer(from, to, u64_at(4)?)?;
+            allocate(to, u64_at(12)?)?;
+            to.assign(&pubkey_at(20)?);
+            Ok(())
+        }
+        // Assign
+        (1, [account]) => {
+            account.assign(&pubkey_at(4)?);
+            Ok(())
+        }
+        // Transfer
+        (2, [from, to]) => transfer(from, to, u64_at(4)?),
+        // Allocate
+        (8, [account]) => allocate(account, u64_at(4)?),
+        _ => Err(ProgramError::InvalidInstructionData),
+    }
+}
+
+fn transfer(from: &AccountInfo, to: &AccountInfo, lamports: u64) -> ProgramResult {
+    if *from.owner != system_program::ID || !from.data_is_empty() {
+        return Err(ProgramError::InvalidArgument);
+    }
+    if from.lamports() < lamports {
+        return Err(ProgramError::Custom(RESULT_WITH_NEGATIVE_LAMPORTS));
+    }
+
+    **from.try_borrow_mut_lamports()? -= lamports;
+    **to.try_borrow_mut_lamports()? += lamports;
+
+    Ok(())
+}
+
+fn allocate(account: &AccountInfo, space: u64) -> ProgramResult {
+    if *account.owner != system_program::ID || !account.data_is_empty() {
+        return Err(ProgramError::Custom(ACCOUNT_ALREADY_IN_USE));
+    }
+
+    account.resize(space as usize)
+}
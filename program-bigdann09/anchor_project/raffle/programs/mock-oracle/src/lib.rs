@@ -0,0 +1,48 @@
+#![allow(unexpected_cfgs)]
+// only for the IDL instructions `#[program]` generates at the crate root, which
+// still call `AccountInfo::realloc`; every module below warns on deprecations
+#![allow(deprecated)]
+
+use anchor_lang::prelude::*;
+
+declare_id!("6NY6auyS6RmTziRGsVcLow3oQhAuQyz3SaA8E2u2UxEt");
+
+/// Stand-in for a VRF oracle: writes caller supplied randomness together with
+/// the slot it was produced in, using the layout the raffle reads.
+#[program]
+#[warn(deprecated)]
+pub mod mock_oracle {
+    use super::*;
+
+    pub fn write_randomness(
+        ctx: Context<WriteRandomness>,
+        randomness: [u8; 32]
+    ) -> Result<()> {
+        ctx.accounts.randomness_account.set_inner(RandomnessAccount {
+            randomness,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct WriteRandomness<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + RandomnessAccount::INIT_SPACE,
+    )]
+    pub randomness_account: Account<'info, RandomnessAccount>,
+    pub system_program: Program<'info, System>
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct RandomnessAccount {
+    pub randomness: [u8; 32],
+    pub slot: u64,
+}
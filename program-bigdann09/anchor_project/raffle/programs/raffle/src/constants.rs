@@ -2,3 +2,56 @@ use anchor_lang::prelude::*;
 
 #[constant]
 pub const SEED: &str = "anchor";
+
+/// Time the owner has to reveal their secret once a commit-reveal raffle sells out.
+#[constant]
+pub const REVEAL_TIMEOUT: i64 = 24 * 60 * 60;
+
+/// Time left to draw an undersold raffle after its end time before anyone may
+/// expire it.
+#[constant]
+pub const DRAW_TIMEOUT: i64 = 24 * 60 * 60;
+
+/// Fees are expressed in basis points of the prize pool.
+#[constant]
+pub const MAX_FEE_BPS: u16 = 10_000;
+
+/// Largest share of the ticket pool a raffle may pay whoever draws its winners.
+#[constant]
+pub const MAX_CRANK_REWARD_BPS: u16 = 100;
+
+/// Upper bound on the number of prize tiers, and so winners, of a raffle.
+#[constant]
+pub const MAX_WINNERS: usize = 10;
+
+/// Number of separate ticket ranges a single `Entry` can hold.
+#[constant]
+pub const MAX_ENTRY_RANGES: usize = 8;
+
+/// Longest series id, leaving room for the round number in the raffle id of
+/// each round.
+#[constant]
+pub const MAX_SERIES_ID_LEN: usize = 16;
+
+/// Raffles listed on a single page of the global registry.
+#[constant]
+pub const RAFFLES_PER_PAGE: usize = 32;
+
+/// Longest raffle title, in bytes.
+#[constant]
+pub const MAX_TITLE_LEN: usize = 64;
+
+/// Longest raffle description, in bytes.
+#[constant]
+pub const MAX_DESCRIPTION_LEN: usize = 256;
+
+/// Longest image URI of a raffle, in bytes.
+#[constant]
+pub const MAX_URI_LEN: usize = 200;
+
+/// Tags a raffle may carry, each at most `MAX_TAG_LEN` bytes long.
+#[constant]
+pub const MAX_TAGS: usize = 5;
+
+#[constant]
+pub const MAX_TAG_LEN: usize = 16;
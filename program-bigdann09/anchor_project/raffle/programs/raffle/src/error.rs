@@ -31,5 +31,135 @@ pub enum RaffleError {
     #[msg("Math overflow")]
     MathOverflow,
     #[msg("Random number generation failed")]
-    RandomNumberGenerationFailed
+    RandomNumberGenerationFailed,
+    #[msg("Raffle uses commit-reveal, winner must be revealed")]
+    RevealRequired,
+    #[msg("Raffle has no commitment to reveal")]
+    NoCommitment,
+    #[msg("Revealed secret does not match commitment")]
+    InvalidReveal,
+    #[msg("Reveal timeout has not been reached")]
+    RevealTimeoutNotReached,
+    #[msg("Reveal window has closed")]
+    RevealWindowClosed,
+    #[msg("Raffle uses an oracle, randomness must be consumed")]
+    OracleRequired,
+    #[msg("Raffle has no randomness oracle configured")]
+    NoOracle,
+    #[msg("Invalid randomness oracle account")]
+    InvalidOracleAccount,
+    #[msg("Oracle randomness is stale")]
+    StaleRandomness,
+    #[msg("Token accounts are required for token raffles")]
+    TokenAccountsRequired,
+    #[msg("Invalid ticket mint")]
+    InvalidTicketMint,
+    #[msg("Invalid end time. Must be in the future.")]
+    InvalidEndTime,
+    #[msg("Raffle has ended")]
+    RaffleEnded,
+    #[msg("Raffle is not cancelled or expired")]
+    RaffleNotRefundable,
+    #[msg("Raffle has been cancelled")]
+    RaffleCancelled,
+    #[msg("Invalid refund accounts")]
+    InvalidRefundAccounts,
+    #[msg("Invalid raffle status transition")]
+    InvalidStatusTransition,
+    #[msg("Raffle can not be expired yet")]
+    ExpiryNotReached,
+    #[msg("Raffle account is not in the legacy layout")]
+    InvalidLegacyAccount,
+    #[msg("Invalid fee. Fees can not exceed the prize pool.")]
+    InvalidFee,
+    #[msg("Token account is not owned by the recipient")]
+    InvalidTokenAccount,
+    #[msg("Invalid prize tiers. Must be 1 to 10 shares adding up to 10000 bps.")]
+    InvalidPrizeTiers,
+    #[msg("Not enough entries to draw every prize tier")]
+    NotEnoughEntries,
+    #[msg("Prize tier has already been claimed")]
+    TierAlreadyClaimed,
+    #[msg("Invalid quantity. Must be greater than zero.")]
+    InvalidQuantity,
+    #[msg("Entry holds too many separate ticket ranges")]
+    TooManyRanges,
+    #[msg("Wallet has reached the ticket cap for this raffle")]
+    WalletCapReached,
+    #[msg("Wallet is not on the raffle allowlist")]
+    NotAllowlisted,
+    #[msg("Prize accounts are required for raffles with an escrowed prize")]
+    PrizeAccountsRequired,
+    #[msg("Invalid prize mint")]
+    InvalidPrizeMint,
+    #[msg("Invalid prize amount. Must be greater than zero for escrowed prizes.")]
+    InvalidPrizeAmount,
+    #[msg("Ticket still backs an unclaimed prize")]
+    PrizeUnclaimed,
+    #[msg("Raffle still has outstanding payouts")]
+    RaffleNotSettled,
+    #[msg("Invalid crank reward. Must not exceed 100 bps of the pool.")]
+    InvalidCrankReward,
+    #[msg("Invalid series id. Must be 1 to 16 characters.")]
+    InvalidSeriesId,
+    #[msg("Invalid round duration. Must be greater than zero.")]
+    InvalidRoundDuration,
+    #[msg("The latest round of the series is required")]
+    PreviousRoundRequired,
+    #[msg("The latest round of the series has not finished")]
+    RoundNotFinished,
+    #[msg("The protocol is paused")]
+    ProtocolPaused,
+    #[msg("The raffle is paused")]
+    RafflePaused,
+    #[msg("Tickets of raffles with an allowlist or wallet cap can not be transferred")]
+    TransferRestricted,
+    #[msg("Invalid listing price. Must be greater than zero.")]
+    InvalidListingPrice,
+    #[msg("The listed ticket has changed hands since it was listed")]
+    ListingStale,
+    #[msg("The listing price does not match the expected price")]
+    ListingPriceChanged,
+    #[msg("The registry page is full")]
+    RegistryPageFull,
+    #[msg("The raffle is not listed on this registry page")]
+    RaffleNotListed,
+    #[msg("The registry page listing the raffle is required")]
+    RegistryPageRequired,
+    #[msg("Cannot set metadata, title too long")]
+    TitleTooLong,
+    #[msg("Cannot set metadata, description too long")]
+    DescriptionTooLong,
+    #[msg("Cannot set metadata, URI too long")]
+    UriTooLong,
+    #[msg("Cannot set metadata, too many tags")]
+    TooManyTags,
+    #[msg("Cannot set metadata, tag too long")]
+    TagTooLong,
+    #[msg("Metadata accounts are required when metadata is given")]
+    MetadataAccountsRequired,
+    #[msg("Metadata can not change once tickets are sold")]
+    MetadataLocked,
+    #[msg("Invalid minimum entries. Must not exceed the maximum entries.")]
+    InvalidMinEntries,
+    #[msg("Invalid pricing mode. Steps and discount windows must be greater than zero.")]
+    InvalidPricingMode,
+    #[msg("Tickets, entries or participants of the raffle are still open")]
+    OpenAccountsRemaining,
+    #[msg("The oracle value was published before the last ticket changed hands")]
+    RandomnessTooEarly,
+    #[msg("The oracle value is published for a future slot")]
+    InvalidOracleSlot,
+    #[msg("The raffle can be drawn, so it can no longer be cancelled")]
+    RaffleDrawing,
+    #[msg("The raffle has no ticket proceeds left to withdraw")]
+    NoProceedsToWithdraw,
+    #[msg("Only the escrowed prize of a cancelled or expired round can roll over")]
+    NoPrizeToRoll,
+    #[msg("Token-2022 mints with a transfer fee are not supported")]
+    UnsupportedMint,
+    #[msg("Accounts to close must be passed as account and owner pairs of the raffle")]
+    InvalidCloseAccounts,
+    #[msg("Raffle ids must follow the owner's registry counter")]
+    InvalidRaffleId
 }
@@ -0,0 +1,141 @@
+use anchor_lang::prelude::*;
+
+#[event]
+pub struct ConfigUpdated {
+    pub admin: Pubkey,
+    pub treasury: Pubkey,
+    pub protocol_fee_bps: u16,
+}
+
+#[event]
+pub struct RaffleCreated {
+    pub raffle: Pubkey,
+    pub owner: Pubkey,
+    pub raffle_id: String,
+    pub ticket_price: u64,
+    pub max_entries: u32,
+    pub ticket_mint: Option<Pubkey>,
+    pub prize_mint: Option<Pubkey>,
+    pub prize_amount: u64,
+    pub end_time: Option<i64>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TicketPurchased {
+    pub raffle: Pubkey,
+    pub buyer: Pubkey,
+    // tickets `first_ticket_id..first_ticket_id + quantity` were bought
+    pub first_ticket_id: u32,
+    pub quantity: u32,
+    pub amount: u64,
+    pub entries: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct WinnerPicked {
+    pub raffle: Pubkey,
+    // one per prize tier, best tier first
+    pub winning_ticket_ids: Vec<u32>,
+    pub entries: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PrizeClaimed {
+    pub raffle: Pubkey,
+    pub tier: u8,
+    pub ticket_id: u32,
+    pub winner: Pubkey,
+    pub winner_amount: u64,
+    pub creator: Pubkey,
+    pub creator_amount: u64,
+    pub treasury: Pubkey,
+    pub protocol_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProceedsWithdrawn {
+    pub raffle: Pubkey,
+    pub creator: Pubkey,
+    pub creator_amount: u64,
+    pub treasury: Pubkey,
+    pub protocol_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PrizeReclaimed {
+    pub raffle: Pubkey,
+    pub creator: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RaffleCancelled {
+    pub raffle: Pubkey,
+    pub owner: Pubkey,
+    pub entries: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RaffleExpired {
+    pub raffle: Pubkey,
+    pub owner: Pubkey,
+    pub entries: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct Refunded {
+    pub raffle: Pubkey,
+    pub owner: Pubkey,
+    // set for single tickets, bulk entries refund all of their tickets at once
+    pub ticket_id: Option<u32>,
+    pub quantity: u32,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RoundStarted {
+    pub series: Pubkey,
+    pub round: u32,
+    pub raffle: Pubkey,
+    // prize asset carried over from the previous round, zero if none
+    pub rolled_prize_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PauseUpdated {
+    // unset when the whole protocol was paused or resumed
+    pub raffle: Option<Pubkey>,
+    pub paused: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TicketTransferred {
+    pub raffle: Pubkey,
+    pub ticket_id: u32,
+    pub from: Pubkey,
+    pub to: Pubkey,
+    // lamports paid through a listing, zero for gifts
+    pub price: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TicketListed {
+    pub raffle: Pubkey,
+    pub ticket_id: u32,
+    pub seller: Pubkey,
+    pub price: u64,
+    pub timestamp: i64,
+}
@@ -0,0 +1,91 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::{program::invoke, system_instruction::transfer},
+};
+
+use crate::{
+    state::{Config, Listing, Raffle, Ticket},
+    error::RaffleError,
+    events::TicketTransferred
+};
+
+#[derive(Accounts)]
+#[instruction(raffle_id: String)]
+pub struct BuyListedTicket<'info> {
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+    /// CHECK: receives the sale price and listing rent, checked against the listing
+    #[account(mut)]
+    pub seller: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"raffle", raffle.owner.as_ref(), raffle_id.as_ref()],
+        bump = raffle.bump
+    )]
+    pub raffle: Account<'info, Raffle>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        has_one = raffle,
+        seeds = [b"ticket", raffle.key().as_ref(), &ticket.ticket_id.to_le_bytes()],
+        bump = ticket.bump
+    )]
+    pub ticket: Account<'info, Ticket>,
+    #[account(
+        mut,
+        close = seller,
+        has_one = seller,
+        has_one = ticket,
+        seeds = [b"listing", ticket.key().as_ref()],
+        bump = listing.bump
+    )]
+    pub listing: Account<'info, Listing>,
+    pub system_program: Program<'info, System>
+}
+
+/// Buys a listed ticket in one step: the price is paid to the seller, the
+/// ticket moves to the buyer and the listing is closed. `price` must match
+/// the listing so a seller can not relist higher in front of the buyer.
+pub fn buy_listed_ticket_handler(
+    ctx: Context<BuyListedTicket>,
+    _raffle_id: String,
+    price: u64,
+) -> Result<()> {
+    let clock: Clock = Clock::get()?;
+    ctx.accounts.raffle.check_transferable(&ctx.accounts.config, clock.unix_timestamp)?;
+    ctx.accounts.raffle.last_ticket_slot = clock.slot;
+
+    let listing = &ctx.accounts.listing;
+    require!(listing.price == price, RaffleError::ListingPriceChanged);
+    require_keys_eq!(ctx.accounts.ticket.owner, listing.seller, RaffleError::ListingStale);
+    require!(ctx.accounts.buyer.lamports() >= price, RaffleError::InsufficientFunds);
+
+    msg!("Transferring {} lamports from buyer to seller", price);
+    invoke(
+        &transfer(&ctx.accounts.buyer.key(), &listing.seller, price),
+        &[
+            ctx.accounts.buyer.to_account_info(),
+            ctx.accounts.seller.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+        ],
+    )?;
+
+    let ticket: &mut Account<Ticket> = &mut ctx.accounts.ticket;
+    ticket.owner = ctx.accounts.buyer.key();
+    msg!("Ticket {} sold to {}", ticket.ticket_id, ticket.owner);
+
+    emit!(TicketTransferred {
+        raffle: ticket.raffle,
+        ticket_id: ticket.ticket_id,
+        from: ctx.accounts.seller.key(),
+        to: ticket.owner,
+        price,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
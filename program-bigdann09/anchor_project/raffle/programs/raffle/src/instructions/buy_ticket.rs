@@ -1,75 +1,131 @@
-use anchor_lang::{
-    prelude::*,
-    solana_program::{program::invoke, system_instruction::transfer},
-};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 
 use crate::{
-    state::{Raffle, Ticket},
+    state::{Config, Participant, Raffle, RaffleStatus, RegistryPage, Ticket},
     error::RaffleError,
+    events::TicketPurchased,
+    utils::{pay_in, unlist_raffle}
 };
 
 #[derive(Accounts)]
-#[instruction(raffle_id: String, ticket_id: u32)]
+#[instruction(raffle_id: String)]
 pub struct BuyTicket<'info> {
     #[account(mut)]
     pub buyer: Signer<'info>,
+    // the ticket id is assigned by the program, so the address always matches it
     #[account(
         init,
         payer = buyer,
         space = 8 + Ticket::INIT_SPACE,
-        seeds = [b"ticket", raffle.key().as_ref(), &ticket_id.to_le_bytes()],
+        seeds = [b"ticket", raffle.key().as_ref(), &raffle.next_ticket_id().to_le_bytes()],
         bump,
     )]
     pub ticket: Account<'info, Ticket>,
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = 8 + Participant::INIT_SPACE,
+        seeds = [b"participant", raffle.key().as_ref(), buyer.key().as_ref()],
+        bump,
+    )]
+    pub participant: Account<'info, Participant>,
     #[account(
         mut,
         seeds = [b"raffle", raffle.owner.as_ref(), raffle_id.as_ref()],
         bump = raffle.bump
     )]
     pub raffle: Account<'info, Raffle>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    // page of the global registry listing the raffle, only needed by the purchase
+    // selling it out
+    #[account(
+        mut,
+        seeds = [b"registry_page".as_ref(), &registry_page.page.to_le_bytes()],
+        bump = registry_page.bump
+    )]
+    pub registry_page: Option<Account<'info, RegistryPage>>,
+    // token accounts below are only passed for raffles priced in SPL tokens
+    pub ticket_mint: Option<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        token::mint = ticket_mint,
+        token::authority = buyer,
+        token::token_program = token_program,
+    )]
+    pub buyer_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = [b"escrow", raffle.key().as_ref()],
+        bump,
+    )]
+    pub escrow: Option<InterfaceAccount<'info, TokenAccount>>,
+    pub token_program: Option<Interface<'info, TokenInterface>>,
     pub system_program: Program<'info, System>
 }
 
 pub fn buy_ticket_handler(
     ctx: Context<BuyTicket>,
     _raffle_id: String,
-    _ticket_id: u32,
+    proof: Vec<[u8; 32]>,
 ) -> Result<()> {
     let raffle: &mut Account<Raffle> = &mut ctx.accounts.raffle;
+    let clock: Clock = Clock::get()?;
 
-    require!(raffle.is_active, RaffleError::RaffleNotActive);
+    raffle.check_not_paused(&ctx.accounts.config)?;
+    require!(raffle.status == RaffleStatus::Open, RaffleError::RaffleNotActive);
     require!(raffle.entries < raffle.max_entries, RaffleError::RaffleSoldOut);
+    require!(!raffle.has_ended(clock.unix_timestamp), RaffleError::RaffleEnded);
+    raffle.check_allowlist(&ctx.accounts.buyer.key(), &proof)?;
 
-    require!(
-        ctx.accounts.buyer.lamports() >= raffle.ticket_price,
-        RaffleError::InsufficientFunds
-    );
-
-    msg!("Transferring {} lamports from buyer to raffle", raffle.ticket_price);
-    invoke(
-        &transfer(
-            &ctx.accounts.buyer.key(),
-            &raffle.key(),
-            raffle.ticket_price,
-        ),
-        &[
-            ctx.accounts.buyer.to_account_info(),
-            raffle.to_account_info(),
-            ctx.accounts.system_program.to_account_info(),
-        ],
+    let price = raffle.price_of(1, clock.unix_timestamp)?;
+    pay_in(
+        raffle,
+        &ctx.accounts.buyer,
+        &ctx.accounts.ticket_mint,
+        &ctx.accounts.buyer_token_account,
+        &ctx.accounts.escrow,
+        &ctx.accounts.token_program,
+        &ctx.accounts.system_program,
+        price
     )?;
 
-    let ticket_id = raffle.entries + 1;
+    let participant: &mut Account<Participant> = &mut ctx.accounts.participant;
+    if participant.raffle == Pubkey::default() {
+        raffle.account_opened()?;
+    }
+    participant.raffle = raffle.key();
+    participant.owner = ctx.accounts.buyer.key();
+    participant.bump = ctx.bumps.participant;
+    participant.add_tickets(1, raffle.max_tickets_per_wallet)?;
+
+    let ticket_id = raffle.record_entries(&ctx.accounts.buyer.key(), 1, price, &clock)?;
+    raffle.account_opened()?;
+    if raffle.status != RaffleStatus::Open {
+        unlist_raffle(raffle, &mut ctx.accounts.registry_page)?;
+    }
     ctx.accounts.ticket.set_inner(Ticket {
         raffle: raffle.key(),
         ticket_id,
         owner: ctx.accounts.buyer.key(),
         bump: ctx.bumps.ticket,
+        price_paid: price,
     });
     msg!("Ticket {} purchased successfully", ticket_id);
 
-    // Update raffle state
-    raffle.entries += 1;
+    emit!(TicketPurchased {
+        raffle: raffle.key(),
+        buyer: ctx.accounts.buyer.key(),
+        first_ticket_id: ticket_id,
+        quantity: 1,
+        amount: price,
+        entries: raffle.entries,
+        timestamp: clock.unix_timestamp,
+    });
 
     Ok(())
-}
\ No newline at end of file
+}
@@ -0,0 +1,142 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::{
+    state::{Config, Entry, Participant, Raffle, RaffleStatus, RegistryPage},
+    error::RaffleError,
+    events::TicketPurchased,
+    utils::{pay_in, unlist_raffle}
+};
+
+#[derive(Accounts)]
+#[instruction(raffle_id: String)]
+pub struct BuyTickets<'info> {
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = 8 + Entry::INIT_SPACE,
+        seeds = [b"entry", raffle.key().as_ref(), buyer.key().as_ref()],
+        bump,
+    )]
+    pub entry: Account<'info, Entry>,
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = 8 + Participant::INIT_SPACE,
+        seeds = [b"participant", raffle.key().as_ref(), buyer.key().as_ref()],
+        bump,
+    )]
+    pub participant: Account<'info, Participant>,
+    #[account(
+        mut,
+        seeds = [b"raffle", raffle.owner.as_ref(), raffle_id.as_ref()],
+        bump = raffle.bump
+    )]
+    pub raffle: Account<'info, Raffle>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    // page of the global registry listing the raffle, only needed by the purchase
+    // selling it out
+    #[account(
+        mut,
+        seeds = [b"registry_page".as_ref(), &registry_page.page.to_le_bytes()],
+        bump = registry_page.bump
+    )]
+    pub registry_page: Option<Account<'info, RegistryPage>>,
+    // token accounts below are only passed for raffles priced in SPL tokens
+    pub ticket_mint: Option<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        token::mint = ticket_mint,
+        token::authority = buyer,
+        token::token_program = token_program,
+    )]
+    pub buyer_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = [b"escrow", raffle.key().as_ref()],
+        bump,
+    )]
+    pub escrow: Option<InterfaceAccount<'info, TokenAccount>>,
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+    pub system_program: Program<'info, System>
+}
+
+pub fn buy_tickets_handler(
+    ctx: Context<BuyTickets>,
+    _raffle_id: String,
+    quantity: u32,
+    proof: Vec<[u8; 32]>,
+) -> Result<()> {
+    let raffle: &mut Account<Raffle> = &mut ctx.accounts.raffle;
+    let clock: Clock = Clock::get()?;
+
+    raffle.check_not_paused(&ctx.accounts.config)?;
+    require!(quantity > 0, RaffleError::InvalidQuantity);
+    require!(raffle.status == RaffleStatus::Open, RaffleError::RaffleNotActive);
+    require!(
+        raffle.entries.checked_add(quantity).is_some_and(|entries| entries <= raffle.max_entries),
+        RaffleError::RaffleSoldOut
+    );
+    require!(!raffle.has_ended(clock.unix_timestamp), RaffleError::RaffleEnded);
+    raffle.check_allowlist(&ctx.accounts.buyer.key(), &proof)?;
+
+    let amount = raffle.price_of(quantity, clock.unix_timestamp)?;
+    pay_in(
+        raffle,
+        &ctx.accounts.buyer,
+        &ctx.accounts.ticket_mint,
+        &ctx.accounts.buyer_token_account,
+        &ctx.accounts.escrow,
+        &ctx.accounts.token_program,
+        &ctx.accounts.system_program,
+        amount
+    )?;
+
+    let participant: &mut Account<Participant> = &mut ctx.accounts.participant;
+    if participant.raffle == Pubkey::default() {
+        raffle.account_opened()?;
+    }
+    participant.raffle = raffle.key();
+    participant.owner = ctx.accounts.buyer.key();
+    participant.bump = ctx.bumps.participant;
+    participant.add_tickets(quantity, raffle.max_tickets_per_wallet)?;
+
+    let first_ticket = raffle.record_entries(&ctx.accounts.buyer.key(), quantity, amount, &clock)?;
+    if raffle.status != RaffleStatus::Open {
+        unlist_raffle(raffle, &mut ctx.accounts.registry_page)?;
+    }
+
+    let entry: &mut Account<Entry> = &mut ctx.accounts.entry;
+    if entry.raffle == Pubkey::default() {
+        raffle.account_opened()?;
+    }
+    entry.raffle = raffle.key();
+    entry.owner = ctx.accounts.buyer.key();
+    entry.bump = ctx.bumps.entry;
+    require!(entry.add_range(first_ticket, quantity), RaffleError::TooManyRanges);
+    entry.amount_paid = entry.amount_paid.checked_add(amount)
+        .ok_or(RaffleError::MathOverflow)?;
+    msg!(
+        "Tickets {} to {} purchased successfully",
+        first_ticket,
+        first_ticket + quantity - 1
+    );
+
+    emit!(TicketPurchased {
+        raffle: raffle.key(),
+        buyer: ctx.accounts.buyer.key(),
+        first_ticket_id: first_ticket,
+        quantity,
+        amount,
+        entries: raffle.entries,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
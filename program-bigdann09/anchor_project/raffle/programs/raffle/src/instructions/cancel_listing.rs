@@ -0,0 +1,26 @@
+use anchor_lang::prelude::*;
+
+use crate::state::Listing;
+
+#[derive(Accounts)]
+pub struct CancelListing<'info> {
+    #[account(mut)]
+    pub seller: Signer<'info>,
+    // the ticket may have been transferred or closed since, so it is not needed
+    #[account(
+        mut,
+        close = seller,
+        has_one = seller,
+        seeds = [b"listing", listing.ticket.as_ref()],
+        bump = listing.bump
+    )]
+    pub listing: Account<'info, Listing>
+}
+
+/// Withdraws a listing and returns its rent. Always available, so stale
+/// listings never lock up the seller's lamports.
+pub fn cancel_listing_handler(ctx: Context<CancelListing>) -> Result<()> {
+    msg!("Listing of ticket account {} cancelled", ctx.accounts.listing.ticket);
+
+    Ok(())
+}
@@ -0,0 +1,67 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    state::{Raffle, RaffleStatus, RandomnessSource, RegistryPage},
+    error::RaffleError,
+    events::RaffleCancelled,
+    utils::unlist_raffle
+};
+
+#[derive(Accounts)]
+#[instruction(raffle_id: String)]
+pub struct CancelRaffle<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        has_one = owner,
+        seeds = [b"raffle", owner.key().as_ref(), raffle_id.as_ref()],
+        bump = raffle.bump
+    )]
+    pub raffle: Account<'info, Raffle>,
+    // page of the global registry still listing the raffle
+    #[account(
+        mut,
+        seeds = [b"registry_page".as_ref(), &registry_page.page.to_le_bytes()],
+        bump = registry_page.bump
+    )]
+    pub registry_page: Option<Account<'info, RegistryPage>>
+}
+
+/// Cancels a raffle that has not been drawn so its tickets can be refunded.
+/// After the end time the owner may either draw or cancel, unless the raffle
+/// draws from a commit-reveal secret or an oracle.
+pub fn cancel_raffle_handler(
+    ctx: Context<CancelRaffle>,
+    _raffle_id: String,
+) -> Result<()> {
+    let raffle: &mut Account<Raffle> = &mut ctx.accounts.raffle;
+
+    match raffle.status {
+        RaffleStatus::Open | RaffleStatus::Drawing => {},
+        RaffleStatus::Cancelled => return err!(RaffleError::RaffleCancelled),
+        RaffleStatus::Drawn | RaffleStatus::Claimed => return err!(RaffleError::WinnerChosen),
+        RaffleStatus::Expired => return err!(RaffleError::RaffleNotActive),
+    }
+    // once drawable the owner may already know the outcome of a commit-reveal
+    // or oracle draw, and must not be able to call off a losing one. Slot hash
+    // draws are not known in advance, so those raffles stay cancellable.
+    let now = Clock::get()?.unix_timestamp;
+    require!(
+        raffle.randomness_source == RandomnessSource::SlotHash || raffle.drawable_since(now).is_none(),
+        RaffleError::RaffleDrawing
+    );
+
+    raffle.transition(RaffleStatus::Cancelled)?;
+    unlist_raffle(raffle, &mut ctx.accounts.registry_page)?;
+    msg!("Raffle {} cancelled with {} entries", raffle.raffle_id, raffle.entries);
+
+    emit!(RaffleCancelled {
+        raffle: raffle.key(),
+        owner: raffle.owner,
+        entries: raffle.entries,
+        timestamp: now,
+    });
+
+    Ok(())
+}
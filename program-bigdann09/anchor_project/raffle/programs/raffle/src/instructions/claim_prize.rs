@@ -1,8 +1,11 @@
 use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 
 use crate::{
-    state::{Raffle, Ticket},
-    error::RaffleError
+    state::{Config, Entry, Raffle, RaffleStatus, Ticket},
+    error::RaffleError,
+    events::PrizeClaimed,
+    utils::{pay_out, release_prize}
 };
 
 #[derive(Accounts)]
@@ -12,6 +15,7 @@ pub struct ClaimPrize<'info> {
     pub winner: Signer<'info>,
     #[account(
         mut,
+        has_one = owner,
         seeds = [b"raffle", raffle.owner.as_ref(), raffle_id.as_ref()],
         bump = raffle.bump
     )]
@@ -21,32 +25,147 @@ pub struct ClaimPrize<'info> {
         seeds = [b"ticket", raffle.key().as_ref(), &ticket.ticket_id.to_le_bytes()],
         bump = ticket.bump
     )]
-    pub ticket: Account<'info, Ticket>,
+    pub ticket: Option<Account<'info, Ticket>>,
+    // winning tickets bought in bulk are claimed through their entry instead
+    #[account(
+        seeds = [b"entry", raffle.key().as_ref(), entry.owner.as_ref()],
+        bump = entry.bump
+    )]
+    pub entry: Option<Account<'info, Entry>>,
+    #[account(
+        has_one = treasury,
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    /// CHECK: raffle creator receiving the creator fee, checked by `has_one` on the raffle
+    #[account(mut)]
+    pub owner: UncheckedAccount<'info>,
+    /// CHECK: protocol treasury receiving the protocol fee, checked by `has_one` on the config
+    #[account(mut)]
+    pub treasury: UncheckedAccount<'info>,
+    // token accounts below are only passed for raffles priced in SPL tokens
+    pub ticket_mint: Option<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        token::mint = ticket_mint,
+        token::token_program = token_program,
+    )]
+    pub winner_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        token::mint = ticket_mint,
+        token::token_program = token_program,
+    )]
+    pub owner_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        token::mint = ticket_mint,
+        token::token_program = token_program,
+    )]
+    pub treasury_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = [b"escrow", raffle.key().as_ref()],
+        bump,
+    )]
+    pub escrow: Option<InterfaceAccount<'info, TokenAccount>>,
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+    // prize accounts below are only passed for raffles with an escrowed prize asset
+    pub prize_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+    #[account(
+        mut,
+        token::mint = prize_mint,
+        token::token_program = prize_token_program,
+    )]
+    pub winner_prize_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    #[account(
+        mut,
+        seeds = [b"prize_vault", raffle.key().as_ref()],
+        bump,
+    )]
+    pub prize_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    pub prize_token_program: Option<Interface<'info, TokenInterface>>,
     pub system_program: Program<'info, System>
 }
 
 pub fn claim_prize_handler(ctx: Context<ClaimPrize>, _raffle_id: String) -> Result<()> {
     let raffle: &mut Account<Raffle> = &mut ctx.accounts.raffle;
-    let ticket: &mut Account<Ticket> = &mut ctx.accounts.ticket;
     
-    require!(!raffle.is_active, RaffleError::RaffleNotActive);
-    require!(raffle.winner_ticket.is_some(), RaffleError::WinnerNotChosen);
-
-    if let Some(winner_ticket_id) = raffle.winner_ticket {
-        require!(
-            winner_ticket_id == ticket.ticket_id && 
-            ticket.owner == ctx.accounts.winner.key(),
-            RaffleError::InvalidWinner
-        );
+    match raffle.status {
+        RaffleStatus::Drawn => {},
+        RaffleStatus::Claimed => return err!(RaffleError::RaffleAlreadyClaimed),
+        RaffleStatus::Cancelled => return err!(RaffleError::RaffleCancelled),
+        RaffleStatus::Open | RaffleStatus::Drawing | RaffleStatus::Expired => {
+            return err!(RaffleError::WinnerNotChosen)
+        }
     }
 
-    let prize_amount = raffle.ticket_price.checked_mul(raffle.entries as u64)
-        .ok_or(RaffleError::MathOverflow)?;
-    
-    raffle.sub_lamports(prize_amount)?;
-    ctx.accounts.winner.add_lamports(prize_amount)?;
+    let (owner, tier) = match (&ctx.accounts.ticket, &ctx.accounts.entry) {
+        (Some(ticket), None) => (ticket.owner, raffle.winning_tier(ticket.ticket_id)),
+        (None, Some(entry)) => (entry.owner, raffle.winning_tier_in(entry)),
+        _ => return err!(RaffleError::InvalidWinner),
+    };
+    require!(owner == ctx.accounts.winner.key(), RaffleError::InvalidWinner);
+    let tier = tier.ok_or(RaffleError::InvalidWinner)?;
+    require!(!raffle.winners[tier].claimed, RaffleError::TierAlreadyClaimed);
 
-    raffle.is_claimed = true;
+    let (winner_amount, creator_amount, protocol_amount) = raffle.split_prize(tier)?;
+
+    pay_out(
+        raffle,
+        &ctx.accounts.winner.to_account_info(),
+        &ctx.accounts.ticket_mint,
+        &ctx.accounts.winner_token_account,
+        &ctx.accounts.escrow,
+        &ctx.accounts.token_program,
+        winner_amount
+    )?;
+    pay_out(
+        raffle,
+        &ctx.accounts.owner.to_account_info(),
+        &ctx.accounts.ticket_mint,
+        &ctx.accounts.owner_token_account,
+        &ctx.accounts.escrow,
+        &ctx.accounts.token_program,
+        creator_amount
+    )?;
+    pay_out(
+        raffle,
+        &ctx.accounts.treasury.to_account_info(),
+        &ctx.accounts.ticket_mint,
+        &ctx.accounts.treasury_token_account,
+        &ctx.accounts.escrow,
+        &ctx.accounts.token_program,
+        protocol_amount
+    )?;
+    release_prize(
+        raffle,
+        &ctx.accounts.winner.key(),
+        &ctx.accounts.prize_mint,
+        &ctx.accounts.winner_prize_account,
+        &ctx.accounts.prize_vault,
+        &ctx.accounts.prize_token_program,
+        &ctx.accounts.owner.to_account_info()
+    )?;
+
+    raffle.winners[tier].claimed = true;
+    if raffle.winners.iter().all(|winner| winner.claimed) {
+        raffle.transition(RaffleStatus::Claimed)?;
+    }
+
+    emit!(PrizeClaimed {
+        raffle: raffle.key(),
+        tier: tier as u8,
+        ticket_id: raffle.winners[tier].ticket_id,
+        winner: ctx.accounts.winner.key(),
+        winner_amount,
+        creator: raffle.owner,
+        creator_amount,
+        treasury: ctx.accounts.treasury.key(),
+        protocol_amount,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
 
     Ok(())
-}
\ No newline at end of file
+}
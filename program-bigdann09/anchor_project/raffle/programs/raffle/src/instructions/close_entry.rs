@@ -0,0 +1,93 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::{
+    state::{Entry, Raffle, RaffleStatus},
+    error::RaffleError,
+    events::Refunded,
+    utils::pay_out
+};
+
+#[derive(Accounts)]
+#[instruction(raffle_id: String)]
+pub struct CloseEntry<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"raffle", raffle.owner.as_ref(), raffle_id.as_ref()],
+        bump = raffle.bump
+    )]
+    pub raffle: Account<'info, Raffle>,
+    #[account(
+        mut,
+        close = owner,
+        has_one = owner,
+        has_one = raffle,
+        seeds = [b"entry", raffle.key().as_ref(), owner.key().as_ref()],
+        bump = entry.bump
+    )]
+    pub entry: Account<'info, Entry>,
+    // token accounts below are only passed to refund entries priced in SPL tokens
+    pub ticket_mint: Option<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        token::mint = ticket_mint,
+        token::token_program = token_program,
+    )]
+    pub owner_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = [b"escrow", raffle.key().as_ref()],
+        bump,
+    )]
+    pub escrow: Option<InterfaceAccount<'info, TokenAccount>>,
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+}
+
+/// Closes an entry once its raffle is decided and returns the rent to the
+/// buyer. Entries stay open until every prize their tickets won is claimed,
+/// and entries of cancelled or expired raffles are refunded before they are
+/// closed.
+pub fn close_entry_handler(ctx: Context<CloseEntry>, _raffle_id: String) -> Result<()> {
+    let raffle: &mut Account<Raffle> = &mut ctx.accounts.raffle;
+    let entry = &ctx.accounts.entry;
+
+    match raffle.status {
+        RaffleStatus::Drawn | RaffleStatus::Claimed => {
+            require!(
+                raffle.winners.iter()
+                    .filter(|winner| entry.contains(winner.ticket_id))
+                    .all(|winner| winner.claimed),
+                RaffleError::PrizeUnclaimed
+            );
+        }
+        RaffleStatus::Cancelled | RaffleStatus::Expired => {
+            let quantity = entry.ticket_count();
+            raffle.refund_entries(quantity)?;
+            pay_out(
+                raffle,
+                &ctx.accounts.owner.to_account_info(),
+                &ctx.accounts.ticket_mint,
+                &ctx.accounts.owner_token_account,
+                &ctx.accounts.escrow,
+                &ctx.accounts.token_program,
+                entry.amount_paid
+            )?;
+
+            emit!(Refunded {
+                raffle: raffle.key(),
+                owner: ctx.accounts.owner.key(),
+                ticket_id: None,
+                quantity,
+                amount: entry.amount_paid,
+                timestamp: Clock::get()?.unix_timestamp,
+            });
+        }
+        RaffleStatus::Open | RaffleStatus::Drawing => return err!(RaffleError::WinnerNotChosen),
+    }
+    raffle.account_closed()?;
+    msg!("Entry of {} closed", ctx.accounts.owner.key());
+
+    Ok(())
+}
@@ -0,0 +1,40 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    state::{Participant, Raffle},
+    error::RaffleError
+};
+
+#[derive(Accounts)]
+#[instruction(raffle_id: String)]
+pub struct CloseParticipant<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"raffle", raffle.owner.as_ref(), raffle_id.as_ref()],
+        bump = raffle.bump
+    )]
+    pub raffle: Account<'info, Raffle>,
+    #[account(
+        mut,
+        close = owner,
+        has_one = owner,
+        has_one = raffle,
+        seeds = [b"participant", raffle.key().as_ref(), owner.key().as_ref()],
+        bump = participant.bump
+    )]
+    pub participant: Account<'info, Participant>,
+}
+
+/// Closes a buyer's participant account once the raffle no longer sells
+/// tickets and returns the rent to the buyer.
+pub fn close_participant_handler(ctx: Context<CloseParticipant>, _raffle_id: String) -> Result<()> {
+    let raffle: &mut Account<Raffle> = &mut ctx.accounts.raffle;
+
+    require!(raffle.status.is_finished(), RaffleError::WinnerNotChosen);
+    raffle.account_closed()?;
+    msg!("Participant {} closed", ctx.accounts.owner.key());
+
+    Ok(())
+}
@@ -0,0 +1,110 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{
+    close_account, CloseAccount, Mint, TokenAccount, TokenInterface
+};
+
+use crate::{
+    state::{Raffle, RaffleMetadata, RegistryPage},
+    error::RaffleError,
+    utils::{close_program_account, load_if_initialized, transfer_from_escrow, unlist_raffle}
+};
+
+#[derive(Accounts)]
+#[instruction(raffle_id: String)]
+pub struct CloseRaffle<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        close = owner,
+        has_one = owner,
+        seeds = [b"raffle", owner.key().as_ref(), raffle_id.as_ref()],
+        bump = raffle.bump
+    )]
+    pub raffle: Account<'info, Raffle>,
+    // page of the global registry still listing the raffle
+    #[account(
+        mut,
+        seeds = [b"registry_page".as_ref(), &registry_page.page.to_le_bytes()],
+        bump = registry_page.bump
+    )]
+    pub registry_page: Option<Account<'info, RegistryPage>>,
+    /// CHECK: metadata of the raffle, only read if the raffle was given metadata
+    #[account(
+        mut,
+        seeds = [b"metadata", raffle.key().as_ref()],
+        bump
+    )]
+    pub metadata: UncheckedAccount<'info>,
+    // token accounts below are only passed for raffles priced in SPL tokens
+    pub ticket_mint: Option<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        token::mint = ticket_mint,
+        token::authority = owner,
+        token::token_program = token_program,
+    )]
+    pub owner_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = [b"escrow", raffle.key().as_ref()],
+        bump,
+    )]
+    pub escrow: Option<InterfaceAccount<'info, TokenAccount>>,
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+}
+
+/// Closes a settled raffle and returns its rent to the owner, along with any
+/// rounding dust left in the pool. Token raffles also close their escrow, and
+/// raffles with metadata their metadata account.
+/// Every ticket, entry and participant must be closed first, by its owner or
+/// through `crank_close_accounts`, so none can be reused by a raffle later
+/// created at the same address.
+pub fn close_raffle_handler(ctx: Context<CloseRaffle>, _raffle_id: String) -> Result<()> {
+    require!(ctx.accounts.raffle.is_settled(), RaffleError::RaffleNotSettled);
+    require!(ctx.accounts.raffle.open_accounts == 0, RaffleError::OpenAccountsRemaining);
+    unlist_raffle(&mut ctx.accounts.raffle, &mut ctx.accounts.registry_page)?;
+
+    let metadata = ctx.accounts.metadata.to_account_info();
+    if load_if_initialized::<RaffleMetadata>(&metadata)?.is_some() {
+        close_program_account(&metadata, &ctx.accounts.owner.to_account_info())?;
+    }
+
+    let raffle: &Account<Raffle> = &ctx.accounts.raffle;
+
+    if let Some(raffle_mint) = raffle.ticket_mint {
+        let (Some(mint), Some(to), Some(escrow), Some(token_program)) = (
+            &ctx.accounts.ticket_mint,
+            &ctx.accounts.owner_token_account,
+            &ctx.accounts.escrow,
+            &ctx.accounts.token_program,
+        ) else {
+            return err!(RaffleError::TokenAccountsRequired);
+        };
+        require_keys_eq!(mint.key(), raffle_mint, RaffleError::InvalidTicketMint);
+
+        if escrow.amount > 0 {
+            transfer_from_escrow(raffle, escrow, mint, to, token_program, escrow.amount)?;
+        }
+
+        let bump = [raffle.bump];
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"raffle",
+            raffle.owner.as_ref(),
+            raffle.raffle_id.as_bytes(),
+            &bump
+        ]];
+        close_account(CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            CloseAccount {
+                account: escrow.to_account_info(),
+                destination: ctx.accounts.owner.to_account_info(),
+                authority: raffle.to_account_info(),
+            },
+            signer_seeds
+        ))?;
+    }
+    msg!("Raffle {} closed", raffle.raffle_id);
+
+    Ok(())
+}
@@ -0,0 +1,89 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::{
+    state::{Raffle, RaffleStatus, Ticket},
+    error::RaffleError,
+    events::Refunded,
+    utils::pay_out
+};
+
+#[derive(Accounts)]
+#[instruction(raffle_id: String)]
+pub struct CloseTicket<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"raffle", raffle.owner.as_ref(), raffle_id.as_ref()],
+        bump = raffle.bump
+    )]
+    pub raffle: Account<'info, Raffle>,
+    #[account(
+        mut,
+        close = owner,
+        has_one = owner,
+        has_one = raffle,
+        seeds = [b"ticket", raffle.key().as_ref(), &ticket.ticket_id.to_le_bytes()],
+        bump = ticket.bump
+    )]
+    pub ticket: Account<'info, Ticket>,
+    // token accounts below are only passed to refund tickets priced in SPL tokens
+    pub ticket_mint: Option<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        token::mint = ticket_mint,
+        token::token_program = token_program,
+    )]
+    pub owner_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = [b"escrow", raffle.key().as_ref()],
+        bump,
+    )]
+    pub escrow: Option<InterfaceAccount<'info, TokenAccount>>,
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+}
+
+/// Closes a ticket once its raffle is decided and returns the rent to the
+/// buyer. Winning tickets stay open until their prize is claimed, and tickets
+/// of cancelled or expired raffles are refunded before they are closed.
+pub fn close_ticket_handler(ctx: Context<CloseTicket>, _raffle_id: String) -> Result<()> {
+    let raffle: &mut Account<Raffle> = &mut ctx.accounts.raffle;
+    let ticket_id = ctx.accounts.ticket.ticket_id;
+
+    match raffle.status {
+        RaffleStatus::Drawn | RaffleStatus::Claimed => {
+            if let Some(tier) = raffle.winning_tier(ticket_id) {
+                require!(raffle.winners[tier].claimed, RaffleError::PrizeUnclaimed);
+            }
+        }
+        RaffleStatus::Cancelled | RaffleStatus::Expired => {
+            raffle.refund_entries(1)?;
+            let amount = ctx.accounts.ticket.price_paid;
+            pay_out(
+                raffle,
+                &ctx.accounts.owner.to_account_info(),
+                &ctx.accounts.ticket_mint,
+                &ctx.accounts.owner_token_account,
+                &ctx.accounts.escrow,
+                &ctx.accounts.token_program,
+                amount
+            )?;
+
+            emit!(Refunded {
+                raffle: raffle.key(),
+                owner: ctx.accounts.owner.key(),
+                ticket_id: Some(ticket_id),
+                quantity: 1,
+                amount,
+                timestamp: Clock::get()?.unix_timestamp,
+            });
+        }
+        RaffleStatus::Open | RaffleStatus::Drawing => return err!(RaffleError::WinnerNotChosen),
+    }
+    raffle.account_closed()?;
+    msg!("Ticket {} closed", ticket_id);
+
+    Ok(())
+}
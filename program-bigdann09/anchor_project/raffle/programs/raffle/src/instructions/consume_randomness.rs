@@ -0,0 +1,116 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::{
+    state::{Config, Raffle, RandomnessSource, RegistryPage},
+    error::RaffleError,
+    events::WinnerPicked,
+    utils::{pay_crank_reward, unlist_raffle}
+};
+
+#[derive(Accounts)]
+#[instruction(raffle_id: String)]
+pub struct ConsumeRandomness<'info> {
+    // anyone may draw once the oracle has published a value
+    #[account(mut)]
+    pub caller: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"raffle", raffle.owner.as_ref(), raffle_id.as_ref()],
+        bump = raffle.bump
+    )]
+    pub raffle: Account<'info, Raffle>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    /// CHECK: address, owner and layout are validated against the raffle's oracle config
+    pub oracle: UncheckedAccount<'info>,
+    // page of the global registry still listing the raffle, only needed while
+    // it has not left `Open`
+    #[account(
+        mut,
+        seeds = [b"registry_page".as_ref(), &registry_page.page.to_le_bytes()],
+        bump = registry_page.bump
+    )]
+    pub registry_page: Option<Account<'info, RegistryPage>>
+,
+    // token accounts below are only passed to pay the crank reward of raffles priced in SPL tokens
+    pub ticket_mint: Option<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        token::mint = ticket_mint,
+        token::token_program = token_program,
+    )]
+    pub caller_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = [b"escrow", raffle.key().as_ref()],
+        bump,
+    )]
+    pub escrow: Option<InterfaceAccount<'info, TokenAccount>>,
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+}
+
+pub fn consume_randomness_handler(
+    ctx: Context<ConsumeRandomness>,
+    _raffle_id: String,
+) -> Result<()> {
+    let raffle: &mut Account<Raffle> = &mut ctx.accounts.raffle;
+    let oracle = &ctx.accounts.oracle;
+    let clock: Clock = Clock::get()?;
+
+    raffle.check_not_paused(&ctx.accounts.config)?;
+    raffle.check_drawable(clock.unix_timestamp)?;
+
+    let config = match &raffle.randomness_source {
+        RandomnessSource::Oracle(config) => config.clone(),
+        _ => return err!(RaffleError::NoOracle),
+    };
+
+    require_keys_eq!(oracle.key(), config.account, RaffleError::InvalidOracleAccount);
+    require_keys_eq!(*oracle.owner, config.program, RaffleError::InvalidOracleAccount);
+
+    let data = oracle.try_borrow_data()?;
+    let randomness_offset = config.randomness_offset as usize;
+    let slot_offset = config.slot_offset as usize;
+
+    let randomness: [u8; 32] = data
+        .get(randomness_offset..randomness_offset + 32)
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or(RaffleError::InvalidOracleAccount)?;
+    let slot = data
+        .get(slot_offset..slot_offset + 8)
+        .and_then(|bytes| bytes.try_into().ok())
+        .map(u64::from_le_bytes)
+        .ok_or(RaffleError::InvalidOracleAccount)?;
+
+    require!(slot <= clock.slot, RaffleError::InvalidOracleSlot);
+    // values published before the last ticket changed hands were known to buyers
+    require!(slot > raffle.last_ticket_slot, RaffleError::RandomnessTooEarly);
+    require!(
+        clock.slot.saturating_sub(slot) <= config.max_staleness,
+        RaffleError::StaleRandomness
+    );
+
+    raffle.draw_winners(&randomness)?;
+    unlist_raffle(raffle, &mut ctx.accounts.registry_page)?;
+    pay_crank_reward(
+        raffle,
+        &ctx.accounts.caller.to_account_info(),
+        &ctx.accounts.ticket_mint,
+        &ctx.accounts.caller_token_account,
+        &ctx.accounts.escrow,
+        &ctx.accounts.token_program,
+    )?;
+
+    emit!(WinnerPicked {
+        raffle: raffle.key(),
+        winning_ticket_ids: raffle.winning_ticket_ids(),
+        entries: raffle.entries,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
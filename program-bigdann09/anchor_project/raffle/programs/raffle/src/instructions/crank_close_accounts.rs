@@ -0,0 +1,62 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    state::{Entry, Participant, Raffle, Ticket},
+    error::RaffleError
+};
+
+#[derive(Accounts)]
+#[instruction(raffle_id: String)]
+pub struct CrankCloseAccounts<'info> {
+    pub caller: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"raffle", raffle.owner.as_ref(), raffle_id.as_ref()],
+        bump = raffle.bump
+    )]
+    pub raffle: Account<'info, Raffle>,
+}
+
+/// Closes a batch of ticket, entry and participant accounts of a settled
+/// raffle, so buyers who never come back can not keep the raffle from being
+/// closed. `remaining_accounts` holds `[account, owner]` pairs, and the rent of
+/// each account goes back to its owner.
+pub fn crank_close_accounts_handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, CrankCloseAccounts<'info>>,
+    _raffle_id: String,
+) -> Result<()> {
+    let raffle: &mut Account<Raffle> = &mut ctx.accounts.raffle;
+
+    // every prize is claimed or every ticket refunded, nothing is owed anymore
+    require!(raffle.is_settled(), RaffleError::RaffleNotSettled);
+
+    let batches = ctx.remaining_accounts.chunks_exact(2);
+    require!(
+        batches.len() > 0 && batches.remainder().is_empty(),
+        RaffleError::InvalidCloseAccounts
+    );
+
+    for accounts in batches {
+        let account_info = &accounts[0];
+        let owner_info = &accounts[1];
+
+        if let Ok(ticket) = Account::<Ticket>::try_from(account_info) {
+            require_keys_eq!(ticket.raffle, raffle.key(), RaffleError::InvalidCloseAccounts);
+            require_keys_eq!(ticket.owner, owner_info.key(), RaffleError::InvalidCloseAccounts);
+            ticket.close(owner_info.clone())?;
+        } else if let Ok(entry) = Account::<Entry>::try_from(account_info) {
+            require_keys_eq!(entry.raffle, raffle.key(), RaffleError::InvalidCloseAccounts);
+            require_keys_eq!(entry.owner, owner_info.key(), RaffleError::InvalidCloseAccounts);
+            entry.close(owner_info.clone())?;
+        } else {
+            let participant = Account::<Participant>::try_from(account_info)?;
+            require_keys_eq!(participant.raffle, raffle.key(), RaffleError::InvalidCloseAccounts);
+            require_keys_eq!(participant.owner, owner_info.key(), RaffleError::InvalidCloseAccounts);
+            participant.close(owner_info.clone())?;
+        }
+        raffle.account_closed()?;
+    }
+    msg!("{} accounts of raffle {} closed", ctx.remaining_accounts.len() / 2, raffle.raffle_id);
+
+    Ok(())
+}
@@ -0,0 +1,108 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::{
+    state::{Entry, Raffle, Ticket},
+    error::RaffleError,
+    events::Refunded,
+    utils::pay_out
+};
+
+#[derive(Accounts)]
+#[instruction(raffle_id: String)]
+pub struct CrankRefunds<'info> {
+    #[account(mut)]
+    pub caller: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"raffle", raffle.owner.as_ref(), raffle_id.as_ref()],
+        bump = raffle.bump
+    )]
+    pub raffle: Account<'info, Raffle>,
+    // token accounts below are only passed for raffles priced in SPL tokens
+    pub ticket_mint: Option<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        seeds = [b"escrow", raffle.key().as_ref()],
+        bump,
+    )]
+    pub escrow: Option<InterfaceAccount<'info, TokenAccount>>,
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+}
+
+/// Refunds a batch of tickets of a cancelled raffle. `remaining_accounts` holds
+/// `[ticket or entry, owner]` pairs, or `[ticket or entry, owner, owner token account]`
+/// triples for token raffles. Refunded accounts are closed to their owner, so a
+/// ticket can never be refunded twice.
+pub fn crank_refunds_handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, CrankRefunds<'info>>,
+    _raffle_id: String,
+) -> Result<()> {
+    let raffle: &mut Account<Raffle> = &mut ctx.accounts.raffle;
+    let timestamp = Clock::get()?.unix_timestamp;
+
+    require!(raffle.status.is_refundable(), RaffleError::RaffleNotRefundable);
+
+    let chunk_size = if raffle.ticket_mint.is_some() { 3 } else { 2 };
+    let batches = ctx.remaining_accounts.chunks_exact(chunk_size);
+    require!(
+        batches.len() > 0 && batches.remainder().is_empty(),
+        RaffleError::InvalidRefundAccounts
+    );
+
+    for accounts in batches {
+        let ticket_info = &accounts[0];
+        let owner_info = &accounts[1];
+
+        // bulk purchases are refunded through their entry account
+        let (ticket, entry) = match Account::<Ticket>::try_from(ticket_info) {
+            Ok(ticket) => (Some(ticket), None),
+            Err(_) => (None, Some(Account::<Entry>::try_from(ticket_info)?)),
+        };
+        let (ticket_raffle, ticket_owner, ticket_id, quantity, amount) = match (&ticket, &entry) {
+            (Some(ticket), _) => {
+                (ticket.raffle, ticket.owner, Some(ticket.ticket_id), 1, ticket.price_paid)
+            }
+            (_, Some(entry)) => {
+                (entry.raffle, entry.owner, None, entry.ticket_count(), entry.amount_paid)
+            }
+            _ => unreachable!(),
+        };
+        require_keys_eq!(ticket_raffle, raffle.key(), RaffleError::InvalidRefundAccounts);
+        require_keys_eq!(ticket_owner, owner_info.key(), RaffleError::InvalidRefundAccounts);
+
+        let owner_token_account = accounts
+            .get(2)
+            .map(InterfaceAccount::<TokenAccount>::try_from)
+            .transpose()?;
+
+        raffle.refund_entries(quantity)?;
+        raffle.account_closed()?;
+        pay_out(
+            raffle,
+            owner_info,
+            &ctx.accounts.ticket_mint,
+            &owner_token_account,
+            &ctx.accounts.escrow,
+            &ctx.accounts.token_program,
+            amount
+        )?;
+
+        msg!("{} tickets of {} refunded", quantity, ticket_owner);
+        emit!(Refunded {
+            raffle: raffle.key(),
+            owner: ticket_owner,
+            ticket_id,
+            quantity,
+            amount,
+            timestamp,
+        });
+        match (ticket, entry) {
+            (Some(ticket), _) => ticket.close(owner_info.clone())?,
+            (_, Some(entry)) => entry.close(owner_info.clone())?,
+            _ => unreachable!(),
+        }
+    }
+
+    Ok(())
+}
@@ -1,10 +1,43 @@
 use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{
+    transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked
+};
 
 use crate::{
     error::RaffleError,
-    state::Raffle,
+    events::RaffleCreated,
+    state::{
+        Config, GlobalRegistry, Raffle, RaffleMetadata, RaffleMetadataArgs, RaffleRegistry, RaffleStatus,
+        PricingMode, RandomnessSource, RegistryPage,
+    },
+    utils::{check_supported_mint, list_raffle},
+    MAX_CRANK_REWARD_BPS,
+    MAX_FEE_BPS,
+    MAX_WINNERS,
 };
 
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct CreateRaffleArgs {
+    pub ticket_price: u64,
+    // how the price of later tickets moves away from `ticket_price`
+    pub pricing: PricingMode,
+    pub max_entries: u32,
+    // tickets that must sell by the end time, zero to draw with any sale
+    pub min_entries: u32,
+    pub end_time: Option<i64>,
+    pub randomness_source: RandomnessSource,
+    pub creator_fee_bps: u16,
+    pub prize_tiers: Vec<u16>,
+    pub max_tickets_per_wallet: Option<u32>,
+    pub allowlist_root: Option<[u8; 32]>,
+    // amount of the escrowed prize asset, zero for raffles paying out the ticket pool
+    pub prize_amount: u64,
+    // share of the ticket pool paid to whoever draws the winners
+    pub crank_reward_bps: u16,
+    // display metadata, stored in its own account when set
+    pub metadata: Option<RaffleMetadataArgs>,
+}
+
 #[derive(Accounts)]
 #[instruction(raffle_id: String)]
 pub struct CreateRaffle<'info> {
@@ -18,17 +51,195 @@ pub struct CreateRaffle<'info> {
         bump,
     )]
     pub raffle: Account<'info, Raffle>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = 8 + RaffleRegistry::INIT_SPACE,
+        seeds = [b"registry", owner.key().as_ref()],
+        bump,
+    )]
+    pub registry: Box<Account<'info, RaffleRegistry>>,
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = 8 + GlobalRegistry::INIT_SPACE,
+        seeds = [b"global_registry"],
+        bump,
+    )]
+    pub global_registry: Box<Account<'info, GlobalRegistry>>,
+    // page new raffles are currently listed on
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = 8 + RegistryPage::INIT_SPACE,
+        seeds = [b"registry_page".as_ref(), &global_registry.current_page.to_le_bytes()],
+        bump,
+    )]
+    pub registry_page: Box<Account<'info, RegistryPage>>,
+    // only passed for raffles created with metadata
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + RaffleMetadata::INIT_SPACE,
+        seeds = [b"metadata", raffle.key().as_ref()],
+        bump,
+    )]
+    pub metadata: Option<Box<Account<'info, RaffleMetadata>>>,
+    // only passed for raffles priced in SPL tokens
+    pub ticket_mint: Option<InterfaceAccount<'info, Mint>>,
+    #[account(
+        init,
+        payer = owner,
+        seeds = [b"escrow", raffle.key().as_ref()],
+        bump,
+        token::mint = ticket_mint,
+        token::authority = raffle,
+        token::token_program = token_program,
+    )]
+    pub escrow: Option<InterfaceAccount<'info, TokenAccount>>,
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+    // prize accounts below are only passed for raffles with an escrowed prize asset
+    pub prize_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+    #[account(
+        mut,
+        token::mint = prize_mint,
+        token::authority = owner,
+        token::token_program = prize_token_program,
+    )]
+    pub owner_prize_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    #[account(
+        init,
+        payer = owner,
+        seeds = [b"prize_vault", raffle.key().as_ref()],
+        bump,
+        token::mint = prize_mint,
+        token::authority = raffle,
+        token::token_program = prize_token_program,
+    )]
+    pub prize_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    pub prize_token_program: Option<Interface<'info, TokenInterface>>,
     pub system_program: Program<'info, System>
 }
 
 pub fn create_raffle_handler(
     ctx: Context<CreateRaffle>,
     raffle_id: String,
-    ticket_price: u64,
-    max_entries: u32,
+    args: CreateRaffleArgs,
 ) -> Result<()> {
+    let CreateRaffleArgs {
+        ticket_price,
+        pricing,
+        max_entries,
+        min_entries,
+        end_time,
+        randomness_source,
+        creator_fee_bps,
+        prize_tiers,
+        max_tickets_per_wallet,
+        allowlist_root,
+        prize_amount,
+        crank_reward_bps,
+        metadata,
+    } = args;
+
+    ctx.accounts.config.check_not_paused()?;
+    let registry = &mut ctx.accounts.registry;
+    registry.owner = ctx.accounts.owner.key();
+    registry.bump = ctx.bumps.registry;
+    registry.record_raffle(&raffle_id)?;
     require!(ticket_price > 0, RaffleError::InvalidTicketPrice);
+    pricing.validate()?;
     require!(max_entries > 0, RaffleError::InvalidMaxEntries);
+    require!(min_entries <= max_entries, RaffleError::InvalidMinEntries);
+    require!(max_tickets_per_wallet != Some(0), RaffleError::InvalidMaxEntries);
+    require!(
+        !prize_tiers.is_empty()
+            && prize_tiers.len() <= MAX_WINNERS
+            && prize_tiers.len() <= max_entries as usize
+            && prize_tiers.iter().map(|&bps| bps as u32).sum::<u32>() == MAX_FEE_BPS as u32,
+        RaffleError::InvalidPrizeTiers
+    );
+
+    require!(crank_reward_bps <= MAX_CRANK_REWARD_BPS, RaffleError::InvalidCrankReward);
+
+    let protocol_fee_bps = ctx.accounts.config.protocol_fee_bps;
+    require!(
+        creator_fee_bps as u32 + protocol_fee_bps as u32 <= MAX_FEE_BPS as u32,
+        RaffleError::InvalidFee
+    );
+
+    let now = Clock::get()?.unix_timestamp;
+    if let Some(end_time) = end_time {
+        require!(end_time > now, RaffleError::InvalidEndTime);
+    }
+
+    let ticket_mint = ctx.accounts.ticket_mint.as_ref().map(|mint| mint.key());
+    require!(
+        ticket_mint.is_some() == ctx.accounts.escrow.is_some(),
+        RaffleError::TokenAccountsRequired
+    );
+    if let Some(mint) = &ctx.accounts.ticket_mint {
+        check_supported_mint(mint)?;
+    }
+
+    let prize_mint = ctx.accounts.prize_mint.as_ref().map(|mint| mint.key());
+    if prize_mint.is_some() {
+        let (Some(mint), Some(from), Some(vault), Some(token_program)) = (
+            &ctx.accounts.prize_mint,
+            &ctx.accounts.owner_prize_account,
+            &ctx.accounts.prize_vault,
+            &ctx.accounts.prize_token_program,
+        ) else {
+            return err!(RaffleError::PrizeAccountsRequired);
+        };
+        check_supported_mint(mint)?;
+        require!(prize_amount > 0, RaffleError::InvalidPrizeAmount);
+        // a single asset can not be split between prize tiers
+        require!(prize_tiers.len() == 1, RaffleError::InvalidPrizeTiers);
+
+        msg!("Escrowing {} prize tokens", prize_amount);
+        transfer_checked(
+            CpiContext::new(
+                token_program.to_account_info(),
+                TransferChecked {
+                    from: from.to_account_info(),
+                    mint: mint.to_account_info(),
+                    to: vault.to_account_info(),
+                    authority: ctx.accounts.owner.to_account_info(),
+                }
+            ),
+            prize_amount,
+            mint.decimals
+        )?;
+    } else {
+        require!(
+            prize_amount == 0 && ctx.accounts.prize_vault.is_none(),
+            RaffleError::PrizeAccountsRequired
+        );
+    }
+
+    match (metadata, &mut ctx.accounts.metadata) {
+        (Some(args), Some(account)) => {
+            account.raffle = ctx.accounts.raffle.key();
+            account.bump = ctx.bumps.metadata.ok_or(RaffleError::MetadataAccountsRequired)?;
+            account.apply(args)?;
+        }
+        (None, None) => {},
+        _ => return err!(RaffleError::MetadataAccountsRequired),
+    }
+
+    let registry_page = list_raffle(
+        ctx.accounts.raffle.key(),
+        &mut ctx.accounts.global_registry,
+        ctx.bumps.global_registry,
+        &mut ctx.accounts.registry_page,
+        ctx.bumps.registry_page
+    )?;
 
     ctx.accounts.raffle.set_inner(Raffle {
         owner: ctx.accounts.owner.key(),
@@ -36,11 +247,47 @@ pub fn create_raffle_handler(
         ticket_price,
         max_entries,
         entries: 0,
-        is_active: true,
-        is_claimed: false,
-        winner_ticket: None,
-        created_at: Clock::get()?.unix_timestamp,
+        status: RaffleStatus::Open,
+        prize_tiers,
+        winners: Vec::new(),
+        created_at: now,
         bump: ctx.bumps.raffle,
+        randomness_source,
+        entropy: [0; 32],
+        sold_out_at: None,
+        ticket_mint,
+        end_time,
+        creator_fee_bps,
+        protocol_fee_bps,
+        max_tickets_per_wallet,
+        allowlist_root,
+        prize_mint,
+        prize_amount,
+        refunded_entries: 0,
+        crank_reward_bps,
+        crank_reward_paid: 0,
+        paused: false,
+        registry_page: Some(registry_page),
+        min_entries,
+        pricing,
+        ticket_sales: 0,
+        open_accounts: 0,
+        last_ticket_slot: 0,
+        proceeds_withdrawn: false,
+    });
+
+    let raffle = &ctx.accounts.raffle;
+    emit!(RaffleCreated {
+        raffle: raffle.key(),
+        owner: raffle.owner,
+        raffle_id: raffle.raffle_id.clone(),
+        ticket_price,
+        max_entries,
+        ticket_mint,
+        prize_mint,
+        prize_amount,
+        end_time,
+        timestamp: now,
     });
 
     Ok(())
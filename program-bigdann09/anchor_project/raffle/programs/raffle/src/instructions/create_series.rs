@@ -0,0 +1,123 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+
+use crate::{
+    error::RaffleError,
+    state::{Config, RaffleSeries},
+    utils::check_supported_mint,
+    MAX_CRANK_REWARD_BPS,
+    MAX_FEE_BPS,
+    MAX_SERIES_ID_LEN,
+    MAX_WINNERS,
+};
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct CreateSeriesArgs {
+    pub ticket_price: u64,
+    pub max_entries: u32,
+    pub round_duration: Option<i64>,
+    // amount of the prize asset escrowed by every round, zero for rounds paying out the ticket pool
+    pub prize_amount: u64,
+    pub creator_fee_bps: u16,
+    pub prize_tiers: Vec<u16>,
+    pub max_tickets_per_wallet: Option<u32>,
+    pub crank_reward_bps: u16,
+}
+
+#[derive(Accounts)]
+#[instruction(series_id: String)]
+pub struct CreateSeries<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + RaffleSeries::INIT_SPACE,
+        seeds = [b"series", owner.key().as_ref(), series_id.as_ref()],
+        bump,
+    )]
+    pub series: Account<'info, RaffleSeries>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    // only passed for series priced in SPL tokens
+    pub ticket_mint: Option<InterfaceAccount<'info, Mint>>,
+    // only passed for series escrowing a prize asset every round
+    pub prize_mint: Option<InterfaceAccount<'info, Mint>>,
+    pub system_program: Program<'info, System>
+}
+
+pub fn create_series_handler(
+    ctx: Context<CreateSeries>,
+    series_id: String,
+    args: CreateSeriesArgs,
+) -> Result<()> {
+    let CreateSeriesArgs {
+        ticket_price,
+        max_entries,
+        round_duration,
+        prize_amount,
+        creator_fee_bps,
+        prize_tiers,
+        max_tickets_per_wallet,
+        crank_reward_bps,
+    } = args;
+
+    ctx.accounts.config.check_not_paused()?;
+    require!(
+        !series_id.is_empty() && series_id.len() <= MAX_SERIES_ID_LEN,
+        RaffleError::InvalidSeriesId
+    );
+    require!(ticket_price > 0, RaffleError::InvalidTicketPrice);
+    require!(max_entries > 0, RaffleError::InvalidMaxEntries);
+    require!(max_tickets_per_wallet != Some(0), RaffleError::InvalidMaxEntries);
+    require!(
+        round_duration.is_none_or(|duration| duration > 0),
+        RaffleError::InvalidRoundDuration
+    );
+    require!(
+        !prize_tiers.is_empty()
+            && prize_tiers.len() <= MAX_WINNERS
+            && prize_tiers.len() <= max_entries as usize
+            && prize_tiers.iter().map(|&bps| bps as u32).sum::<u32>() == MAX_FEE_BPS as u32,
+        RaffleError::InvalidPrizeTiers
+    );
+    for mint in [&ctx.accounts.ticket_mint, &ctx.accounts.prize_mint].into_iter().flatten() {
+        check_supported_mint(mint)?;
+    }
+    let prize_mint = ctx.accounts.prize_mint.as_ref().map(|mint| mint.key());
+    if prize_mint.is_some() {
+        require!(prize_amount > 0, RaffleError::InvalidPrizeAmount);
+        // a single asset can not be split between prize tiers
+        require!(prize_tiers.len() == 1, RaffleError::InvalidPrizeTiers);
+    } else {
+        require!(prize_amount == 0, RaffleError::PrizeAccountsRequired);
+    }
+    require!(crank_reward_bps <= MAX_CRANK_REWARD_BPS, RaffleError::InvalidCrankReward);
+    require!(
+        creator_fee_bps as u32 + ctx.accounts.config.protocol_fee_bps as u32 <= MAX_FEE_BPS as u32,
+        RaffleError::InvalidFee
+    );
+
+    ctx.accounts.series.set_inner(RaffleSeries {
+        owner: ctx.accounts.owner.key(),
+        series_id,
+        ticket_price,
+        max_entries,
+        round_duration,
+        ticket_mint: ctx.accounts.ticket_mint.as_ref().map(|mint| mint.key()),
+        prize_mint,
+        prize_amount,
+        creator_fee_bps,
+        prize_tiers,
+        max_tickets_per_wallet,
+        crank_reward_bps,
+        rounds: 0,
+        bump: ctx.bumps.series,
+    });
+    msg!("Raffle series {} created", ctx.accounts.series.series_id);
+
+    Ok(())
+}
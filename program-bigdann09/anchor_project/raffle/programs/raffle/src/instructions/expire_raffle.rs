@@ -0,0 +1,85 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    state::{Config, Raffle, RaffleStatus, RandomnessSource, RegistryPage},
+    error::RaffleError,
+    events::RaffleExpired,
+    utils::unlist_raffle,
+    DRAW_TIMEOUT
+};
+
+#[derive(Accounts)]
+#[instruction(raffle_id: String)]
+pub struct ExpireRaffle<'info> {
+    #[account(mut)]
+    pub caller: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"raffle", raffle.owner.as_ref(), raffle_id.as_ref()],
+        bump = raffle.bump
+    )]
+    pub raffle: Account<'info, Raffle>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    // page of the global registry still listing the raffle, only needed while
+    // it has not left `Open`
+    #[account(
+        mut,
+        seeds = [b"registry_page".as_ref(), &registry_page.page.to_le_bytes()],
+        bump = registry_page.bump
+    )]
+    pub registry_page: Option<Account<'info, RegistryPage>>
+}
+
+/// Moves an undersold raffle past its end time to `Expired` so its tickets can
+/// be refunded. Raffles short of `min_entries` expire right away, others only
+/// once the owner had `DRAW_TIMEOUT` to draw. Sold out oracle raffles expire
+/// the same way if the oracle never delivers. Commit-reveal raffles that can be
+/// drawn never expire, they settle through `reveal_winner` or `expire_reveal`.
+pub fn expire_raffle_handler(
+    ctx: Context<ExpireRaffle>,
+    _raffle_id: String,
+) -> Result<()> {
+    let raffle: &mut Account<Raffle> = &mut ctx.accounts.raffle;
+    let clock: Clock = Clock::get()?;
+
+    // paused the same way as the draws, so a pause can not run the clock out on them
+    raffle.check_not_paused(&ctx.accounts.config)?;
+    let expires_at = match raffle.status {
+        RaffleStatus::Open => {
+            let end_time = raffle.end_time.ok_or(RaffleError::ExpiryNotReached)?;
+            if !raffle.meets_min_entries() {
+                end_time
+            } else if raffle.commitment().is_some() {
+                // the owner knows the outcome once the raffle ends, withholding
+                // the reveal must not turn a losing draw into refunds
+                return err!(RaffleError::RevealRequired);
+            } else {
+                end_time + DRAW_TIMEOUT
+            }
+        }
+        RaffleStatus::Drawing if matches!(raffle.randomness_source, RandomnessSource::Oracle(_)) => {
+            let drawable_since = raffle.drawable_since(clock.unix_timestamp)
+                .ok_or(RaffleError::ExpiryNotReached)?;
+            drawable_since + DRAW_TIMEOUT
+        }
+        _ => return err!(RaffleError::RaffleNotActive),
+    };
+    require!(clock.unix_timestamp >= expires_at, RaffleError::ExpiryNotReached);
+
+    raffle.transition(RaffleStatus::Expired)?;
+    unlist_raffle(raffle, &mut ctx.accounts.registry_page)?;
+    msg!("Raffle {} expired with {} entries", raffle.raffle_id, raffle.entries);
+
+    emit!(RaffleExpired {
+        raffle: raffle.key(),
+        owner: raffle.owner,
+        entries: raffle.entries,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,96 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::hash::hash
+};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::{
+    state::{Config, Raffle, RegistryPage},
+    error::RaffleError,
+    events::WinnerPicked,
+    utils::{pay_crank_reward, unlist_raffle},
+    REVEAL_TIMEOUT
+};
+
+#[derive(Accounts)]
+#[instruction(raffle_id: String)]
+pub struct ExpireReveal<'info> {
+    #[account(mut)]
+    pub caller: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"raffle", raffle.owner.as_ref(), raffle_id.as_ref()],
+        bump = raffle.bump
+    )]
+    pub raffle: Account<'info, Raffle>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    // page of the global registry still listing the raffle, only needed while
+    // it has not left `Open`
+    #[account(
+        mut,
+        seeds = [b"registry_page".as_ref(), &registry_page.page.to_le_bytes()],
+        bump = registry_page.bump
+    )]
+    pub registry_page: Option<Account<'info, RegistryPage>>
+,
+    // token accounts below are only passed to pay the crank reward of raffles priced in SPL tokens
+    pub ticket_mint: Option<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        token::mint = ticket_mint,
+        token::token_program = token_program,
+    )]
+    pub caller_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = [b"escrow", raffle.key().as_ref()],
+        bump,
+    )]
+    pub escrow: Option<InterfaceAccount<'info, TokenAccount>>,
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+}
+
+/// Draws the winner from the buyers' entropy alone when the owner failed to
+/// reveal their secret in time. The seed only depends on values fixed once
+/// the raffle stopped selling, so the caller can not grind it by picking the
+/// slot they submit in.
+pub fn expire_reveal_handler(
+    ctx: Context<ExpireReveal>,
+    _raffle_id: String,
+) -> Result<()> {
+    let raffle: &mut Account<Raffle> = &mut ctx.accounts.raffle;
+    let clock: Clock = Clock::get()?;
+
+    raffle.check_not_paused(&ctx.accounts.config)?;
+    let drawable_since = raffle.check_drawable(clock.unix_timestamp)?;
+    let commitment = raffle.commitment().ok_or(RaffleError::NoCommitment)?;
+    require!(
+        clock.unix_timestamp > drawable_since + REVEAL_TIMEOUT,
+        RaffleError::RevealTimeoutNotReached
+    );
+
+    let hash_bytes = hash(&[&raffle.entropy[..], &commitment[..]].concat()).to_bytes();
+    raffle.draw_winners(&hash_bytes)?;
+    unlist_raffle(raffle, &mut ctx.accounts.registry_page)?;
+    pay_crank_reward(
+        raffle,
+        &ctx.accounts.caller.to_account_info(),
+        &ctx.accounts.ticket_mint,
+        &ctx.accounts.caller_token_account,
+        &ctx.accounts.escrow,
+        &ctx.accounts.token_program,
+    )?;
+
+    emit!(WinnerPicked {
+        raffle: raffle.key(),
+        winning_ticket_ids: raffle.winning_ticket_ids(),
+        entries: raffle.entries,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,57 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    state::Config,
+    error::RaffleError,
+    events::ConfigUpdated,
+    MAX_FEE_BPS
+};
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + Config::INIT_SPACE,
+        seeds = [b"config"],
+        bump,
+    )]
+    pub config: Account<'info, Config>,
+    // only the program's upgrade authority may set up the config, so nobody
+    // can claim the admin role between deployment and initialization
+    #[account(
+        seeds = [crate::ID.as_ref()],
+        // program data accounts are owned by the upgradeable loader
+        seeds::program = ProgramData::owner(),
+        bump,
+        constraint = program_data.upgrade_authority_address == Some(admin.key()) @ RaffleError::Unauthorized
+    )]
+    pub program_data: Account<'info, ProgramData>,
+    pub system_program: Program<'info, System>
+}
+
+pub fn initialize_config_handler(
+    ctx: Context<InitializeConfig>,
+    treasury: Pubkey,
+    protocol_fee_bps: u16,
+) -> Result<()> {
+    require!(protocol_fee_bps <= MAX_FEE_BPS, RaffleError::InvalidFee);
+
+    ctx.accounts.config.set_inner(Config {
+        admin: ctx.accounts.admin.key(),
+        treasury,
+        protocol_fee_bps,
+        bump: ctx.bumps.config,
+        paused: false,
+    });
+
+    emit!(ConfigUpdated {
+        admin: ctx.accounts.admin.key(),
+        treasury,
+        protocol_fee_bps,
+    });
+
+    Ok(())
+}
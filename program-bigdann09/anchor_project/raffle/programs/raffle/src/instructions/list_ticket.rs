@@ -0,0 +1,72 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    state::{Config, Listing, Raffle, Ticket},
+    error::RaffleError,
+    events::TicketListed
+};
+
+#[derive(Accounts)]
+#[instruction(raffle_id: String)]
+pub struct ListTicket<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        seeds = [b"raffle", raffle.owner.as_ref(), raffle_id.as_ref()],
+        bump = raffle.bump
+    )]
+    pub raffle: Account<'info, Raffle>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        has_one = owner,
+        has_one = raffle,
+        seeds = [b"ticket", raffle.key().as_ref(), &ticket.ticket_id.to_le_bytes()],
+        bump = ticket.bump
+    )]
+    pub ticket: Account<'info, Ticket>,
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + Listing::INIT_SPACE,
+        seeds = [b"listing", ticket.key().as_ref()],
+        bump,
+    )]
+    pub listing: Account<'info, Listing>,
+    pub system_program: Program<'info, System>
+}
+
+/// Offers a ticket for sale at `price` lamports. To change the price, cancel
+/// the listing and list the ticket again.
+pub fn list_ticket_handler(
+    ctx: Context<ListTicket>,
+    _raffle_id: String,
+    price: u64,
+) -> Result<()> {
+    let clock: Clock = Clock::get()?;
+    ctx.accounts.raffle.check_transferable(&ctx.accounts.config, clock.unix_timestamp)?;
+    require!(price > 0, RaffleError::InvalidListingPrice);
+
+    let ticket = &ctx.accounts.ticket;
+    ctx.accounts.listing.set_inner(Listing {
+        raffle: ticket.raffle,
+        ticket: ticket.key(),
+        seller: ticket.owner,
+        price,
+        bump: ctx.bumps.listing,
+    });
+    msg!("Ticket {} listed for {} lamports", ticket.ticket_id, price);
+
+    emit!(TicketListed {
+        raffle: ticket.raffle,
+        ticket_id: ticket.ticket_id,
+        seller: ticket.owner,
+        price,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,138 @@
+use anchor_lang::{
+    prelude::*,
+    system_program::{transfer, Transfer},
+    Discriminator
+};
+
+use crate::{
+    state::{PricingMode, Raffle, RaffleStatus, RandomnessSource, Winner},
+    error::RaffleError,
+    MAX_FEE_BPS
+};
+
+/// Layout of `Raffle` accounts created before the lifecycle status was added.
+#[derive(AnchorDeserialize, InitSpace)]
+pub struct LegacyRaffle {
+    pub owner: Pubkey,
+    #[max_len(30)]
+    pub raffle_id: String,
+    pub ticket_price: u64,
+    pub max_entries: u32,
+    pub entries: u32,
+    pub is_active: bool,
+    pub is_claimed: bool,
+    pub winner_ticket: Option<u32>,
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+#[derive(Accounts)]
+#[instruction(raffle_id: String)]
+pub struct MigrateRaffle<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    /// CHECK: deserialized manually from the legacy layout, address checked by seeds
+    #[account(
+        mut,
+        owner = crate::ID,
+        seeds = [b"raffle", owner.key().as_ref(), raffle_id.as_ref()],
+        bump
+    )]
+    pub raffle: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>
+}
+
+pub fn migrate_raffle_handler(
+    ctx: Context<MigrateRaffle>,
+    _raffle_id: String,
+) -> Result<()> {
+    let raffle_info = ctx.accounts.raffle.to_account_info();
+    let legacy_len = 8 + LegacyRaffle::INIT_SPACE;
+    let new_len = 8 + Raffle::INIT_SPACE;
+
+    let legacy = {
+        let data = raffle_info.try_borrow_data()?;
+        require!(
+            data.len() == legacy_len && data.starts_with(Raffle::DISCRIMINATOR),
+            RaffleError::InvalidLegacyAccount
+        );
+        LegacyRaffle::deserialize(&mut &data[8..])?
+    };
+    require_keys_eq!(legacy.owner, ctx.accounts.owner.key(), RaffleError::Unauthorized);
+
+    let now = Clock::get()?.unix_timestamp;
+    let sold_out = legacy.entries == legacy.max_entries;
+    // legacy raffles only cleared `is_active` when the winner was picked
+    let status = if legacy.is_claimed {
+        RaffleStatus::Claimed
+    } else if legacy.winner_ticket.is_some() || !legacy.is_active {
+        RaffleStatus::Drawn
+    } else if sold_out {
+        RaffleStatus::Drawing
+    } else {
+        RaffleStatus::Open
+    };
+
+    // the account already holds the ticket pool, so only top up the extra rent
+    let rent = Rent::get()?;
+    let extra_rent = rent.minimum_balance(new_len)
+        .saturating_sub(rent.minimum_balance(legacy_len));
+    transfer(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.owner.to_account_info(),
+                to: raffle_info.clone(),
+            }
+        ),
+        extra_rent
+    )?;
+    raffle_info.resize(new_len)?;
+
+    let raffle = Raffle {
+        owner: legacy.owner,
+        raffle_id: legacy.raffle_id,
+        ticket_price: legacy.ticket_price,
+        max_entries: legacy.max_entries,
+        entries: legacy.entries,
+        status,
+        prize_tiers: vec![MAX_FEE_BPS],
+        winners: legacy.winner_ticket
+            .map(|ticket_id| Winner { ticket_id, claimed: legacy.is_claimed })
+            .into_iter()
+            .collect(),
+        created_at: legacy.created_at,
+        bump: legacy.bump,
+        randomness_source: RandomnessSource::SlotHash,
+        entropy: [0; 32],
+        sold_out_at: sold_out.then_some(now),
+        ticket_mint: None,
+        end_time: None,
+        // legacy raffles were sold without fees
+        creator_fee_bps: 0,
+        protocol_fee_bps: 0,
+        max_tickets_per_wallet: None,
+        allowlist_root: None,
+        prize_mint: None,
+        prize_amount: 0,
+        refunded_entries: 0,
+        crank_reward_bps: 0,
+        crank_reward_paid: 0,
+        paused: false,
+        registry_page: None,
+        min_entries: 0,
+        pricing: PricingMode::Fixed,
+        // every legacy ticket was sold at the fixed price
+        ticket_sales: legacy.ticket_price.checked_mul(legacy.entries as u64)
+            .ok_or(RaffleError::MathOverflow)?,
+        // legacy raffles only opened a ticket account per entry
+        open_accounts: legacy.entries,
+        last_ticket_slot: 0,
+        proceeds_withdrawn: false,
+    };
+    let mut data = raffle_info.try_borrow_mut_data()?;
+    raffle.try_serialize(&mut &mut data[..])?;
+    msg!("Raffle {} migrated with status {:?}", raffle.raffle_id, raffle.status);
+
+    Ok(())
+}
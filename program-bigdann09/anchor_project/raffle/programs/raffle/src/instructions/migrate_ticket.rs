@@ -0,0 +1,91 @@
+use anchor_lang::{
+    prelude::*,
+    system_program::{transfer, Transfer},
+    Discriminator
+};
+
+use crate::{
+    state::{Raffle, Ticket},
+    error::RaffleError
+};
+
+/// Layout of `Ticket` accounts created before the price paid was recorded.
+#[derive(AnchorDeserialize, InitSpace)]
+pub struct LegacyTicket {
+    pub raffle: Pubkey,
+    pub ticket_id: u32,
+    pub owner: Pubkey,
+    pub bump: u8,
+}
+
+#[derive(Accounts)]
+#[instruction(raffle_id: String, ticket_id: u32)]
+pub struct MigrateTicket<'info> {
+    // anyone may pay the extra rent to migrate a ticket
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [b"raffle", raffle.owner.as_ref(), raffle_id.as_ref()],
+        bump = raffle.bump
+    )]
+    pub raffle: Account<'info, Raffle>,
+    /// CHECK: deserialized manually from the legacy layout, address checked by seeds
+    #[account(
+        mut,
+        owner = crate::ID,
+        seeds = [b"ticket", raffle.key().as_ref(), &ticket_id.to_le_bytes()],
+        bump
+    )]
+    pub ticket: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>
+}
+
+/// Upgrades a ticket to the current layout. Tickets bought before prices
+/// could change were all sold at the raffle's base price.
+pub fn migrate_ticket_handler(
+    ctx: Context<MigrateTicket>,
+    _raffle_id: String,
+    _ticket_id: u32,
+) -> Result<()> {
+    let ticket_info = ctx.accounts.ticket.to_account_info();
+    let legacy_len = 8 + LegacyTicket::INIT_SPACE;
+    let new_len = 8 + Ticket::INIT_SPACE;
+
+    let legacy = {
+        let data = ticket_info.try_borrow_data()?;
+        require!(
+            data.len() == legacy_len && data.starts_with(Ticket::DISCRIMINATOR),
+            RaffleError::InvalidLegacyAccount
+        );
+        LegacyTicket::deserialize(&mut &data[8..])?
+    };
+    require_keys_eq!(legacy.raffle, ctx.accounts.raffle.key(), RaffleError::InvalidLegacyAccount);
+
+    let rent = Rent::get()?;
+    let extra_rent = rent.minimum_balance(new_len)
+        .saturating_sub(rent.minimum_balance(legacy_len));
+    transfer(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.payer.to_account_info(),
+                to: ticket_info.clone(),
+            }
+        ),
+        extra_rent
+    )?;
+    ticket_info.resize(new_len)?;
+
+    let ticket = Ticket {
+        raffle: legacy.raffle,
+        ticket_id: legacy.ticket_id,
+        owner: legacy.owner,
+        bump: legacy.bump,
+        price_paid: ctx.accounts.raffle.ticket_price,
+    };
+    let mut data = ticket_info.try_borrow_mut_data()?;
+    ticket.try_serialize(&mut &mut data[..])?;
+    msg!("Ticket {} migrated", ticket.ticket_id);
+
+    Ok(())
+}
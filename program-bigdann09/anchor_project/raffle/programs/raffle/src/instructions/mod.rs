@@ -10,3 +10,86 @@ pub use pick_winner::*;
 pub mod claim_prize;
 pub use claim_prize::*;
 
+pub mod reveal_winner;
+pub use reveal_winner::*;
+
+pub mod expire_reveal;
+pub use expire_reveal::*;
+
+pub mod consume_randomness;
+pub use consume_randomness::*;
+
+pub mod cancel_raffle;
+pub use cancel_raffle::*;
+
+pub mod refund_ticket;
+pub use refund_ticket::*;
+
+pub mod crank_refunds;
+pub use crank_refunds::*;
+
+pub mod expire_raffle;
+pub use expire_raffle::*;
+
+pub mod migrate_raffle;
+pub use migrate_raffle::*;
+
+pub mod migrate_ticket;
+pub use migrate_ticket::*;
+
+pub mod initialize_config;
+pub use initialize_config::*;
+
+pub mod update_config;
+pub use update_config::*;
+
+pub mod buy_tickets;
+pub use buy_tickets::*;
+
+
+pub mod reclaim_prize;
+pub use reclaim_prize::*;
+
+pub mod close_ticket;
+pub use close_ticket::*;
+
+pub mod close_raffle;
+pub use close_raffle::*;
+
+pub mod create_series;
+pub use create_series::*;
+
+pub mod start_next_round;
+pub use start_next_round::*;
+pub mod set_protocol_paused;
+pub use set_protocol_paused::*;
+
+pub mod set_raffle_paused;
+pub use set_raffle_paused::*;
+
+pub mod transfer_ticket;
+pub use transfer_ticket::*;
+
+pub mod list_ticket;
+pub use list_ticket::*;
+
+pub mod cancel_listing;
+pub use cancel_listing::*;
+
+pub mod buy_listed_ticket;
+pub use buy_listed_ticket::*;
+
+pub mod update_metadata;
+pub use update_metadata::*;
+
+pub mod close_entry;
+pub use close_entry::*;
+
+pub mod close_participant;
+pub use close_participant::*;
+
+pub mod withdraw_proceeds;
+pub use withdraw_proceeds::*;
+
+pub mod crank_close_accounts;
+pub use crank_close_accounts::*;
@@ -2,26 +2,63 @@ use anchor_lang::{
     prelude::*,
     solana_program::hash::hash
 };
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 
 use crate::{
-    state::Raffle,
-    error::RaffleError
+    state::{Config, Raffle, RaffleStatus, RandomnessSource, RegistryPage},
+    error::RaffleError,
+    events::{RaffleExpired, WinnerPicked},
+    utils::{pay_crank_reward, unlist_raffle}
 };
 
 #[derive(Accounts)]
 #[instruction(raffle_id: String)]
 pub struct PickWinner<'info> {
+    // anyone may draw once the raffle is drawable, so an absent owner can not
+    // hold the ticket pool hostage
     #[account(mut)]
-    pub owner: Signer<'info>,
+    pub caller: Signer<'info>,
     #[account(
         mut,
-        has_one = owner,
-        seeds = [b"raffle", owner.key().as_ref(), raffle_id.as_ref()],
+        seeds = [b"raffle", raffle.owner.as_ref(), raffle_id.as_ref()],
         bump = raffle.bump
     )]
-    pub raffle: Account<'info, Raffle>
+    pub raffle: Account<'info, Raffle>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    // page of the global registry still listing the raffle, only needed while
+    // it has not left `Open`
+    #[account(
+        mut,
+        seeds = [b"registry_page".as_ref(), &registry_page.page.to_le_bytes()],
+        bump = registry_page.bump
+    )]
+    pub registry_page: Option<Account<'info, RegistryPage>>,
+    // token accounts below are only passed to pay the crank reward of raffles priced in SPL tokens
+    pub ticket_mint: Option<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        token::mint = ticket_mint,
+        token::token_program = token_program,
+    )]
+    pub caller_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = [b"escrow", raffle.key().as_ref()],
+        bump,
+    )]
+    pub escrow: Option<InterfaceAccount<'info, TokenAccount>>,
+    pub token_program: Option<Interface<'info, TokenInterface>>,
 }
 
+/// Draws the winners of a `SlotHash` raffle, or expires it when it ended short
+/// of its minimum entries. Anyone may submit the draw and earn the crank
+/// reward, which also means a ticket holder can choose the slot they submit in
+/// and grind the seed. `SlotHash` is only meant for low value raffles; use
+/// `CommitReveal` or an oracle when the prize is worth that effort.
 pub fn pick_winner_handler(
     ctx: Context<PickWinner>,
     raffle_id: String,
@@ -29,27 +66,63 @@ pub fn pick_winner_handler(
     let raffle: &mut Account<Raffle> = &mut ctx.accounts.raffle;
     let clock: Clock = Clock::get()?;
 
-    require!(raffle.is_active, RaffleError::RaffleNotActive);
-    require!(raffle.entries == raffle.max_entries, RaffleError::EntriesNotFull);
-    
-    require!(raffle.winner_ticket.is_none(), RaffleError::WinnerChosen);
+    raffle.check_not_paused(&ctx.accounts.config)?;
+
+    // a raffle that ended short of its minimum can never be drawn, so the
+    // draw expires it instead and its tickets become refundable
+    if raffle.status == RaffleStatus::Open
+        && raffle.has_ended(clock.unix_timestamp)
+        && !raffle.meets_min_entries()
+    {
+        raffle.transition(RaffleStatus::Expired)?;
+        unlist_raffle(raffle, &mut ctx.accounts.registry_page)?;
+        msg!(
+            "Raffle {} expired with {} of {} minimum entries",
+            raffle.raffle_id,
+            raffle.entries,
+            raffle.min_entries
+        );
+
+        emit!(RaffleExpired {
+            raffle: raffle.key(),
+            owner: raffle.owner,
+            entries: raffle.entries,
+            timestamp: clock.unix_timestamp,
+        });
+        return Ok(());
+    }
+
+    raffle.check_drawable(clock.unix_timestamp)?;
+    match raffle.randomness_source {
+        RandomnessSource::SlotHash => {},
+        RandomnessSource::CommitReveal { .. } => return err!(RaffleError::RevealRequired),
+        RandomnessSource::Oracle(_) => return err!(RaffleError::OracleRequired),
+    }
 
     let hash_bytes = hash(&[
         &clock.unix_timestamp.to_be_bytes(),
         &clock.slot.to_be_bytes(),
         raffle_id.as_bytes()
     ].concat()).to_bytes();
-    
-    // Fix 2: Use only the first 8 bytes and avoid overflow-prone operations
-    let random_seed = u64::from_le_bytes(
-        <[u8; 8]>::try_from(&hash_bytes[..8]).unwrap()
-    );
-
-    let winner_ticket = ((random_seed % raffle.max_entries as u64) + 1) as u32;
-    
-    raffle.is_active = false;
-    raffle.winner_ticket = Some(winner_ticket);
-    msg!("winner id: {}", winner_ticket);
+
+    raffle.draw_winners(&hash_bytes)?;
+    unlist_raffle(raffle, &mut ctx.accounts.registry_page)?;
+
+    pay_crank_reward(
+        raffle,
+        &ctx.accounts.caller.to_account_info(),
+        &ctx.accounts.ticket_mint,
+        &ctx.accounts.caller_token_account,
+        &ctx.accounts.escrow,
+        &ctx.accounts.token_program,
+    )?;
+
+    emit!(WinnerPicked {
+        raffle: raffle.key(),
+        winning_ticket_ids: raffle.winning_ticket_ids(),
+        entries: raffle.entries,
+        timestamp: clock.unix_timestamp,
+    });
 
     Ok(())
-}
\ No newline at end of file
+}
@@ -0,0 +1,74 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::{
+    state::Raffle,
+    error::RaffleError,
+    events::PrizeReclaimed,
+    utils::release_prize
+};
+
+#[derive(Accounts)]
+#[instruction(raffle_id: String)]
+pub struct ReclaimPrize<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        has_one = owner,
+        seeds = [b"raffle", owner.key().as_ref(), raffle_id.as_ref()],
+        bump = raffle.bump
+    )]
+    pub raffle: Account<'info, Raffle>,
+    pub prize_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+    #[account(
+        mut,
+        token::mint = prize_mint,
+        token::authority = owner,
+        token::token_program = prize_token_program,
+    )]
+    pub owner_prize_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    #[account(
+        mut,
+        seeds = [b"prize_vault", raffle.key().as_ref()],
+        bump,
+    )]
+    pub prize_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    pub prize_token_program: Option<Interface<'info, TokenInterface>>,
+}
+
+/// Returns the escrowed prize asset of a cancelled or expired raffle to its
+/// creator. The prize vault is closed, so the prize can only be reclaimed once.
+pub fn reclaim_prize_handler(
+    ctx: Context<ReclaimPrize>,
+    _raffle_id: String,
+) -> Result<()> {
+    let raffle: &mut Account<Raffle> = &mut ctx.accounts.raffle;
+
+    require!(raffle.status.is_refundable(), RaffleError::RaffleNotRefundable);
+    let prize_amount = raffle.prize_amount;
+    let Some(prize_mint) = raffle.prize_mint.filter(|_| prize_amount > 0) else {
+        return err!(RaffleError::PrizeAccountsRequired);
+    };
+
+    release_prize(
+        raffle,
+        &ctx.accounts.owner.key(),
+        &ctx.accounts.prize_mint,
+        &ctx.accounts.owner_prize_account,
+        &ctx.accounts.prize_vault,
+        &ctx.accounts.prize_token_program,
+        &ctx.accounts.owner.to_account_info()
+    )?;
+    msg!("Prize of raffle {} returned to its creator", raffle.raffle_id);
+
+    emit!(PrizeReclaimed {
+        raffle: raffle.key(),
+        creator: raffle.owner,
+        mint: prize_mint,
+        amount: prize_amount,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
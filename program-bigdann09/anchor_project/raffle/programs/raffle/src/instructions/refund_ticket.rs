@@ -0,0 +1,98 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::{
+    state::{Entry, Raffle, Ticket},
+    error::RaffleError,
+    events::Refunded,
+    utils::pay_out
+};
+
+#[derive(Accounts)]
+#[instruction(raffle_id: String)]
+pub struct RefundTicket<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"raffle", raffle.owner.as_ref(), raffle_id.as_ref()],
+        bump = raffle.bump
+    )]
+    pub raffle: Account<'info, Raffle>,
+    #[account(
+        mut,
+        close = owner,
+        has_one = owner,
+        has_one = raffle,
+        seeds = [b"ticket", raffle.key().as_ref(), &ticket.ticket_id.to_le_bytes()],
+        bump = ticket.bump
+    )]
+    pub ticket: Option<Account<'info, Ticket>>,
+    // tickets bought in bulk are refunded through their entry instead
+    #[account(
+        mut,
+        close = owner,
+        has_one = owner,
+        has_one = raffle,
+        seeds = [b"entry", raffle.key().as_ref(), owner.key().as_ref()],
+        bump = entry.bump
+    )]
+    pub entry: Option<Account<'info, Entry>>,
+    // token accounts below are only passed for raffles priced in SPL tokens
+    pub ticket_mint: Option<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        token::mint = ticket_mint,
+        token::token_program = token_program,
+    )]
+    pub owner_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = [b"escrow", raffle.key().as_ref()],
+        bump,
+    )]
+    pub escrow: Option<InterfaceAccount<'info, TokenAccount>>,
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+    pub system_program: Program<'info, System>
+}
+
+pub fn refund_ticket_handler(ctx: Context<RefundTicket>, _raffle_id: String) -> Result<()> {
+    let raffle: &mut Account<Raffle> = &mut ctx.accounts.raffle;
+
+    require!(raffle.status.is_refundable(), RaffleError::RaffleNotRefundable);
+
+    let (ticket_id, quantity, amount) = match (&ctx.accounts.ticket, &ctx.accounts.entry) {
+        (Some(ticket), None) => {
+            msg!("Ticket {} refunded", ticket.ticket_id);
+            (Some(ticket.ticket_id), 1, ticket.price_paid)
+        }
+        (None, Some(entry)) => {
+            msg!("{} entry tickets refunded", entry.ticket_count());
+            (None, entry.ticket_count(), entry.amount_paid)
+        }
+        _ => return err!(RaffleError::InvalidRefundAccounts),
+    };
+    raffle.refund_entries(quantity)?;
+    raffle.account_closed()?;
+
+    pay_out(
+        raffle,
+        &ctx.accounts.owner.to_account_info(),
+        &ctx.accounts.ticket_mint,
+        &ctx.accounts.owner_token_account,
+        &ctx.accounts.escrow,
+        &ctx.accounts.token_program,
+        amount
+    )?;
+
+    emit!(Refunded {
+        raffle: raffle.key(),
+        owner: ctx.accounts.owner.key(),
+        ticket_id,
+        quantity,
+        amount,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,73 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::hash::hash
+};
+
+use crate::{
+    state::{Config, Raffle, RegistryPage},
+    error::RaffleError,
+    events::WinnerPicked,
+    utils::unlist_raffle,
+    REVEAL_TIMEOUT
+};
+
+#[derive(Accounts)]
+#[instruction(raffle_id: String)]
+pub struct RevealWinner<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        has_one = owner,
+        seeds = [b"raffle", owner.key().as_ref(), raffle_id.as_ref()],
+        bump = raffle.bump
+    )]
+    pub raffle: Account<'info, Raffle>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    // page of the global registry still listing the raffle, only needed while
+    // it has not left `Open`
+    #[account(
+        mut,
+        seeds = [b"registry_page".as_ref(), &registry_page.page.to_le_bytes()],
+        bump = registry_page.bump
+    )]
+    pub registry_page: Option<Account<'info, RegistryPage>>
+}
+
+pub fn reveal_winner_handler(
+    ctx: Context<RevealWinner>,
+    _raffle_id: String,
+    secret: [u8; 32],
+) -> Result<()> {
+    let raffle: &mut Account<Raffle> = &mut ctx.accounts.raffle;
+    let clock: Clock = Clock::get()?;
+
+    raffle.check_not_paused(&ctx.accounts.config)?;
+    let drawable_since = raffle.check_drawable(clock.unix_timestamp)?;
+
+    let commitment = raffle.commitment().ok_or(RaffleError::NoCommitment)?;
+    require!(hash(&secret).to_bytes() == commitment, RaffleError::InvalidReveal);
+
+    require!(
+        clock.unix_timestamp <= drawable_since + REVEAL_TIMEOUT,
+        RaffleError::RevealWindowClosed
+    );
+
+    // neither the owner nor the buyers know the other half of the seed in advance
+    let hash_bytes = hash(&[&secret[..], &raffle.entropy[..]].concat()).to_bytes();
+    raffle.draw_winners(&hash_bytes)?;
+    unlist_raffle(raffle, &mut ctx.accounts.registry_page)?;
+
+    emit!(WinnerPicked {
+        raffle: raffle.key(),
+        winning_ticket_ids: raffle.winning_ticket_ids(),
+        entries: raffle.entries,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,36 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    state::Config,
+    events::PauseUpdated
+};
+
+#[derive(Accounts)]
+pub struct SetProtocolPaused<'info> {
+    pub admin: Signer<'info>,
+    #[account(
+        mut,
+        has_one = admin,
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>
+}
+
+/// Halts or resumes every raffle at once. Claims, refunds and cancellations
+/// stay available while paused so no funds are frozen.
+pub fn set_protocol_paused_handler(
+    ctx: Context<SetProtocolPaused>,
+    paused: bool,
+) -> Result<()> {
+    ctx.accounts.config.paused = paused;
+    msg!("Protocol paused: {}", paused);
+
+    emit!(PauseUpdated {
+        raffle: None,
+        paused,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
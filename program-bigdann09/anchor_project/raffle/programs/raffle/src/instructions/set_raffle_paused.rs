@@ -0,0 +1,47 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    state::{Raffle, RaffleStatus},
+    error::RaffleError,
+    events::PauseUpdated
+};
+
+#[derive(Accounts)]
+#[instruction(raffle_id: String)]
+pub struct SetRafflePaused<'info> {
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        has_one = owner,
+        seeds = [b"raffle", owner.key().as_ref(), raffle_id.as_ref()],
+        bump = raffle.bump
+    )]
+    pub raffle: Account<'info, Raffle>
+}
+
+pub fn set_raffle_paused_handler(
+    ctx: Context<SetRafflePaused>,
+    _raffle_id: String,
+    paused: bool,
+) -> Result<()> {
+    let raffle: &mut Account<Raffle> = &mut ctx.accounts.raffle;
+    let now = Clock::get()?.unix_timestamp;
+    // only ticket sales can be paused, a raffle that stopped selling must stay
+    // drawable so its owner can not hold back a losing draw until it expires
+    require!(raffle.status == RaffleStatus::Open, RaffleError::RaffleNotActive);
+    require!(
+        !paused || (!raffle.has_ended(now) && raffle.drawable_since(now).is_none()),
+        RaffleError::RaffleNotActive
+    );
+
+    raffle.paused = paused;
+    msg!("Raffle {} paused: {}", raffle.raffle_id, paused);
+
+    emit!(PauseUpdated {
+        raffle: Some(raffle.key()),
+        paused,
+        timestamp: now,
+    });
+
+    Ok(())
+}
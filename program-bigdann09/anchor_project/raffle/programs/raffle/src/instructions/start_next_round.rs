@@ -0,0 +1,280 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{
+    transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked
+};
+
+use crate::{
+    error::RaffleError,
+    events::{RaffleCreated, RoundStarted},
+    state::{
+        Config, GlobalRegistry, PricingMode, Raffle, RaffleSeries, RaffleStatus,
+        RandomnessSource, RegistryPage,
+    },
+    utils::{check_supported_mint, list_raffle, release_prize},
+    MAX_FEE_BPS,
+};
+
+#[derive(Accounts)]
+#[instruction(series_id: String)]
+pub struct StartNextRound<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        has_one = owner,
+        seeds = [b"series", owner.key().as_ref(), series_id.as_ref()],
+        bump = series.bump
+    )]
+    pub series: Account<'info, RaffleSeries>,
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + Raffle::INIT_SPACE,
+        seeds = [b"raffle", owner.key().as_ref(), series.next_round_id().as_bytes()],
+        bump,
+    )]
+    pub round: Box<Account<'info, Raffle>>,
+    // latest round of the series, required once the first round has started
+    #[account(
+        mut,
+        seeds = [b"raffle", owner.key().as_ref(), series.round_id(series.rounds).as_bytes()],
+        bump = previous_round.bump
+    )]
+    pub previous_round: Option<Box<Account<'info, Raffle>>>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = 8 + GlobalRegistry::INIT_SPACE,
+        seeds = [b"global_registry"],
+        bump,
+    )]
+    pub global_registry: Box<Account<'info, GlobalRegistry>>,
+    // page new raffles are currently listed on
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = 8 + RegistryPage::INIT_SPACE,
+        seeds = [b"registry_page".as_ref(), &global_registry.current_page.to_le_bytes()],
+        bump,
+    )]
+    pub registry_page: Box<Account<'info, RegistryPage>>,
+    // only passed for series priced in SPL tokens
+    pub ticket_mint: Option<InterfaceAccount<'info, Mint>>,
+    #[account(
+        init,
+        payer = owner,
+        seeds = [b"escrow", round.key().as_ref()],
+        bump,
+        token::mint = ticket_mint,
+        token::authority = round,
+        token::token_program = token_program,
+    )]
+    pub escrow: Option<InterfaceAccount<'info, TokenAccount>>,
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+    // prize accounts below are only passed for series escrowing a prize asset
+    pub prize_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+    #[account(
+        mut,
+        token::mint = prize_mint,
+        token::authority = owner,
+        token::token_program = prize_token_program,
+    )]
+    pub owner_prize_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    // prize vault of the previous round, only passed with `roll_prize`
+    #[account(mut)]
+    pub previous_prize_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    #[account(
+        init,
+        payer = owner,
+        seeds = [b"prize_vault", round.key().as_ref()],
+        bump,
+        token::mint = prize_mint,
+        token::authority = round,
+        token::token_program = prize_token_program,
+    )]
+    pub prize_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    pub prize_token_program: Option<Interface<'info, TokenInterface>>,
+    pub system_program: Program<'info, System>
+}
+
+/// Opens the next round of a series from its template once the latest round
+/// has finished, escrowing the template prize if the series has one. Rounds
+/// are counted and listed on the registries like any other raffle. With
+/// `roll_prize`, the prize left in a previous round that expired or was
+/// cancelled is added to the new round instead of going back to the owner.
+/// Only escrowed prize assets roll over: ticket pools are always refunded to
+/// their buyers or paid to the winner, and the prize of a drawn round stays
+/// claimable by its winner.
+pub fn start_next_round_handler(
+    ctx: Context<StartNextRound>,
+    _series_id: String,
+    roll_prize: bool,
+) -> Result<()> {
+    let series = &ctx.accounts.series;
+    let now = Clock::get()?.unix_timestamp;
+
+    ctx.accounts.config.check_not_paused()?;
+    if series.rounds > 0 {
+        let previous = ctx.accounts.previous_round.as_ref()
+            .ok_or(RaffleError::PreviousRoundRequired)?;
+        require!(previous.status.is_finished(), RaffleError::RoundNotFinished);
+    }
+
+    let protocol_fee_bps = ctx.accounts.config.protocol_fee_bps;
+    require!(
+        series.creator_fee_bps as u32 + protocol_fee_bps as u32 <= MAX_FEE_BPS as u32,
+        RaffleError::InvalidFee
+    );
+
+    let ticket_mint = ctx.accounts.ticket_mint.as_ref().map(|mint| mint.key());
+    require!(
+        ticket_mint.is_some() == ctx.accounts.escrow.is_some(),
+        RaffleError::TokenAccountsRequired
+    );
+    require!(ticket_mint == series.ticket_mint, RaffleError::InvalidTicketMint);
+    if let Some(mint) = &ctx.accounts.ticket_mint {
+        check_supported_mint(mint)?;
+    }
+
+    let round_key = ctx.accounts.round.key();
+    let registry_page = list_raffle(
+        round_key,
+        &mut ctx.accounts.global_registry,
+        ctx.bumps.global_registry,
+        &mut ctx.accounts.registry_page,
+        ctx.bumps.registry_page
+    )?;
+    ctx.accounts.round.set_inner(Raffle {
+        owner: series.owner,
+        raffle_id: series.next_round_id(),
+        ticket_price: series.ticket_price,
+        max_entries: series.max_entries,
+        entries: 0,
+        status: RaffleStatus::Open,
+        prize_tiers: series.prize_tiers.clone(),
+        winners: Vec::new(),
+        created_at: now,
+        bump: ctx.bumps.round,
+        randomness_source: RandomnessSource::SlotHash,
+        entropy: [0; 32],
+        sold_out_at: None,
+        ticket_mint,
+        end_time: series.round_duration.map(|duration| now + duration),
+        creator_fee_bps: series.creator_fee_bps,
+        protocol_fee_bps,
+        max_tickets_per_wallet: series.max_tickets_per_wallet,
+        allowlist_root: None,
+        prize_mint: series.prize_mint,
+        prize_amount: series.prize_amount,
+        refunded_entries: 0,
+        crank_reward_bps: series.crank_reward_bps,
+        crank_reward_paid: 0,
+        paused: false,
+        registry_page: Some(registry_page),
+        min_entries: 0,
+        pricing: PricingMode::Fixed,
+        ticket_sales: 0,
+        open_accounts: 0,
+        last_ticket_slot: 0,
+        proceeds_withdrawn: false,
+    });
+
+    if let Some(series_prize_mint) = series.prize_mint {
+        let (Some(mint), Some(from), Some(vault), Some(token_program)) = (
+            &ctx.accounts.prize_mint,
+            &ctx.accounts.owner_prize_account,
+            &ctx.accounts.prize_vault,
+            &ctx.accounts.prize_token_program,
+        ) else {
+            return err!(RaffleError::PrizeAccountsRequired);
+        };
+        require_keys_eq!(mint.key(), series_prize_mint, RaffleError::InvalidPrizeMint);
+        check_supported_mint(mint)?;
+
+        msg!("Escrowing {} prize tokens", series.prize_amount);
+        transfer_checked(
+            CpiContext::new(
+                token_program.to_account_info(),
+                TransferChecked {
+                    from: from.to_account_info(),
+                    mint: mint.to_account_info(),
+                    to: vault.to_account_info(),
+                    authority: ctx.accounts.owner.to_account_info(),
+                }
+            ),
+            series.prize_amount,
+            mint.decimals
+        )?;
+    } else {
+        require!(ctx.accounts.prize_vault.is_none(), RaffleError::PrizeAccountsRequired);
+    }
+
+    let mut rolled_prize_amount = 0;
+    if roll_prize {
+        let previous = ctx.accounts.previous_round.as_mut()
+            .ok_or(RaffleError::PreviousRoundRequired)?;
+        require!(
+            previous.status.is_refundable() && previous.prize_mint.is_some() && previous.prize_amount > 0,
+            RaffleError::NoPrizeToRoll
+        );
+        require!(previous.prize_mint == series.prize_mint, RaffleError::InvalidPrizeMint);
+
+        let previous_vault = Pubkey::find_program_address(
+            &[b"prize_vault", previous.key().as_ref()],
+            &crate::ID
+        ).0;
+        require!(
+            ctx.accounts.previous_prize_vault.as_ref()
+                .is_some_and(|vault| vault.key() == previous_vault),
+            RaffleError::PrizeAccountsRequired
+        );
+
+        rolled_prize_amount = previous.prize_amount;
+        release_prize(
+            previous,
+            &round_key,
+            &ctx.accounts.prize_mint,
+            &ctx.accounts.prize_vault,
+            &ctx.accounts.previous_prize_vault,
+            &ctx.accounts.prize_token_program,
+            &ctx.accounts.owner.to_account_info()
+        )?;
+
+        let round = &mut ctx.accounts.round;
+        round.prize_amount = round.prize_amount.checked_add(rolled_prize_amount)
+            .ok_or(RaffleError::MathOverflow)?;
+        msg!("Rolled {} prize tokens over from the previous round", rolled_prize_amount);
+    }
+
+    let series = &mut ctx.accounts.series;
+    series.rounds += 1;
+
+    let round = &ctx.accounts.round;
+    msg!("Round {} of series {} started", series.rounds, series.series_id);
+    emit!(RaffleCreated {
+        raffle: round_key,
+        owner: round.owner,
+        raffle_id: round.raffle_id.clone(),
+        ticket_price: round.ticket_price,
+        max_entries: round.max_entries,
+        ticket_mint: round.ticket_mint,
+        prize_mint: round.prize_mint,
+        prize_amount: round.prize_amount,
+        end_time: round.end_time,
+        timestamp: now,
+    });
+    emit!(RoundStarted {
+        series: series.key(),
+        round: series.rounds,
+        raffle: round_key,
+        rolled_prize_amount,
+        timestamp: now,
+    });
+
+    Ok(())
+}
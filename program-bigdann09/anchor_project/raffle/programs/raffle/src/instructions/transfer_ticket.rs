@@ -0,0 +1,76 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    state::{Config, Listing, Raffle, Ticket},
+    events::TicketTransferred,
+    utils::{close_program_account, load_if_initialized}
+};
+
+#[derive(Accounts)]
+#[instruction(raffle_id: String)]
+pub struct TransferTicket<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"raffle", raffle.owner.as_ref(), raffle_id.as_ref()],
+        bump = raffle.bump
+    )]
+    pub raffle: Account<'info, Raffle>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        has_one = owner,
+        has_one = raffle,
+        seeds = [b"ticket", raffle.key().as_ref(), &ticket.ticket_id.to_le_bytes()],
+        bump = ticket.bump
+    )]
+    pub ticket: Account<'info, Ticket>,
+    /// CHECK: listing of the ticket, only read if it exists
+    #[account(
+        mut,
+        seeds = [b"listing", ticket.key().as_ref()],
+        bump
+    )]
+    pub listing: UncheckedAccount<'info>
+}
+
+/// Hands a ticket to `new_owner` before the draw. Prizes and refunds follow
+/// the ticket, so they are paid to whoever holds it at that point. A listing
+/// of the ticket is closed, so the new holder can list it again.
+pub fn transfer_ticket_handler(
+    ctx: Context<TransferTicket>,
+    _raffle_id: String,
+    new_owner: Pubkey,
+) -> Result<()> {
+    let clock: Clock = Clock::get()?;
+    ctx.accounts.raffle.check_transferable(&ctx.accounts.config, clock.unix_timestamp)?;
+    ctx.accounts.raffle.last_ticket_slot = clock.slot;
+
+    let listing = ctx.accounts.listing.to_account_info();
+    if let Some(Listing { seller, .. }) = load_if_initialized(&listing)? {
+        if seller == ctx.accounts.owner.key() {
+            close_program_account(&listing, &ctx.accounts.owner.to_account_info())?;
+        }
+    }
+
+    let ticket: &mut Account<Ticket> = &mut ctx.accounts.ticket;
+    let from = ticket.owner;
+    ticket.owner = new_owner;
+    msg!("Ticket {} transferred to {}", ticket.ticket_id, new_owner);
+
+    emit!(TicketTransferred {
+        raffle: ticket.raffle,
+        ticket_id: ticket.ticket_id,
+        from,
+        to: new_owner,
+        price: 0,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
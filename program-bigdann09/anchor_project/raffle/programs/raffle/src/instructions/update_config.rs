@@ -0,0 +1,42 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    state::Config,
+    error::RaffleError,
+    events::ConfigUpdated,
+    MAX_FEE_BPS
+};
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    pub admin: Signer<'info>,
+    #[account(
+        mut,
+        has_one = admin,
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>
+}
+
+pub fn update_config_handler(
+    ctx: Context<UpdateConfig>,
+    admin: Pubkey,
+    treasury: Pubkey,
+    protocol_fee_bps: u16,
+) -> Result<()> {
+    require!(protocol_fee_bps <= MAX_FEE_BPS, RaffleError::InvalidFee);
+
+    let config: &mut Account<Config> = &mut ctx.accounts.config;
+    config.admin = admin;
+    config.treasury = treasury;
+    config.protocol_fee_bps = protocol_fee_bps;
+
+    emit!(ConfigUpdated {
+        admin,
+        treasury,
+        protocol_fee_bps,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,48 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    state::{Raffle, RaffleMetadata, RaffleMetadataArgs},
+    error::RaffleError
+};
+
+#[derive(Accounts)]
+#[instruction(raffle_id: String)]
+pub struct UpdateMetadata<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        has_one = owner,
+        seeds = [b"raffle", owner.key().as_ref(), raffle_id.as_ref()],
+        bump = raffle.bump
+    )]
+    pub raffle: Account<'info, Raffle>,
+    // created here for raffles that were created without metadata
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = 8 + RaffleMetadata::INIT_SPACE,
+        seeds = [b"metadata", raffle.key().as_ref()],
+        bump,
+    )]
+    pub metadata: Account<'info, RaffleMetadata>,
+    pub system_program: Program<'info, System>
+}
+
+/// Replaces the display metadata of a raffle. Buyers see the metadata when
+/// they enter, so it is frozen once the first ticket is sold.
+pub fn update_metadata_handler(
+    ctx: Context<UpdateMetadata>,
+    _raffle_id: String,
+    args: RaffleMetadataArgs,
+) -> Result<()> {
+    let raffle = &ctx.accounts.raffle;
+    require!(raffle.entries == 0, RaffleError::MetadataLocked);
+
+    let metadata: &mut Account<RaffleMetadata> = &mut ctx.accounts.metadata;
+    metadata.raffle = raffle.key();
+    metadata.bump = ctx.bumps.metadata;
+    metadata.apply(args)?;
+    msg!("Metadata of raffle {} updated", raffle.raffle_id);
+
+    Ok(())
+}
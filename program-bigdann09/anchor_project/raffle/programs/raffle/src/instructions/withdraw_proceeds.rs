@@ -0,0 +1,105 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::{
+    state::{Config, Raffle, RaffleStatus},
+    error::RaffleError,
+    events::ProceedsWithdrawn,
+    utils::pay_out
+};
+
+#[derive(Accounts)]
+#[instruction(raffle_id: String)]
+pub struct WithdrawProceeds<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        has_one = owner,
+        seeds = [b"raffle", owner.key().as_ref(), raffle_id.as_ref()],
+        bump = raffle.bump
+    )]
+    pub raffle: Account<'info, Raffle>,
+    #[account(
+        has_one = treasury,
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    /// CHECK: protocol treasury receiving the protocol fee, checked by `has_one` on the config
+    #[account(mut)]
+    pub treasury: UncheckedAccount<'info>,
+    // token accounts below are only passed for raffles priced in SPL tokens
+    pub ticket_mint: Option<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        token::mint = ticket_mint,
+        token::token_program = token_program,
+    )]
+    pub owner_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        token::mint = ticket_mint,
+        token::token_program = token_program,
+    )]
+    pub treasury_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = [b"escrow", raffle.key().as_ref()],
+        bump,
+    )]
+    pub escrow: Option<InterfaceAccount<'info, TokenAccount>>,
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+}
+
+/// Pays the ticket proceeds of a drawn raffle with an escrowed prize to its
+/// creator and the protocol, without waiting for the winner to claim. The
+/// winner's later claim then only releases the prize asset.
+pub fn withdraw_proceeds_handler(
+    ctx: Context<WithdrawProceeds>,
+    _raffle_id: String,
+) -> Result<()> {
+    let raffle: &mut Account<Raffle> = &mut ctx.accounts.raffle;
+
+    require!(raffle.prize_mint.is_some(), RaffleError::NoProceedsToWithdraw);
+    match raffle.status {
+        RaffleStatus::Drawn => {},
+        RaffleStatus::Claimed => return err!(RaffleError::NoProceedsToWithdraw),
+        RaffleStatus::Cancelled | RaffleStatus::Expired => return err!(RaffleError::RaffleNotActive),
+        RaffleStatus::Open | RaffleStatus::Drawing => return err!(RaffleError::WinnerNotChosen),
+    }
+    require!(!raffle.proceeds_withdrawn, RaffleError::NoProceedsToWithdraw);
+
+    let (_, creator_amount, protocol_amount) = raffle.split_prize(0)?;
+
+    pay_out(
+        raffle,
+        &ctx.accounts.owner.to_account_info(),
+        &ctx.accounts.ticket_mint,
+        &ctx.accounts.owner_token_account,
+        &ctx.accounts.escrow,
+        &ctx.accounts.token_program,
+        creator_amount
+    )?;
+    pay_out(
+        raffle,
+        &ctx.accounts.treasury.to_account_info(),
+        &ctx.accounts.ticket_mint,
+        &ctx.accounts.treasury_token_account,
+        &ctx.accounts.escrow,
+        &ctx.accounts.token_program,
+        protocol_amount
+    )?;
+    raffle.proceeds_withdrawn = true;
+
+    emit!(ProceedsWithdrawn {
+        raffle: raffle.key(),
+        creator: raffle.owner,
+        creator_amount,
+        treasury: ctx.accounts.treasury.key(),
+        protocol_amount,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
@@ -1,7 +1,22 @@
+#![allow(unexpected_cfgs)]
+// only for the IDL instructions `#[program]` generates at the crate root, which
+// still call `AccountInfo::realloc`; every module below warns on deprecations
+#![allow(deprecated)]
+
+#[warn(deprecated)]
 pub mod constants;
+#[warn(deprecated)]
 pub mod error;
+#[warn(deprecated)]
+pub mod events;
+#[warn(deprecated)]
 pub mod instructions;
+#[warn(deprecated)]
+pub mod merkle;
+#[warn(deprecated)]
 pub mod state;
+#[warn(deprecated)]
+pub mod utils;
 
 use anchor_lang::prelude::*;
 
@@ -12,32 +27,45 @@ pub use state::*;
 declare_id!("649vJ9q8JBKY7pvc5Uspwf6diESX4qKKjVQ1DTCubNQX");
 
 #[program]
+#[warn(deprecated)]
 pub mod raffle {
     use super::*;
 
     pub fn create_raffle(
         ctx: Context<CreateRaffle>,
         raffle_id: String,
-        ticket_price: u64,
-        max_entries: u32,
+        args: CreateRaffleArgs,
     ) -> Result<()> {
         create_raffle_handler(
             ctx,
             raffle_id,
-            ticket_price,
-            max_entries
+            args
         )
     }
 
     pub fn buy_ticket(
         ctx: Context<BuyTicket>,
         raffle_id: String,
-        ticket_id: u32
+        proof: Vec<[u8; 32]>
     ) -> Result<()> {
         buy_ticket_handler(
             ctx,
             raffle_id,
-            ticket_id
+            proof
+        )
+    }
+
+    pub fn buy_tickets(
+        ctx: Context<BuyTickets>,
+        raffle_id: String,
+        quantity: u32,
+        proof: Vec<[u8; 32]>
+    ) -> Result<()> {
+        buy_tickets_handler(
+            ctx,
+            raffle_id,
+            quantity,
+            proof
         )
     }
 
@@ -60,4 +88,292 @@ pub mod raffle {
             raffle_id
         )
     }
+
+    pub fn reveal_winner(
+        ctx: Context<RevealWinner>,
+        raffle_id: String,
+        secret: [u8; 32]
+    ) -> Result<()> {
+        reveal_winner_handler(
+            ctx,
+            raffle_id,
+            secret
+        )
+    }
+
+    pub fn expire_reveal(
+        ctx: Context<ExpireReveal>,
+        raffle_id: String
+    ) -> Result<()> {
+        expire_reveal_handler(
+            ctx,
+            raffle_id
+        )
+    }
+
+    pub fn consume_randomness(
+        ctx: Context<ConsumeRandomness>,
+        raffle_id: String
+    ) -> Result<()> {
+        consume_randomness_handler(
+            ctx,
+            raffle_id
+        )
+    }
+
+    pub fn cancel_raffle(
+        ctx: Context<CancelRaffle>,
+        raffle_id: String
+    ) -> Result<()> {
+        cancel_raffle_handler(
+            ctx,
+            raffle_id
+        )
+    }
+
+    pub fn refund_ticket(
+        ctx: Context<RefundTicket>,
+        raffle_id: String
+    ) -> Result<()> {
+        refund_ticket_handler(
+            ctx,
+            raffle_id
+        )
+    }
+
+    pub fn reclaim_prize(
+        ctx: Context<ReclaimPrize>,
+        raffle_id: String
+    ) -> Result<()> {
+        reclaim_prize_handler(
+            ctx,
+            raffle_id
+        )
+    }
+
+    pub fn close_ticket(
+        ctx: Context<CloseTicket>,
+        raffle_id: String
+    ) -> Result<()> {
+        close_ticket_handler(
+            ctx,
+            raffle_id
+        )
+    }
+
+    pub fn close_raffle(
+        ctx: Context<CloseRaffle>,
+        raffle_id: String
+    ) -> Result<()> {
+        close_raffle_handler(
+            ctx,
+            raffle_id
+        )
+    }
+
+    pub fn crank_refunds<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CrankRefunds<'info>>,
+        raffle_id: String
+    ) -> Result<()> {
+        crank_refunds_handler(
+            ctx,
+            raffle_id
+        )
+    }
+
+    pub fn expire_raffle(
+        ctx: Context<ExpireRaffle>,
+        raffle_id: String
+    ) -> Result<()> {
+        expire_raffle_handler(
+            ctx,
+            raffle_id
+        )
+    }
+
+    pub fn migrate_raffle(
+        ctx: Context<MigrateRaffle>,
+        raffle_id: String
+    ) -> Result<()> {
+        migrate_raffle_handler(
+            ctx,
+            raffle_id
+        )
+    }
+
+    pub fn initialize_config(
+        ctx: Context<InitializeConfig>,
+        treasury: Pubkey,
+        protocol_fee_bps: u16
+    ) -> Result<()> {
+        initialize_config_handler(
+            ctx,
+            treasury,
+            protocol_fee_bps
+        )
+    }
+
+    pub fn update_config(
+        ctx: Context<UpdateConfig>,
+        admin: Pubkey,
+        treasury: Pubkey,
+        protocol_fee_bps: u16
+    ) -> Result<()> {
+        update_config_handler(
+            ctx,
+            admin,
+            treasury,
+            protocol_fee_bps
+        )
+    }
+
+    pub fn create_series(
+        ctx: Context<CreateSeries>,
+        series_id: String,
+        args: CreateSeriesArgs
+    ) -> Result<()> {
+        create_series_handler(
+            ctx,
+            series_id,
+            args
+        )
+    }
+
+    pub fn start_next_round(
+        ctx: Context<StartNextRound>,
+        series_id: String,
+        roll_prize: bool
+    ) -> Result<()> {
+        start_next_round_handler(
+            ctx,
+            series_id,
+            roll_prize
+        )
+    }
+
+    pub fn set_protocol_paused(
+        ctx: Context<SetProtocolPaused>,
+        paused: bool
+    ) -> Result<()> {
+        set_protocol_paused_handler(
+            ctx,
+            paused
+        )
+    }
+
+    pub fn set_raffle_paused(
+        ctx: Context<SetRafflePaused>,
+        raffle_id: String,
+        paused: bool
+    ) -> Result<()> {
+        set_raffle_paused_handler(
+            ctx,
+            raffle_id,
+            paused
+        )
+    }
+
+    pub fn transfer_ticket(
+        ctx: Context<TransferTicket>,
+        raffle_id: String,
+        new_owner: Pubkey
+    ) -> Result<()> {
+        transfer_ticket_handler(
+            ctx,
+            raffle_id,
+            new_owner
+        )
+    }
+
+    pub fn list_ticket(
+        ctx: Context<ListTicket>,
+        raffle_id: String,
+        price: u64
+    ) -> Result<()> {
+        list_ticket_handler(
+            ctx,
+            raffle_id,
+            price
+        )
+    }
+
+    pub fn cancel_listing(ctx: Context<CancelListing>) -> Result<()> {
+        cancel_listing_handler(ctx)
+    }
+
+    pub fn buy_listed_ticket(
+        ctx: Context<BuyListedTicket>,
+        raffle_id: String,
+        price: u64
+    ) -> Result<()> {
+        buy_listed_ticket_handler(
+            ctx,
+            raffle_id,
+            price
+        )
+    }
+
+    pub fn update_metadata(
+        ctx: Context<UpdateMetadata>,
+        raffle_id: String,
+        args: RaffleMetadataArgs
+    ) -> Result<()> {
+        update_metadata_handler(
+            ctx,
+            raffle_id,
+            args
+        )
+    }
+
+    pub fn close_entry(
+        ctx: Context<CloseEntry>,
+        raffle_id: String
+    ) -> Result<()> {
+        close_entry_handler(
+            ctx,
+            raffle_id
+        )
+    }
+
+    pub fn close_participant(
+        ctx: Context<CloseParticipant>,
+        raffle_id: String
+    ) -> Result<()> {
+        close_participant_handler(
+            ctx,
+            raffle_id
+        )
+    }
+
+    pub fn migrate_ticket(
+        ctx: Context<MigrateTicket>,
+        raffle_id: String,
+        ticket_id: u32
+    ) -> Result<()> {
+        migrate_ticket_handler(
+            ctx,
+            raffle_id,
+            ticket_id
+        )
+    }
+
+    pub fn withdraw_proceeds(
+        ctx: Context<WithdrawProceeds>,
+        raffle_id: String
+    ) -> Result<()> {
+        withdraw_proceeds_handler(
+            ctx,
+            raffle_id
+        )
+    }
+
+    pub fn crank_close_accounts<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CrankCloseAccounts<'info>>,
+        raffle_id: String
+    ) -> Result<()> {
+        crank_close_accounts_handler(
+            ctx,
+            raffle_id
+        )
+    }
 }
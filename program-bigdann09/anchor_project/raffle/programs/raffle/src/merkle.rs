@@ -0,0 +1,137 @@
+//! Merkle allowlists for gated raffles. Leaves are the hashes of the allowed
+//! wallets and every pair of nodes is hashed in sorted order, so proofs are a
+//! plain list of sibling hashes without position bits.
+
+use anchor_lang::{
+    prelude::*,
+    solana_program::hash::hash
+};
+
+pub fn leaf(wallet: &Pubkey) -> [u8; 32] {
+    hash(wallet.as_ref()).to_bytes()
+}
+
+pub fn hash_pair(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    let (first, second) = if a <= b { (a, b) } else { (b, a) };
+
+    hash(&[&first[..], &second[..]].concat()).to_bytes()
+}
+
+/// Returns true when `proof` links `wallet` to `root`.
+pub fn verify(root: &[u8; 32], wallet: &Pubkey, proof: &[[u8; 32]]) -> bool {
+    let computed = proof
+        .iter()
+        .fold(leaf(wallet), |node, sibling| hash_pair(&node, sibling));
+
+    &computed == root
+}
+
+/// Off-chain builder for allowlist roots and the proofs buyers pass to
+/// `buy_ticket`. A node without a sibling is carried up to the next layer
+/// unchanged.
+#[cfg(not(target_os = "solana"))]
+pub struct MerkleTree {
+    layers: Vec<Vec<[u8; 32]>>,
+}
+
+#[cfg(not(target_os = "solana"))]
+impl MerkleTree {
+    pub fn new(wallets: &[Pubkey]) -> Self {
+        let mut layers = vec![wallets.iter().map(leaf).collect::<Vec<_>>()];
+
+        while layers.last().is_some_and(|layer| layer.len() > 1) {
+            let next = layers
+                .last()
+                .unwrap()
+                .chunks(2)
+                .map(|pair| match pair {
+                    [a, b] => hash_pair(a, b),
+                    [a] => *a,
+                    _ => unreachable!(),
+                })
+                .collect();
+            layers.push(next);
+        }
+
+        Self { layers }
+    }
+
+    /// Root to store on the raffle, all zeroes for an empty allowlist.
+    pub fn root(&self) -> [u8; 32] {
+        self.layers
+            .last()
+            .and_then(|layer| layer.first())
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Proof for `wallet`, or `None` if it is not on the allowlist.
+    pub fn proof(&self, wallet: &Pubkey) -> Option<Vec<[u8; 32]>> {
+        let target = leaf(wallet);
+        let mut index = self.layers[0].iter().position(|node| *node == target)?;
+        let mut proof = Vec::new();
+
+        for layer in &self.layers[..self.layers.len() - 1] {
+            if let Some(sibling) = layer.get(index ^ 1) {
+                proof.push(*sibling);
+            }
+            index /= 2;
+        }
+
+        Some(proof)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wallets(count: usize) -> Vec<Pubkey> {
+        (0..count).map(|_| Pubkey::new_unique()).collect()
+    }
+
+    #[test]
+    fn proofs_verify_for_every_allowed_wallet() {
+        for count in 1..=9 {
+            let wallets = wallets(count);
+            let tree = MerkleTree::new(&wallets);
+
+            for wallet in &wallets {
+                let proof = tree.proof(wallet).unwrap();
+                assert!(verify(&tree.root(), wallet, &proof));
+            }
+        }
+    }
+
+    #[test]
+    fn single_wallet_root_is_its_leaf() {
+        let wallets = wallets(1);
+        let tree = MerkleTree::new(&wallets);
+
+        assert_eq!(tree.root(), leaf(&wallets[0]));
+        assert!(tree.proof(&wallets[0]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn rejects_wallets_not_on_the_allowlist() {
+        let wallets = wallets(5);
+        let tree = MerkleTree::new(&wallets);
+        let outsider = Pubkey::new_unique();
+
+        assert!(tree.proof(&outsider).is_none());
+        // an allowed wallet's proof does not work for someone else
+        let proof = tree.proof(&wallets[0]).unwrap();
+        assert!(!verify(&tree.root(), &outsider, &proof));
+    }
+
+    #[test]
+    fn rejects_proofs_against_another_root() {
+        let wallet = Pubkey::new_unique();
+        let tree = MerkleTree::new(&[wallet, Pubkey::new_unique()]);
+        let other = MerkleTree::new(&wallets(4));
+
+        let proof = tree.proof(&wallet).unwrap();
+        assert!(verify(&tree.root(), &wallet, &proof));
+        assert!(!verify(&other.root(), &wallet, &proof));
+    }
+}
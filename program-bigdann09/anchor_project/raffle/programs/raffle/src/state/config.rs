@@ -0,0 +1,22 @@
+use anchor_lang::prelude::*;
+
+use crate::error::RaffleError;
+
+#[account]
+#[derive(InitSpace)]
+pub struct Config {
+    pub admin: Pubkey,
+    pub treasury: Pubkey,
+    pub protocol_fee_bps: u16,
+    pub bump: u8,
+    // halts every instruction that moves a raffle forward, claims and refunds stay open
+    pub paused: bool,
+}
+
+impl Config {
+    pub fn check_not_paused(&self) -> Result<()> {
+        require!(!self.paused, RaffleError::ProtocolPaused);
+
+        Ok(())
+    }
+}
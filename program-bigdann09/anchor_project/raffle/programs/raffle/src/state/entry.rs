@@ -0,0 +1,50 @@
+use anchor_lang::prelude::*;
+
+use crate::MAX_ENTRY_RANGES;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
+pub struct TicketRange {
+    pub first_ticket: u32,
+    pub quantity: u32,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Entry {
+    pub raffle: Pubkey,
+    pub owner: Pubkey,
+    // contiguous ticket ids bought in bulk, adjacent purchases are merged
+    #[max_len(MAX_ENTRY_RANGES)]
+    pub ranges: Vec<TicketRange>,
+    pub bump: u8,
+    // total paid for the entry's tickets, refunded if the raffle is called off
+    pub amount_paid: u64,
+}
+
+impl Entry {
+    pub fn add_range(&mut self, first_ticket: u32, quantity: u32) -> bool {
+        if let Some(last) = self.ranges.last_mut() {
+            if last.first_ticket + last.quantity == first_ticket {
+                last.quantity += quantity;
+                return true;
+            }
+        }
+
+        if self.ranges.len() >= MAX_ENTRY_RANGES {
+            return false;
+        }
+        self.ranges.push(TicketRange { first_ticket, quantity });
+
+        true
+    }
+
+    pub fn contains(&self, ticket_id: u32) -> bool {
+        self.ranges.iter().any(|range| {
+            ticket_id >= range.first_ticket && ticket_id - range.first_ticket < range.quantity
+        })
+    }
+
+    pub fn ticket_count(&self) -> u32 {
+        self.ranges.iter().map(|range| range.quantity).sum()
+    }
+}
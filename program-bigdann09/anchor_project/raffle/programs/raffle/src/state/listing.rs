@@ -0,0 +1,12 @@
+use anchor_lang::prelude::*;
+
+#[account]
+#[derive(InitSpace)]
+pub struct Listing {
+    pub raffle: Pubkey,
+    pub ticket: Pubkey,
+    pub seller: Pubkey,
+    // asking price in lamports, also for raffles priced in SPL tokens
+    pub price: u64,
+    pub bump: u8,
+}
@@ -0,0 +1,74 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::RaffleError,
+    MAX_DESCRIPTION_LEN,
+    MAX_TAGS,
+    MAX_TAG_LEN,
+    MAX_TITLE_LEN,
+    MAX_URI_LEN
+};
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub enum RaffleCategory {
+    Collectibles,
+    Gaming,
+    Art,
+    Tickets,
+    Charity,
+    Other,
+}
+
+/// Display metadata of a raffle, kept apart from `Raffle` so the UI strings
+/// do not grow the account every instruction loads.
+#[account]
+#[derive(InitSpace)]
+pub struct RaffleMetadata {
+    pub raffle: Pubkey,
+    #[max_len(MAX_TITLE_LEN)]
+    pub title: String,
+    #[max_len(MAX_DESCRIPTION_LEN)]
+    pub description: String,
+    // image of the raffle, usually an arweave or ipfs link
+    #[max_len(MAX_URI_LEN)]
+    pub uri: String,
+    pub category: RaffleCategory,
+    #[max_len(MAX_TAGS, MAX_TAG_LEN)]
+    pub tags: Vec<String>,
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct RaffleMetadataArgs {
+    pub title: String,
+    pub description: String,
+    pub uri: String,
+    pub category: RaffleCategory,
+    pub tags: Vec<String>,
+}
+
+impl RaffleMetadataArgs {
+    pub fn validate(&self) -> Result<()> {
+        require!(self.title.len() <= MAX_TITLE_LEN, RaffleError::TitleTooLong);
+        require!(self.description.len() <= MAX_DESCRIPTION_LEN, RaffleError::DescriptionTooLong);
+        require!(self.uri.len() <= MAX_URI_LEN, RaffleError::UriTooLong);
+        require!(self.tags.len() <= MAX_TAGS, RaffleError::TooManyTags);
+        require!(self.tags.iter().all(|tag| tag.len() <= MAX_TAG_LEN), RaffleError::TagTooLong);
+
+        Ok(())
+    }
+}
+
+impl RaffleMetadata {
+    pub fn apply(&mut self, args: RaffleMetadataArgs) -> Result<()> {
+        args.validate()?;
+
+        self.title = args.title;
+        self.description = args.description;
+        self.uri = args.uri;
+        self.category = args.category;
+        self.tags = args.tags;
+
+        Ok(())
+    }
+}
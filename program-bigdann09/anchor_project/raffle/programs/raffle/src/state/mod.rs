@@ -2,4 +2,27 @@ pub mod raffle;
 pub use raffle::*;
 
 pub mod ticket;
-pub use ticket::*;
\ No newline at end of file
+pub use ticket::*;
+
+pub mod config;
+pub use config::*;
+
+pub mod entry;
+pub use entry::*;
+
+pub mod participant;
+pub use participant::*;
+
+pub mod series;
+pub use series::*;
+pub mod listing;
+pub use listing::*;
+
+pub mod registry;
+pub use registry::*;
+
+pub mod metadata;
+pub use metadata::*;
+
+pub mod pricing;
+pub use pricing::*;
@@ -0,0 +1,28 @@
+use anchor_lang::prelude::*;
+
+use crate::error::RaffleError;
+
+#[account]
+#[derive(InitSpace)]
+pub struct Participant {
+    pub raffle: Pubkey,
+    pub owner: Pubkey,
+    // tickets bought by `owner` across single and bulk purchases
+    pub tickets_bought: u32,
+    pub bump: u8,
+}
+
+impl Participant {
+    /// Counts `quantity` more tickets for this wallet, rejecting purchases
+    /// that would go over the raffle's per-wallet cap.
+    pub fn add_tickets(&mut self, quantity: u32, cap: Option<u32>) -> Result<()> {
+        let tickets_bought = self.tickets_bought.checked_add(quantity)
+            .ok_or(RaffleError::MathOverflow)?;
+        if let Some(cap) = cap {
+            require!(tickets_bought <= cap, RaffleError::WalletCapReached);
+        }
+        self.tickets_bought = tickets_bought;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,115 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::RaffleError, MAX_FEE_BPS};
+
+/// How the price of a ticket moves away from the raffle's base `ticket_price`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub enum PricingMode {
+    // every ticket costs the base price
+    Fixed,
+    // each ticket sold raises the price by `increment`
+    Linear { increment: u64 },
+    // every `step` tickets sold raise the price by `increment`
+    Stepwise { step: u32, increment: u64 },
+    // tickets bought within `window` seconds of creation are `discount_bps` cheaper
+    EarlyBird { window: i64, discount_bps: u16 },
+}
+
+impl PricingMode {
+    pub fn validate(&self) -> Result<()> {
+        let valid = match *self {
+            PricingMode::Fixed | PricingMode::Linear { .. } => true,
+            PricingMode::Stepwise { step, .. } => step > 0,
+            PricingMode::EarlyBird { window, discount_bps } => {
+                window > 0 && discount_bps < MAX_FEE_BPS
+            }
+        };
+        require!(valid, RaffleError::InvalidPricingMode);
+
+        Ok(())
+    }
+
+    /// Total price of the next `quantity` tickets of a raffle that already
+    /// sold `sold` tickets at `base_price`.
+    pub fn price(
+        &self,
+        base_price: u64,
+        sold: u32,
+        quantity: u32,
+        created_at: i64,
+        now: i64,
+    ) -> Result<u64> {
+        let (base, sold, quantity) = (base_price as u128, sold as u128, quantity as u128);
+
+        let total = match *self {
+            PricingMode::Fixed => base * quantity,
+            PricingMode::Linear { increment } => {
+                // the increments form an arithmetic series from `sold` to `sold + quantity - 1`
+                let steps = sold * quantity + quantity * quantity.saturating_sub(1) / 2;
+                base * quantity + increment as u128 * steps
+            }
+            PricingMode::Stepwise { step, increment } => {
+                // increments paid by the first `n` tickets: `step` tickets per
+                // completed tier plus the tickets sold into the current one
+                let step = step as u128;
+                let increments = |n: u128| {
+                    let (tiers, rest) = (n / step, n % step);
+                    step * tiers * tiers.saturating_sub(1) / 2 + rest * tiers
+                };
+                base * quantity + increment as u128 * (increments(sold + quantity) - increments(sold))
+            }
+            PricingMode::EarlyBird { window, discount_bps } => {
+                let price = if now < created_at.saturating_add(window) {
+                    base - base * discount_bps as u128 / MAX_FEE_BPS as u128
+                } else {
+                    base
+                };
+                price * quantity
+            }
+        };
+
+        u64::try_from(total).map_err(|_| error!(RaffleError::MathOverflow))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn price(pricing: PricingMode, sold: u32, quantity: u32) -> u64 {
+        pricing.price(100, sold, quantity, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn single_ticket_steps_match_the_linear_curve() {
+        let stepwise = PricingMode::Stepwise { step: 1, increment: 7 };
+        let linear = PricingMode::Linear { increment: 7 };
+
+        for sold in 0..20 {
+            for quantity in 0..20 {
+                assert_eq!(price(stepwise, sold, quantity), price(linear, sold, quantity));
+            }
+        }
+    }
+
+    #[test]
+    fn stepwise_totals_add_up_ticket_by_ticket() {
+        for step in 1..6 {
+            let pricing = PricingMode::Stepwise { step, increment: 3 };
+            for sold in 0..15 {
+                let one_by_one: u64 = (sold..sold + 10).map(|next| price(pricing, next, 1)).sum();
+                assert_eq!(price(pricing, sold, 10), one_by_one);
+            }
+        }
+    }
+
+    #[test]
+    fn stepwise_prices_large_purchases_without_iterating() {
+        let pricing = PricingMode::Stepwise { step: 1_000, increment: 1 };
+
+        // tiers 0 to 4_294_966 are complete, the last 295 tickets sell in tier 4_294_967
+        let tiers: u64 = 4_294_967;
+        let expected = 100 * u32::MAX as u64 + 1_000 * tiers * (tiers - 1) / 2 + 295 * tiers;
+        assert_eq!(price(pricing, 0, u32::MAX), expected);
+    }
+}
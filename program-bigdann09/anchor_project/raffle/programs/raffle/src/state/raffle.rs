@@ -1,4 +1,79 @@
-use anchor_lang::prelude::*;
+use anchor_lang::{
+    prelude::*,
+    solana_program::hash::hash
+};
+
+use crate::{
+    state::{Config, Entry, PricingMode},
+    error::RaffleError,
+    merkle,
+    MAX_FEE_BPS,
+    MAX_WINNERS
+};
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub enum RaffleStatus {
+    // selling tickets
+    Open,
+    // sold out, waiting for the winner to be drawn
+    Drawing,
+    Drawn,
+    Claimed,
+    Cancelled,
+    // ended without a draw, tickets are refundable
+    Expired,
+}
+
+impl RaffleStatus {
+    pub fn can_transition_to(&self, next: RaffleStatus) -> bool {
+        use RaffleStatus::*;
+
+        matches!(
+            (self, next),
+            (Open, Drawing)
+                | (Open, Drawn)
+                | (Open, Cancelled)
+                | (Open, Expired)
+                | (Drawing, Drawn)
+                | (Drawing, Expired)
+                | (Drawn, Claimed)
+        )
+    }
+
+    pub fn is_refundable(&self) -> bool {
+        matches!(self, RaffleStatus::Cancelled | RaffleStatus::Expired)
+    }
+
+    /// True once winners have been drawn or the raffle ended without a draw.
+    pub fn is_finished(&self) -> bool {
+        !matches!(self, RaffleStatus::Open | RaffleStatus::Drawing)
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
+pub struct OracleConfig {
+    // program that must own the randomness account
+    pub program: Pubkey,
+    pub account: Pubkey,
+    // byte offsets of the 32 byte randomness and the u64 slot it was written in
+    pub randomness_offset: u32,
+    pub slot_offset: u32,
+    pub max_staleness: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
+pub enum RandomnessSource {
+    SlotHash,
+    // hash of the owner's secret, revealed in `reveal_winner`
+    CommitReveal { commitment: [u8; 32] },
+    Oracle(OracleConfig),
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
+pub struct Winner {
+    pub ticket_id: u32,
+    pub claimed: bool,
+}
 
 #[account]
 #[derive(InitSpace)]
@@ -9,9 +84,330 @@ pub struct Raffle {
     pub ticket_price: u64,
     pub max_entries: u32,
     pub entries: u32,
-    pub is_active: bool,
-    pub is_claimed: bool,
-    pub winner_ticket: Option<u32>,
+    pub status: RaffleStatus,
+    // share of the prize pool in basis points for each winner, best tier first
+    #[max_len(MAX_WINNERS)]
+    pub prize_tiers: Vec<u16>,
+    // one per prize tier, only set once the raffle is `Drawn`
+    #[max_len(MAX_WINNERS)]
+    pub winners: Vec<Winner>,
     pub created_at: i64,
     pub bump: u8,
-}
\ No newline at end of file
+    pub randomness_source: RandomnessSource,
+    // running hash of the entropy contributed by every ticket purchase
+    pub entropy: [u8; 32],
+    pub sold_out_at: Option<i64>,
+    // tickets are paid in this SPL token instead of lamports when set
+    pub ticket_mint: Option<Pubkey>,
+    // ticket sales close at this timestamp when set
+    pub end_time: Option<i64>,
+    // fee shares of the prize pool, the protocol fee is snapshotted from `Config`
+    pub creator_fee_bps: u16,
+    pub protocol_fee_bps: u16,
+    // tickets a single wallet may buy when set
+    pub max_tickets_per_wallet: Option<u32>,
+    // only wallets proven against this merkle root may buy tickets when set
+    pub allowlist_root: Option<[u8; 32]>,
+    // asset escrowed in the prize vault and won instead of the ticket pool when
+    // set, the ticket proceeds then go to the creator
+    pub prize_mint: Option<Pubkey>,
+    // amount still held in the prize vault, zeroed once the prize is released
+    pub prize_amount: u64,
+    // tickets refunded after a cancellation or expiry
+    pub refunded_entries: u32,
+    // share of the ticket pool paid to whoever draws the winners
+    pub crank_reward_bps: u16,
+    pub crank_reward_paid: u64,
+    // set by the owner to halt ticket sales and draws, claims and refunds stay open
+    pub paused: bool,
+    // page of the global registry listing the raffle, unset once it stops
+    // selling tickets or for migrated raffles that were never listed
+    pub registry_page: Option<u32>,
+    // tickets that must sell by the end time for the raffle to be drawn,
+    // below it the raffle expires and refunds its tickets
+    pub min_entries: u32,
+    // how the price moves away from `ticket_price` as tickets sell
+    pub pricing: PricingMode,
+    // amount actually paid for tickets, the prize pool is taken from it
+    pub ticket_sales: u64,
+    // ticket, entry and participant accounts not closed yet, the raffle can
+    // only be closed once none are left
+    pub open_accounts: u32,
+    // slot tickets were last bought or changed hands in, oracle randomness
+    // must be published after it
+    pub last_ticket_slot: u64,
+    // set once the creator withdrew the proceeds of an escrowed prize raffle
+    // ahead of the winner's claim
+    pub proceeds_withdrawn: bool,
+}
+
+impl Raffle {
+    pub fn commitment(&self) -> Option<[u8; 32]> {
+        match self.randomness_source {
+            RandomnessSource::CommitReveal { commitment } => Some(commitment),
+            _ => None,
+        }
+    }
+
+    pub fn check_allowlist(&self, buyer: &Pubkey, proof: &[[u8; 32]]) -> Result<()> {
+        if let Some(root) = self.allowlist_root {
+            require!(merkle::verify(&root, buyer, proof), RaffleError::NotAllowlisted);
+        }
+
+        Ok(())
+    }
+
+    /// Fails while either the protocol or this raffle is paused.
+    pub fn check_not_paused(&self, config: &Config) -> Result<()> {
+        config.check_not_paused()?;
+        require!(!self.paused, RaffleError::RafflePaused);
+
+        Ok(())
+    }
+
+    /// Tickets may change hands while sales are open, unless the raffle
+    /// restricts who may hold its tickets.
+    pub fn check_transferable(&self, config: &Config, now: i64) -> Result<()> {
+        self.check_not_paused(config)?;
+        require!(
+            self.status == RaffleStatus::Open && !self.has_ended(now),
+            RaffleError::RaffleNotActive
+        );
+        require!(
+            self.allowlist_root.is_none() && self.max_tickets_per_wallet.is_none(),
+            RaffleError::TransferRestricted
+        );
+
+        Ok(())
+    }
+
+    pub fn has_ended(&self, now: i64) -> bool {
+        self.end_time.is_some_and(|end_time| now >= end_time)
+    }
+
+    /// Whether enough tickets sold for the raffle to be drawn at its end time.
+    pub fn meets_min_entries(&self) -> bool {
+        self.entries > 0 && self.entries >= self.min_entries
+    }
+
+    /// Returns the timestamp from which a winner may be drawn: when the raffle
+    /// sold out, or its end time if it closed with at least `min_entries`
+    /// tickets sold.
+    pub fn drawable_since(&self, now: i64) -> Option<i64> {
+        if self.sold_out_at.is_some() {
+            return self.sold_out_at;
+        }
+
+        match self.end_time {
+            Some(end_time) if now >= end_time && self.meets_min_entries() => Some(end_time),
+            _ => None,
+        }
+    }
+
+    pub fn transition(&mut self, next: RaffleStatus) -> Result<()> {
+        require!(
+            self.status.can_transition_to(next),
+            RaffleError::InvalidStatusTransition
+        );
+        self.status = next;
+
+        Ok(())
+    }
+
+    /// Checks that a winner may be drawn now and returns the timestamp from
+    /// which drawing became possible.
+    pub fn check_drawable(&self, now: i64) -> Result<i64> {
+        match self.status {
+            RaffleStatus::Open | RaffleStatus::Drawing => {},
+            RaffleStatus::Cancelled => return err!(RaffleError::RaffleCancelled),
+            RaffleStatus::Drawn | RaffleStatus::Claimed => return err!(RaffleError::WinnerChosen),
+            RaffleStatus::Expired => return err!(RaffleError::RaffleNotActive),
+        }
+
+        require!(
+            self.entries as usize >= self.prize_tiers.len(),
+            RaffleError::NotEnoughEntries
+        );
+
+        self.drawable_since(now).ok_or(error!(RaffleError::EntriesNotFull))
+    }
+
+    /// Draws one distinct winning ticket per prize tier and moves the raffle
+    /// to `Drawn`.
+    pub fn draw_winners(&mut self, randomness: &[u8; 32]) -> Result<()> {
+        self.transition(RaffleStatus::Drawn)?;
+
+        let mut winners: Vec<Winner> = Vec::with_capacity(self.prize_tiers.len());
+        let mut seed = *randomness;
+        let mut nonce: u32 = 0;
+        while winners.len() < self.prize_tiers.len() {
+            let ticket_id = self.draw_ticket(&seed);
+            if !winners.iter().any(|winner| winner.ticket_id == ticket_id) {
+                msg!("winner id: {}", ticket_id);
+                winners.push(Winner { ticket_id, claimed: false });
+            }
+
+            // drawn tickets are skipped, so the next draw rehashes the seed
+            nonce += 1;
+            seed = hash(&[&randomness[..], &nonce.to_le_bytes()].concat()).to_bytes();
+        }
+        self.winners = winners;
+
+        Ok(())
+    }
+
+    pub fn winning_ticket_ids(&self) -> Vec<u32> {
+        self.winners.iter().map(|winner| winner.ticket_id).collect()
+    }
+
+    /// Returns the prize tier won by `ticket_id`, if any.
+    pub fn winning_tier(&self, ticket_id: u32) -> Option<usize> {
+        self.winners.iter().position(|winner| winner.ticket_id == ticket_id)
+    }
+
+    /// Returns a prize tier won by one of the tickets of `entry`, preferring
+    /// tiers that have not been claimed yet.
+    pub fn winning_tier_in(&self, entry: &Entry) -> Option<usize> {
+        let won = |winner: &Winner| entry.contains(winner.ticket_id);
+
+        self.winners.iter().position(|winner| won(winner) && !winner.claimed)
+            .or_else(|| self.winners.iter().position(won))
+    }
+
+    /// Ticket proceeds left to pay out as prizes and fees.
+    pub fn prize_pool(&self) -> Result<u64> {
+        let pool = self.ticket_sales.checked_sub(self.crank_reward_paid)
+            .ok_or(RaffleError::MathOverflow)?;
+
+        Ok(pool)
+    }
+
+    /// Price of the next `quantity` tickets under the raffle's pricing mode.
+    pub fn price_of(&self, quantity: u32, now: i64) -> Result<u64> {
+        self.pricing.price(self.ticket_price, self.entries, quantity, self.created_at, now)
+    }
+
+    /// Reward owed to whoever draws the winners of this raffle.
+    pub fn crank_reward(&self) -> Result<u64> {
+        Ok(share(self.prize_pool()?, self.crank_reward_bps))
+    }
+
+    /// Splits the share of the prize pool won by `tier` into the winner,
+    /// creator and protocol amounts. Raffles with an escrowed prize pay the
+    /// whole pool, less the protocol fee, to the creator, unless it has been
+    /// withdrawn already.
+    pub fn split_prize(&self, tier: usize) -> Result<(u64, u64, u64)> {
+        let pool = self.prize_pool()?;
+
+        let tier_amount = share(pool, self.prize_tiers[tier]);
+        let protocol_amount = share(tier_amount, self.protocol_fee_bps);
+        if self.prize_mint.is_some() {
+            if self.proceeds_withdrawn {
+                return Ok((0, 0, 0));
+            }
+            let creator_amount = tier_amount.checked_sub(protocol_amount)
+                .ok_or(RaffleError::MathOverflow)?;
+            return Ok((0, creator_amount, protocol_amount));
+        }
+
+        let creator_amount = share(tier_amount, self.creator_fee_bps);
+        let winner_amount = tier_amount
+            .checked_sub(creator_amount)
+            .and_then(|amount| amount.checked_sub(protocol_amount))
+            .ok_or(RaffleError::MathOverflow)?;
+
+        Ok((winner_amount, creator_amount, protocol_amount))
+    }
+
+    /// Counts `quantity` more refunded tickets. Refunds pay back the amount
+    /// recorded on the ticket or entry, as prices may differ between tickets.
+    pub fn refund_entries(&mut self, quantity: u32) -> Result<()> {
+        self.refunded_entries = self.refunded_entries.checked_add(quantity)
+            .filter(|&refunded| refunded <= self.entries)
+            .ok_or(RaffleError::MathOverflow)?;
+
+        Ok(())
+    }
+
+    /// Returns true once every prize has been claimed or every ticket refunded,
+    /// and the escrowed prize asset has left the prize vault.
+    pub fn is_settled(&self) -> bool {
+        let payouts_done = match self.status {
+            RaffleStatus::Claimed => true,
+            RaffleStatus::Cancelled | RaffleStatus::Expired => self.refunded_entries == self.entries,
+            _ => false,
+        };
+
+        payouts_done && self.prize_amount == 0
+    }
+
+    /// Id of the next ticket sold, tickets are numbered from one in purchase order.
+    pub fn next_ticket_id(&self) -> u32 {
+        self.entries + 1
+    }
+
+    /// Counts a ticket, entry or participant account opened for the raffle.
+    pub fn account_opened(&mut self) -> Result<()> {
+        self.open_accounts = self.open_accounts.checked_add(1)
+            .ok_or(RaffleError::MathOverflow)?;
+
+        Ok(())
+    }
+
+    pub fn account_closed(&mut self) -> Result<()> {
+        self.open_accounts = self.open_accounts.checked_sub(1)
+            .ok_or(RaffleError::MathOverflow)?;
+
+        Ok(())
+    }
+
+    /// Records `quantity` tickets bought by `buyer` and returns the id of the
+    /// first one. Sold out raffles move on to `Drawing`.
+    pub fn record_entries(
+        &mut self,
+        buyer: &Pubkey,
+        quantity: u32,
+        amount: u64,
+        clock: &Clock,
+    ) -> Result<u32> {
+        let first_ticket = self.next_ticket_id();
+        self.entries = self.entries.checked_add(quantity)
+            .filter(|&entries| entries <= self.max_entries)
+            .ok_or(RaffleError::RaffleSoldOut)?;
+        self.ticket_sales = self.ticket_sales.checked_add(amount)
+            .ok_or(RaffleError::MathOverflow)?;
+        self.last_ticket_slot = clock.slot;
+
+        self.mix_entropy(&[
+            buyer.as_ref(),
+            &clock.slot.to_le_bytes(),
+            &clock.unix_timestamp.to_le_bytes()
+        ].concat());
+
+        if self.entries == self.max_entries {
+            self.sold_out_at = Some(clock.unix_timestamp);
+            self.transition(RaffleStatus::Drawing)?;
+        }
+
+        Ok(first_ticket)
+    }
+
+    /// Folds a new contribution into the accumulated entropy.
+    pub fn mix_entropy(&mut self, contribution: &[u8]) {
+        self.entropy = hash(&[&self.entropy[..], contribution].concat()).to_bytes();
+    }
+
+    /// Maps a 32 byte random value to a ticket id in `1..=entries`.
+    pub fn draw_ticket(&self, randomness: &[u8; 32]) -> u32 {
+        let random_seed = u64::from_le_bytes(
+            <[u8; 8]>::try_from(&randomness[..8]).unwrap()
+        );
+
+        ((random_seed % self.entries as u64) + 1) as u32
+    }
+}
+
+/// Returns `bps` basis points of `amount`, rounded down.
+fn share(amount: u64, bps: u16) -> u64 {
+    (amount as u128 * bps as u128 / MAX_FEE_BPS as u128) as u64
+}
@@ -0,0 +1,68 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::RaffleError, RAFFLES_PER_PAGE};
+
+/// Counts the raffles an owner created with `create_raffle`. Their ids are
+/// sequential, so every one of them can be derived from the counter. Series
+/// rounds are derived from their series instead and are not counted.
+#[account]
+#[derive(InitSpace)]
+pub struct RaffleRegistry {
+    pub owner: Pubkey,
+    pub raffles_created: u32,
+    pub bump: u8,
+}
+
+impl RaffleRegistry {
+    /// Id the owner's next raffle must be created with.
+    pub fn next_raffle_id(&self) -> String {
+        self.raffles_created.to_string()
+    }
+
+    /// Counts a raffle created with `raffle_id`, which must be the next id.
+    pub fn record_raffle(&mut self, raffle_id: &str) -> Result<()> {
+        require!(raffle_id == self.next_raffle_id(), RaffleError::InvalidRaffleId);
+        self.raffles_created = self.raffles_created.checked_add(1)
+            .ok_or(RaffleError::MathOverflow)?;
+
+        Ok(())
+    }
+}
+
+/// Points at the page new raffles are listed on. Pages fill up in order, a
+/// page stops taking raffles once it has been full.
+#[account]
+#[derive(InitSpace)]
+pub struct GlobalRegistry {
+    pub current_page: u32,
+    pub bump: u8,
+}
+
+/// One page of the global registry, listing raffles from creation until they
+/// stop selling tickets: once sold out, drawn, expired or cancelled.
+#[account]
+#[derive(InitSpace)]
+pub struct RegistryPage {
+    pub page: u32,
+    #[max_len(RAFFLES_PER_PAGE)]
+    pub raffles: Vec<Pubkey>,
+    pub bump: u8,
+}
+
+impl RegistryPage {
+    /// Lists `raffle` on the page and returns whether the page is now full.
+    pub fn add(&mut self, raffle: Pubkey) -> Result<bool> {
+        require!(self.raffles.len() < RAFFLES_PER_PAGE, RaffleError::RegistryPageFull);
+        self.raffles.push(raffle);
+
+        Ok(self.raffles.len() == RAFFLES_PER_PAGE)
+    }
+
+    pub fn remove(&mut self, raffle: &Pubkey) -> Result<()> {
+        let index = self.raffles.iter().position(|listed| listed == raffle)
+            .ok_or(RaffleError::RaffleNotListed)?;
+        self.raffles.swap_remove(index);
+
+        Ok(())
+    }
+}
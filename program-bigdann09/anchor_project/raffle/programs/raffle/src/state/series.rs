@@ -0,0 +1,40 @@
+use anchor_lang::prelude::*;
+
+use crate::{MAX_SERIES_ID_LEN, MAX_WINNERS};
+
+/// Template for recurring raffles. Every round is a regular `Raffle` of the
+/// series owner, whose raffle id is the series id followed by the round number.
+#[account]
+#[derive(InitSpace)]
+pub struct RaffleSeries {
+    pub owner: Pubkey,
+    #[max_len(MAX_SERIES_ID_LEN)]
+    pub series_id: String,
+    pub ticket_price: u64,
+    pub max_entries: u32,
+    // rounds end this many seconds after they start, or run until sold out
+    pub round_duration: Option<i64>,
+    pub ticket_mint: Option<Pubkey>,
+    // prize asset the owner escrows at the start of every round, if any
+    pub prize_mint: Option<Pubkey>,
+    pub prize_amount: u64,
+    pub creator_fee_bps: u16,
+    #[max_len(MAX_WINNERS)]
+    pub prize_tiers: Vec<u16>,
+    pub max_tickets_per_wallet: Option<u32>,
+    pub crank_reward_bps: u16,
+    // rounds started so far, rounds are numbered from one
+    pub rounds: u32,
+    pub bump: u8,
+}
+
+impl RaffleSeries {
+    /// Raffle id of round `round`, e.g. `weekly-3`.
+    pub fn round_id(&self, round: u32) -> String {
+        format!("{}-{}", self.series_id, round)
+    }
+
+    pub fn next_round_id(&self) -> String {
+        self.round_id(self.rounds + 1)
+    }
+}
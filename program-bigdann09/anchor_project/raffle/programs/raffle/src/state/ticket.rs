@@ -6,5 +6,7 @@ pub struct Ticket {
     pub raffle: Pubkey,
     pub ticket_id: u32,
     pub owner: Pubkey,
-    pub bump: u8
+    pub bump: u8,
+    // price the ticket was bought at, refunded if the raffle is called off
+    pub price_paid: u64,
 }
\ No newline at end of file
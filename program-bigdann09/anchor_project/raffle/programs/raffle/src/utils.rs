@@ -0,0 +1,303 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::{program::invoke, system_instruction::transfer},
+};
+use anchor_spl::{
+    token_2022::spl_token_2022::{
+        self,
+        extension::{transfer_fee::TransferFeeConfig, BaseStateWithExtensions, StateWithExtensions},
+    },
+    token_interface::{
+        close_account, transfer_checked, CloseAccount, Mint, TokenAccount, TokenInterface,
+        TransferChecked
+    },
+};
+
+use crate::{
+    state::{GlobalRegistry, Raffle, RegistryPage},
+    error::RaffleError
+};
+
+/// Rejects Token-2022 mints charging a transfer fee. The escrows would receive
+/// less than the amounts the raffle books, so the last payout would fail.
+pub fn check_supported_mint(mint: &InterfaceAccount<Mint>) -> Result<()> {
+    let info = mint.to_account_info();
+    if *info.owner != spl_token_2022::ID {
+        return Ok(());
+    }
+
+    let data = info.try_borrow_data()?;
+    let mint = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&data)?;
+    require!(
+        mint.get_extension::<TransferFeeConfig>().is_err(),
+        RaffleError::UnsupportedMint
+    );
+
+    Ok(())
+}
+
+/// Moves ticket tokens out of the raffle escrow, signing as the raffle PDA.
+pub fn transfer_from_escrow<'info>(
+    raffle: &Account<'info, Raffle>,
+    escrow: &InterfaceAccount<'info, TokenAccount>,
+    mint: &InterfaceAccount<'info, Mint>,
+    to: &InterfaceAccount<'info, TokenAccount>,
+    token_program: &Interface<'info, TokenInterface>,
+    amount: u64,
+) -> Result<()> {
+    let bump = [raffle.bump];
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        b"raffle",
+        raffle.owner.as_ref(),
+        raffle.raffle_id.as_bytes(),
+        &bump
+    ]];
+
+    transfer_checked(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            TransferChecked {
+                from: escrow.to_account_info(),
+                mint: mint.to_account_info(),
+                to: to.to_account_info(),
+                authority: raffle.to_account_info(),
+            },
+            signer_seeds
+        ),
+        amount,
+        mint.decimals
+    )
+}
+
+/// Sends the escrowed prize asset to `recipient` and closes the prize vault,
+/// returning its rent to the raffle creator.
+pub fn release_prize<'info>(
+    raffle: &mut Account<'info, Raffle>,
+    recipient: &Pubkey,
+    prize_mint: &Option<Box<InterfaceAccount<'info, Mint>>>,
+    recipient_prize_account: &Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    prize_vault: &Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    prize_token_program: &Option<Interface<'info, TokenInterface>>,
+    creator: &AccountInfo<'info>,
+) -> Result<()> {
+    let Some(raffle_prize_mint) = raffle.prize_mint else {
+        return Ok(());
+    };
+    let (Some(mint), Some(to), Some(vault), Some(token_program)) = (
+        prize_mint,
+        recipient_prize_account,
+        prize_vault,
+        prize_token_program,
+    ) else {
+        return err!(RaffleError::PrizeAccountsRequired);
+    };
+    require_keys_eq!(mint.key(), raffle_prize_mint, RaffleError::InvalidPrizeMint);
+    require_keys_eq!(to.owner, *recipient, RaffleError::InvalidTokenAccount);
+
+    transfer_from_escrow(raffle, vault, mint, to, token_program, raffle.prize_amount)?;
+
+    let bump = [raffle.bump];
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        b"raffle",
+        raffle.owner.as_ref(),
+        raffle.raffle_id.as_bytes(),
+        &bump
+    ]];
+
+    close_account(CpiContext::new_with_signer(
+        token_program.to_account_info(),
+        CloseAccount {
+            account: vault.to_account_info(),
+            destination: creator.clone(),
+            authority: raffle.to_account_info(),
+        },
+        signer_seeds
+    ))?;
+    raffle.prize_amount = 0;
+
+    Ok(())
+}
+
+/// Pays `amount` out of the raffle pool, either in lamports or from the token
+/// escrow depending on how the raffle is priced.
+pub fn pay_out<'info>(
+    raffle: &Account<'info, Raffle>,
+    recipient: &AccountInfo<'info>,
+    ticket_mint: &Option<InterfaceAccount<'info, Mint>>,
+    recipient_token_account: &Option<InterfaceAccount<'info, TokenAccount>>,
+    escrow: &Option<InterfaceAccount<'info, TokenAccount>>,
+    token_program: &Option<Interface<'info, TokenInterface>>,
+    amount: u64,
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+
+    match raffle.ticket_mint {
+        Some(raffle_mint) => {
+            let (Some(mint), Some(to), Some(escrow), Some(token_program)) = (
+                ticket_mint,
+                recipient_token_account,
+                escrow,
+                token_program,
+            ) else {
+                return err!(RaffleError::TokenAccountsRequired);
+            };
+            require_keys_eq!(mint.key(), raffle_mint, RaffleError::InvalidTicketMint);
+            require_keys_eq!(to.owner, recipient.key(), RaffleError::InvalidTokenAccount);
+
+            transfer_from_escrow(raffle, escrow, mint, to, token_program, amount)
+        }
+        None => {
+            raffle.sub_lamports(amount)?;
+            recipient.add_lamports(amount)?;
+            Ok(())
+        }
+    }
+}
+
+/// Pays the crank reward of a raffle to whoever submitted its draw, and
+/// records it so it is left out of the prize pool and the owner's proceeds.
+pub fn pay_crank_reward<'info>(
+    raffle: &mut Account<'info, Raffle>,
+    caller: &AccountInfo<'info>,
+    ticket_mint: &Option<InterfaceAccount<'info, Mint>>,
+    caller_token_account: &Option<InterfaceAccount<'info, TokenAccount>>,
+    escrow: &Option<InterfaceAccount<'info, TokenAccount>>,
+    token_program: &Option<Interface<'info, TokenInterface>>,
+) -> Result<()> {
+    let crank_reward = raffle.crank_reward()?;
+    pay_out(
+        raffle,
+        caller,
+        ticket_mint,
+        caller_token_account,
+        escrow,
+        token_program,
+        crank_reward
+    )?;
+    raffle.crank_reward_paid = crank_reward;
+
+    Ok(())
+}
+
+/// Collects `amount` from the buyer into the raffle pool, either in lamports or
+/// into the token escrow depending on how the raffle is priced.
+#[allow(clippy::too_many_arguments)]
+pub fn pay_in<'info>(
+    raffle: &Account<'info, Raffle>,
+    buyer: &Signer<'info>,
+    ticket_mint: &Option<InterfaceAccount<'info, Mint>>,
+    buyer_token_account: &Option<InterfaceAccount<'info, TokenAccount>>,
+    escrow: &Option<InterfaceAccount<'info, TokenAccount>>,
+    token_program: &Option<Interface<'info, TokenInterface>>,
+    system_program: &Program<'info, System>,
+    amount: u64,
+) -> Result<()> {
+    match raffle.ticket_mint {
+        Some(raffle_mint) => {
+            let (Some(mint), Some(buyer_token_account), Some(escrow), Some(token_program)) = (
+                ticket_mint,
+                buyer_token_account,
+                escrow,
+                token_program,
+            ) else {
+                return err!(RaffleError::TokenAccountsRequired);
+            };
+            require_keys_eq!(mint.key(), raffle_mint, RaffleError::InvalidTicketMint);
+            require!(buyer_token_account.amount >= amount, RaffleError::InsufficientFunds);
+
+            msg!("Transferring {} tokens from buyer to raffle escrow", amount);
+            transfer_checked(
+                CpiContext::new(
+                    token_program.to_account_info(),
+                    TransferChecked {
+                        from: buyer_token_account.to_account_info(),
+                        mint: mint.to_account_info(),
+                        to: escrow.to_account_info(),
+                        authority: buyer.to_account_info(),
+                    }
+                ),
+                amount,
+                mint.decimals
+            )
+        }
+        None => {
+            require!(buyer.lamports() >= amount, RaffleError::InsufficientFunds);
+
+            msg!("Transferring {} lamports from buyer to raffle", amount);
+            invoke(
+                &transfer(&buyer.key(), &raffle.key(), amount),
+                &[
+                    buyer.to_account_info(),
+                    raffle.to_account_info(),
+                    system_program.to_account_info(),
+                ],
+            )?;
+
+            Ok(())
+        }
+    }
+}
+
+/// Reads `account` as a `T` if this program has created it. Optional PDAs that
+/// may not exist are passed unchecked and loaded through this.
+pub fn load_if_initialized<T: AccountDeserialize>(account: &AccountInfo) -> Result<Option<T>> {
+    if account.owner != &crate::ID {
+        return Ok(None);
+    }
+
+    let data = account.try_borrow_data()?;
+    T::try_deserialize(&mut &data[..]).map(Some)
+}
+
+/// Closes an account of this program, sending its rent to `destination`.
+pub fn close_program_account<'info>(
+    account: &AccountInfo<'info>,
+    destination: &AccountInfo<'info>,
+) -> Result<()> {
+    destination.add_lamports(account.lamports())?;
+    account.sub_lamports(account.lamports())?;
+    account.assign(&System::id());
+    account.resize(0)?;
+
+    Ok(())
+}
+
+/// Lists a new raffle on the current page of the global registry, returning
+/// that page. Either registry account may have just been created, so their
+/// bumps are set too.
+pub fn list_raffle(
+    raffle: Pubkey,
+    global_registry: &mut GlobalRegistry,
+    global_registry_bump: u8,
+    registry_page: &mut RegistryPage,
+    registry_page_bump: u8,
+) -> Result<u32> {
+    global_registry.bump = global_registry_bump;
+    registry_page.page = global_registry.current_page;
+    registry_page.bump = registry_page_bump;
+    if registry_page.add(raffle)? {
+        global_registry.current_page += 1;
+    }
+
+    Ok(registry_page.page)
+}
+
+/// Takes a raffle off the global registry page it is listed on, if any.
+pub fn unlist_raffle(
+    raffle: &mut Account<Raffle>,
+    registry_page: &mut Option<Account<RegistryPage>>,
+) -> Result<()> {
+    let Some(page) = raffle.registry_page else {
+        return Ok(());
+    };
+    let registry_page = registry_page.as_mut()
+        .filter(|registry_page| registry_page.page == page)
+        .ok_or(RaffleError::RegistryPageRequired)?;
+
+    registry_page.remove(&raffle.key())?;
+    raffle.registry_page = None;
+
+    Ok(())
+}
@@ -1,7 +1,16 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
 import { Raffle } from '../target/types/raffle'
+import { MockOracle } from '../target/types/mock_oracle'
 import { assert } from "chai";
+import { createHash, randomBytes } from "crypto";
+import {
+  TOKEN_PROGRAM_ID,
+  createMint,
+  getAccount,
+  getOrCreateAssociatedTokenAccount,
+  mintTo,
+} from "@solana/spl-token";
 
 describe("raffle", () => {
   // Configure the client to use the local cluster.
@@ -11,16 +20,43 @@ describe("raffle", () => {
   const wallet = provider.wallet;
   const connection = provider.connection;
   const program = anchor.workspace.raffle as Program<Raffle>;
+  const mockOracle = anchor.workspace.mockOracle as Program<MockOracle>;
 
   const bob = anchor.web3.Keypair.generate();
   const john = anchor.web3.Keypair.generate();
   const alice = anchor.web3.Keypair.generate();
+  const treasury = anchor.web3.Keypair.generate();
+
+  before(async () => {
+    await airdrop(treasury.publicKey)
+    try {
+      await program.methods.initializeConfig(treasury.publicKey, 0)
+        .accounts({
+          admin: wallet.publicKey
+        })
+        .signers([wallet.payer])
+        .rpc();
+    } catch (error) {
+      // config already initialized on this validator
+      await updateConfig(wallet.publicKey, treasury.publicKey, 0)
+    }
+
+    // lists a first raffle so the global registry exists, later raffles
+    // resolve the registry page from it
+    await program.methods.createRaffle(await nextRaffleID(), raffleArgs(100_000_000, 2))
+      .accountsPartial({
+        owner: wallet.publicKey,
+        registryPage: await getCurrentRegistryPage()
+      })
+      .signers([wallet.payer])
+      .rpc();
+  });
 
   describe("Create Raffle", async () => {
     it('Should fail if max entries is zero(0)', async () => {
       try {
-        const raffleId = generateRaffleID()
-        await program.methods.createRaffle(raffleId, new anchor.BN(1000000000), 0)
+        const raffleId = await nextRaffleID()
+        await program.methods.createRaffle(raffleId, raffleArgs(1000000000, 0))
           .accounts({
             owner: wallet.publicKey
           })
@@ -34,8 +70,8 @@ describe("raffle", () => {
 
     it('Should fail if ticket prize is zero(0)', async () => {
       try {
-        const raffleId = generateRaffleID()
-        await program.methods.createRaffle(raffleId, new anchor.BN(0), 2)
+        const raffleId = await nextRaffleID()
+        await program.methods.createRaffle(raffleId, raffleArgs(0, 2))
           .accounts({
             owner: wallet.publicKey
           })
@@ -49,7 +85,7 @@ describe("raffle", () => {
 
     it('Should create raffle if ticket prize and max entries are valid', async () => {
       const raffleId = "dddjdjd"
-      await program.methods.createRaffle(raffleId, new anchor.BN(10_000_000), 2)
+      await program.methods.createRaffle(raffleId, raffleArgs(10_000_000, 2))
         .accounts({
           owner: wallet.publicKey
         })
@@ -59,7 +95,7 @@ describe("raffle", () => {
       const account = await program.account.raffle.fetch(getRafflePDA(wallet.publicKey, raffleId))
       assert.equal(account.entries, 0)
       assert.equal(account.maxEntries, 2)
-      assert.equal(account.isActive, true)
+      assert.deepEqual(account.status, { open: {} })
       assert.equal(account.ticketPrice.toNumber(), 10_000_000)
       assert.equal(account.owner.toBase58(), wallet.publicKey.toBase58())
     });
@@ -67,8 +103,8 @@ describe("raffle", () => {
 
   describe("Buy Ticket", async () => {
     it("Should fail if alice has insufficient funds", async () => {
-      const raffleId = generateRaffleID()
-      await program.methods.createRaffle(raffleId, new anchor.BN(100_000_000), 2)
+      const raffleId = await nextRaffleID()
+      await program.methods.createRaffle(raffleId, raffleArgs(100_000_000, 2))
         .accounts({
           owner: wallet.publicKey
         })
@@ -88,8 +124,8 @@ describe("raffle", () => {
     });
 
     it("Should fail if raffle entries exceeds max entries", async () => {
-      const raffleId = generateRaffleID()
-      await program.methods.createRaffle(raffleId, new anchor.BN(100_000_000), 2)
+      const raffleId = await nextRaffleID()
+      await program.methods.createRaffle(raffleId, raffleArgs(100_000_000, 2))
         .accounts({
           owner: wallet.publicKey
         })
@@ -121,8 +157,8 @@ describe("raffle", () => {
     });
 
     it("Should pass if alice has sufficient funds and raffle entries is below max entries", async () => {
-      const raffleId = generateRaffleID()
-      await program.methods.createRaffle(raffleId, new anchor.BN(100_000_000), 2)
+      const raffleId = await nextRaffleID()
+      await program.methods.createRaffle(raffleId, raffleArgs(100_000_000, 2))
         .accounts({
           owner: wallet.publicKey
         })
@@ -149,12 +185,307 @@ describe("raffle", () => {
       assert.equal(ticketAccount.raffle.toBase58(), rafflePDA.toBase58())
       assert.equal(ticketAccount.owner.toBase58(), alice.publicKey.toBase58())
     });
+
+    it("Should store the ticket id its address is derived from", async () => {
+      const raffleId = await nextRaffleID()
+      const rafflePDA = getRafflePDA(wallet.publicKey, raffleId)
+      await program.methods.createRaffle(raffleId, raffleArgs(100_000_000, 3))
+        .accounts({
+          owner: wallet.publicKey
+        })
+        .signers([wallet.payer])
+        .rpc();
+
+      await airdrop(alice.publicKey)
+      await buyTicket(raffleId, rafflePDA, alice)
+      await airdrop(bob.publicKey)
+      await buyTicket(raffleId, rafflePDA, bob)
+
+      for (const ticketID of [1, 2]) {
+        const ticketAccount = await program.account.ticket.fetch(getTicketPDA(rafflePDA, ticketID))
+        assert.equal(ticketAccount.ticketId, ticketID)
+      }
+    });
+
+    it("Should fail if the ticket address does not match the next ticket id", async () => {
+      const raffleId = await nextRaffleID()
+      const rafflePDA = getRafflePDA(wallet.publicKey, raffleId)
+      await program.methods.createRaffle(raffleId, raffleArgs(100_000_000, 3))
+        .accounts({
+          owner: wallet.publicKey
+        })
+        .signers([wallet.payer])
+        .rpc();
+
+      await airdrop(alice.publicKey)
+      for (const ticketID of [0, 2, 42]) {
+        try {
+          await program.methods.buyTicket(raffleId, [])
+            .accountsPartial({
+              buyer: alice.publicKey,
+              raffle: rafflePDA,
+              ticket: getTicketPDA(rafflePDA, ticketID),
+            })
+            .signers([alice])
+            .rpc();
+          assert.fail("Reason: ticket address does not match the next ticket id");
+        } catch (error) {
+          assert.include(error.toString(), "ConstraintSeeds")
+        }
+      }
+
+      const raffleAccount = await program.account.raffle.fetch(rafflePDA)
+      assert.equal(raffleAccount.entries, 0)
+    });
+
+    it("Should let the winner of a ticket claim through its derived address", async () => {
+      const raffleId = await nextRaffleID()
+      const rafflePDA = getRafflePDA(wallet.publicKey, raffleId)
+      await program.methods.createRaffle(raffleId, raffleArgs(100_000_000, 2))
+        .accounts({
+          owner: wallet.publicKey
+        })
+        .signers([wallet.payer])
+        .rpc();
+
+      await airdrop(alice.publicKey)
+      await buyTicket(raffleId, rafflePDA, alice)
+      await airdrop(bob.publicKey)
+      await buyTicket(raffleId, rafflePDA, bob)
+      await program.methods.pickWinner(raffleId)
+        .accounts({
+          caller: wallet.publicKey,
+          raffle: rafflePDA
+        })
+        .signers([wallet.payer])
+        .rpc();
+
+      const raffleAccount = await program.account.raffle.fetch(rafflePDA)
+      const winningTicketID = raffleAccount.winners[0].ticketId
+      const winner = winningTicketID == 1 ? alice : bob
+      await claimPrize(raffleId, rafflePDA, getTicketPDA(rafflePDA, winningTicketID), winner)
+
+      const claimedRaffle = await program.account.raffle.fetch(rafflePDA)
+      assert.deepEqual(claimedRaffle.status, { claimed: {} })
+    });
+  });
+
+  describe("Buy Tickets", async () => {
+    it("Should record a bulk purchase as one ticket range", async () => {
+      const raffleId = await nextRaffleID()
+      const rafflePDA = getRafflePDA(wallet.publicKey, raffleId)
+      await program.methods.createRaffle(raffleId, raffleArgs(100_000_000, 5))
+        .accounts({
+          owner: wallet.publicKey
+        })
+        .signers([wallet.payer])
+        .rpc();
+
+      await airdrop(bob.publicKey)
+      await buyTicket(raffleId, rafflePDA, bob)
+      await airdrop(alice.publicKey)
+      await buyTickets(raffleId, rafflePDA, alice, 3)
+      await buyTickets(raffleId, rafflePDA, alice, 1)
+
+      const entry = await program.account.entry.fetch(getEntryPDA(rafflePDA, alice.publicKey))
+      assert.equal(entry.ranges.length, 1)
+      assert.equal(entry.ranges[0].firstTicket, 2)
+      assert.equal(entry.ranges[0].quantity, 4)
+
+      const raffleAccount = await program.account.raffle.fetch(rafflePDA)
+      assert.equal(raffleAccount.entries, 5)
+      assert.deepEqual(raffleAccount.status, { drawing: {} })
+    });
+
+    it("Should fail if quantity is zero(0)", async () => {
+      const raffleId = await nextRaffleID()
+      const rafflePDA = getRafflePDA(wallet.publicKey, raffleId)
+      await program.methods.createRaffle(raffleId, raffleArgs(100_000_000, 5))
+        .accounts({
+          owner: wallet.publicKey
+        })
+        .signers([wallet.payer])
+        .rpc();
+
+      await airdrop(alice.publicKey)
+      try {
+        await buyTickets(raffleId, rafflePDA, alice, 0)
+        assert.fail("Reason: quantity is zero");
+      } catch (error) {
+        assert.include(error.toString(), "InvalidQuantity")
+      }
+    });
+
+    it("Should fail if quantity exceeds remaining entries", async () => {
+      const raffleId = await nextRaffleID()
+      const rafflePDA = getRafflePDA(wallet.publicKey, raffleId)
+      await program.methods.createRaffle(raffleId, raffleArgs(100_000_000, 3))
+        .accounts({
+          owner: wallet.publicKey
+        })
+        .signers([wallet.payer])
+        .rpc();
+
+      await airdrop(alice.publicKey)
+      await buyTickets(raffleId, rafflePDA, alice, 2)
+      try {
+        await buyTickets(raffleId, rafflePDA, alice, 2)
+        assert.fail("Reason: raffle would be oversold");
+      } catch (error) {
+        assert.ok(error)
+      }
+    });
+
+    it("Should let the entry owner claim a winning ticket from a bulk purchase", async () => {
+      const raffleId = await nextRaffleID()
+      const rafflePDA = getRafflePDA(wallet.publicKey, raffleId)
+      await program.methods.createRaffle(raffleId, raffleArgs(100_000_000, 3))
+        .accounts({
+          owner: wallet.publicKey
+        })
+        .signers([wallet.payer])
+        .rpc();
+
+      await airdrop(alice.publicKey)
+      await buyTickets(raffleId, rafflePDA, alice, 3)
+      await program.methods.pickWinner(raffleId)
+        .accounts({
+          caller: wallet.publicKey,
+          raffle: rafflePDA
+        })
+        .signers([wallet.payer])
+        .rpc();
+
+      const balanceBefore = await connection.getBalance(alice.publicKey)
+      await program.methods.claimPrize(raffleId)
+        .accountsPartial({
+          winner: alice.publicKey,
+          raffle: rafflePDA,
+          ticket: null,
+          entry: getEntryPDA(rafflePDA, alice.publicKey)
+        })
+        .signers([alice])
+        .rpc();
+      const balanceAfter = await connection.getBalance(alice.publicKey)
+      assert.isTrue(balanceAfter > balanceBefore)
+
+      const raffleAccount = await program.account.raffle.fetch(rafflePDA)
+      assert.deepEqual(raffleAccount.status, { claimed: {} })
+    });
+  });
+
+  describe("Wallet Cap", async () => {
+    it("Should let a wallet buy exactly up to the cap", async () => {
+      const raffleId = await nextRaffleID()
+      const rafflePDA = getRafflePDA(wallet.publicKey, raffleId)
+      await program.methods.createRaffle(raffleId, raffleArgs(100_000_000, 5, { maxTicketsPerWallet: 3 }))
+        .accounts({
+          owner: wallet.publicKey
+        })
+        .signers([wallet.payer])
+        .rpc();
+
+      await airdrop(alice.publicKey)
+      await buyTicket(raffleId, rafflePDA, alice)
+      await buyTickets(raffleId, rafflePDA, alice, 2)
+
+      const participant = await program.account.participant.fetch(getParticipantPDA(rafflePDA, alice.publicKey))
+      assert.equal(participant.ticketsBought, 3)
+    });
+
+    it("Should fail if a wallet exceeds the cap by one ticket", async () => {
+      const raffleId = await nextRaffleID()
+      const rafflePDA = getRafflePDA(wallet.publicKey, raffleId)
+      await program.methods.createRaffle(raffleId, raffleArgs(100_000_000, 5, { maxTicketsPerWallet: 3 }))
+        .accounts({
+          owner: wallet.publicKey
+        })
+        .signers([wallet.payer])
+        .rpc();
+
+      await airdrop(alice.publicKey)
+      await buyTickets(raffleId, rafflePDA, alice, 3)
+      try {
+        await buyTicket(raffleId, rafflePDA, alice)
+        assert.fail("Reason: wallet cap reached");
+      } catch (error) {
+        assert.include(error.toString(), "WalletCapReached")
+      }
+
+      // other wallets are not affected by alice's cap
+      await airdrop(bob.publicKey)
+      await buyTicket(raffleId, rafflePDA, bob)
+      const raffleAccount = await program.account.raffle.fetch(rafflePDA)
+      assert.equal(raffleAccount.entries, 4)
+    });
+
+    it("Should fail if a bulk purchase goes over the cap", async () => {
+      const raffleId = await nextRaffleID()
+      const rafflePDA = getRafflePDA(wallet.publicKey, raffleId)
+      await program.methods.createRaffle(raffleId, raffleArgs(100_000_000, 5, { maxTicketsPerWallet: 3 }))
+        .accounts({
+          owner: wallet.publicKey
+        })
+        .signers([wallet.payer])
+        .rpc();
+
+      await airdrop(alice.publicKey)
+      try {
+        await buyTickets(raffleId, rafflePDA, alice, 4)
+        assert.fail("Reason: wallet cap reached");
+      } catch (error) {
+        assert.include(error.toString(), "WalletCapReached")
+      }
+    });
+  });
+
+  describe("Allowlist", async () => {
+    it("Should let allowlisted wallets buy with a valid proof", async () => {
+      const raffleId = await nextRaffleID()
+      const rafflePDA = getRafflePDA(wallet.publicKey, raffleId)
+      const allowlist = [alice.publicKey, bob.publicKey, treasury.publicKey]
+      await program.methods.createRaffle(raffleId, raffleArgs(100_000_000, 3, { allowlistRoot: merkleRoot(allowlist) }))
+        .accounts({
+          owner: wallet.publicKey
+        })
+        .signers([wallet.payer])
+        .rpc();
+
+      await airdrop(alice.publicKey)
+      await buyTicket(raffleId, rafflePDA, alice, merkleProof(allowlist, alice.publicKey))
+      await airdrop(bob.publicKey)
+      await buyTickets(raffleId, rafflePDA, bob, 2, merkleProof(allowlist, bob.publicKey))
+
+      const raffleAccount = await program.account.raffle.fetch(rafflePDA)
+      assert.equal(raffleAccount.entries, 3)
+    });
+
+    it("Should fail if the wallet is not on the allowlist", async () => {
+      const raffleId = await nextRaffleID()
+      const rafflePDA = getRafflePDA(wallet.publicKey, raffleId)
+      const allowlist = [alice.publicKey, bob.publicKey]
+      await program.methods.createRaffle(raffleId, raffleArgs(100_000_000, 3, { allowlistRoot: merkleRoot(allowlist) }))
+        .accounts({
+          owner: wallet.publicKey
+        })
+        .signers([wallet.payer])
+        .rpc();
+
+      await airdrop(john.publicKey)
+      try {
+        // john borrows alice's proof
+        await buyTicket(raffleId, rafflePDA, john, merkleProof(allowlist, alice.publicKey))
+        assert.fail("Reason: john is not allowlisted");
+      } catch (error) {
+        assert.include(error.toString(), "NotAllowlisted")
+      }
+    });
   });
 
   describe("Pick Winner", async () => {
     it("Should fail if entries is less than max entries", async () => {
-      const raffleId = generateRaffleID()
-      await program.methods.createRaffle(raffleId, new anchor.BN(100_000_000), 2)
+      const raffleId = await nextRaffleID()
+      await program.methods.createRaffle(raffleId, raffleArgs(100_000_000, 2))
         .accounts({
           owner: wallet.publicKey
         })
@@ -175,7 +506,7 @@ describe("raffle", () => {
       try {
         await program.methods.pickWinner(raffleId)
           .accounts({
-            owner: wallet.publicKey,
+            caller: wallet.publicKey,
             raffle: rafflePDA
           })
           .signers([wallet.payer])
@@ -187,9 +518,9 @@ describe("raffle", () => {
       }
     });
 
-    it("Should fail if incorrect raffle wallet tries to pick winner", async () => {
-      const raffleId = generateRaffleID()
-      await program.methods.createRaffle(raffleId, new anchor.BN(100_000_000), 2)
+    it("Should let any wallet pick the winner once the raffle is sold out", async () => {
+      const raffleId = await nextRaffleID()
+      await program.methods.createRaffle(raffleId, raffleArgs(100_000_000, 2))
         .accounts({
           owner: wallet.publicKey
         })
@@ -215,24 +546,82 @@ describe("raffle", () => {
         bob
       );
       
+      await program.methods.pickWinner(raffleId)
+        .accounts({
+          caller: alice.publicKey,
+          raffle: rafflePDA
+        })
+        .signers([alice])
+        .rpc();
+
+      const raffleAccount = await program.account.raffle.fetch(rafflePDA)
+      assert.deepEqual(raffleAccount.status, { drawn: {} })
+    });
+
+    it("Should pay the crank reward to whoever picks the winner", async () => {
+      const raffleId = await nextRaffleID()
+      const rafflePDA = getRafflePDA(wallet.publicKey, raffleId)
+      await program.methods.createRaffle(raffleId, raffleArgs(100_000_000, 2, { crankRewardBps: 100 }))
+        .accounts({
+          owner: wallet.publicKey
+        })
+        .signers([wallet.payer])
+        .rpc();
+
+      await airdrop(alice.publicKey)
+      await buyTicket(raffleId, rafflePDA, alice)
+      await airdrop(bob.publicKey)
+      await buyTicket(raffleId, rafflePDA, bob)
+
+      await airdrop(john.publicKey)
+      const johnBalanceBefore = await connection.getBalance(john.publicKey)
+      await program.methods.pickWinner(raffleId)
+        .accounts({
+          caller: john.publicKey,
+          raffle: rafflePDA
+        })
+        .signers([john])
+        .rpc();
+      const johnBalanceAfter = await connection.getBalance(john.publicKey)
+      // 1% of the 0.2 SOL pool, less the transaction fee
+      assert.isTrue(johnBalanceAfter - johnBalanceBefore > 1_990_000)
+
+      const raffleAccount = await program.account.raffle.fetch(rafflePDA)
+      assert.equal(raffleAccount.crankRewardPaid.toNumber(), 2_000_000)
+
+      const winningTicketID = raffleAccount.winners[0].ticketId
+      const winner = winningTicketID == 1 ? alice : bob
+      const claimed = await getEvents(
+        await program.methods.claimPrize(raffleId)
+          .accountsPartial({
+            winner: winner.publicKey,
+            ticket: getTicketPDA(rafflePDA, winningTicketID),
+            raffle: rafflePDA
+          })
+          .signers([winner])
+          .rpc()
+      )
+      assert.equal(claimed[0].data.winnerAmount.toNumber(), 198_000_000)
+    });
+
+    it("Should fail if the crank reward is above 100 bps", async () => {
       try {
-        await program.methods.pickWinner(raffleId)
+        const raffleId = await nextRaffleID()
+        await program.methods.createRaffle(raffleId, raffleArgs(100_000_000, 2, { crankRewardBps: 101 }))
           .accounts({
-            owner: alice.publicKey,
-            raffle: rafflePDA
+            owner: wallet.publicKey
           })
           .signers([wallet.payer])
           .rpc();
-          
-        assert.fail("Reason: unauthorized action");
+        assert.fail("Reason: crank reward is too high");
       } catch (error) {
-        assert.ok(error)
+        assert.include(error.toString(), "InvalidCrankReward")
       }
     });
 
     it("Should pass if all conditions are met", async () => {
-      const raffleId = generateRaffleID()
-      await program.methods.createRaffle(raffleId, new anchor.BN(100_000_000), 2)
+      const raffleId = await nextRaffleID()
+      await program.methods.createRaffle(raffleId, raffleArgs(100_000_000, 2))
         .accounts({
           owner: wallet.publicKey
         })
@@ -260,7 +649,7 @@ describe("raffle", () => {
       
       await program.methods.pickWinner(raffleId)
         .accounts({
-          owner: wallet.publicKey,
+          caller: wallet.publicKey,
           raffle: rafflePDA
         })
         .signers([wallet.payer])
@@ -269,16 +658,16 @@ describe("raffle", () => {
       // raffle account
       const raffleAccount = await program.account.raffle.fetch(rafflePDA);
         
-      const ticketPDA = getTicketPDA(rafflePDA, raffleAccount.winnerTicket);
+      const ticketPDA = getTicketPDA(rafflePDA, raffleAccount.winners[0].ticketId);
       const ticketAccount = await program.account.ticket.fetch(ticketPDA)
-      assert.equal(raffleAccount.winnerTicket, ticketAccount.ticketId);
+      assert.equal(raffleAccount.winners[0].ticketId, ticketAccount.ticketId);
     });
   });
 
   describe("Claim Prize", async () => {
     it("Should fail if raffle is active and no winner has been picked", async () => {
-      const raffleId = generateRaffleID()
-      await program.methods.createRaffle(raffleId, new anchor.BN(100_000_000), 2)
+      const raffleId = await nextRaffleID()
+      await program.methods.createRaffle(raffleId, raffleArgs(100_000_000, 2))
         .accounts({
           owner: wallet.publicKey
         })
@@ -326,8 +715,8 @@ describe("raffle", () => {
     });
 
     it("Should fail if invalid winner tries to claim", async () => {
-      const raffleId = generateRaffleID()
-      await program.methods.createRaffle(raffleId, new anchor.BN(100_000_000), 2)
+      const raffleId = await nextRaffleID()
+      await program.methods.createRaffle(raffleId, raffleArgs(100_000_000, 2))
         .accounts({
           owner: wallet.publicKey
         })
@@ -355,7 +744,7 @@ describe("raffle", () => {
 
       await program.methods.pickWinner(raffleId)
         .accounts({
-          owner: wallet.publicKey,
+          caller: wallet.publicKey,
           raffle: rafflePDA
         })
         .signers([wallet.payer])
@@ -365,7 +754,7 @@ describe("raffle", () => {
       const ticketPDA = getTicketPDA(rafflePDA, raffleAccount.entries);
 
       try {
-        const invalidUser = raffleAccount.winnerTicket == 1 ? bob : alice
+        const invalidUser = raffleAccount.winners[0].ticketId == 1 ? bob : alice
         await program.methods.claimPrize(raffleId)
           .accountsPartial({
             winner: invalidUser.publicKey,
@@ -381,8 +770,8 @@ describe("raffle", () => {
     });
 
     it("Should pass if all conditions are met", async () => {
-      const raffleId = generateRaffleID()
-      await program.methods.createRaffle(raffleId, new anchor.BN(2_000_000_000), 2)
+      const raffleId = await nextRaffleID()
+      await program.methods.createRaffle(raffleId, raffleArgs(2_000_000_000, 2))
         .accounts({
           owner: wallet.publicKey
         })
@@ -410,14 +799,14 @@ describe("raffle", () => {
 
       await program.methods.pickWinner(raffleId)
         .accounts({
-          owner: wallet.publicKey,
+          caller: wallet.publicKey,
           raffle: rafflePDA
         })
         .signers([wallet.payer])
         .rpc();
 
       const raffleAccount = await program.account.raffle.fetch(rafflePDA);
-      const ticketPDA = getTicketPDA(rafflePDA, raffleAccount.winnerTicket);
+      const ticketPDA = getTicketPDA(rafflePDA, raffleAccount.winners[0].ticketId);
       const ticketAccount = await program.account.ticket.fetch(ticketPDA);
 
       const signer = ticketAccount.owner.toBase58() == alice.publicKey.toBase58() ? alice : bob;
@@ -435,49 +824,2126 @@ describe("raffle", () => {
 
       const winnerBalanceAfter = await connection.getBalance(ticketAccount.owner);
       assert.isTrue(winnerBalanceAfter > winnerBalanceBefore)
+
+      const claimedRaffle = await program.account.raffle.fetch(rafflePDA);
+      assert.deepEqual(claimedRaffle.status, { claimed: {} })
+
+      try {
+        await program.methods.claimPrize(raffleId)
+          .accountsPartial({
+            winner: ticketAccount.owner,
+            ticket: ticketPDA,
+            raffle: rafflePDA
+          })
+          .signers([signer])
+          .rpc()
+        assert.fail("Reason: prize already claimed");
+      } catch (error) {
+        assert.include(error.toString(), "RaffleAlreadyClaimed")
+      }
     });
   });
 
-  function getRafflePDA(owner: anchor.web3.PublicKey, raffleID: string) {
-    return anchor.web3.PublicKey.findProgramAddressSync(
-      [
-        Buffer.from("raffle"),
-        owner.toBuffer(),
-        Buffer.from(raffleID)
-      ],
-      program.programId
-    )[0]
-  }
-
-  function getTicketPDA(raffle: anchor.web3.PublicKey, ticketID: number) {
-    return anchor.web3.PublicKey.findProgramAddressSync(
-      [
-        Buffer.from("ticket"),
-        raffle.toBuffer(),
-        new anchor.BN(ticketID).toArrayLike(Buffer, "le", 4)
-      ],
+  describe("Fees", async () => {
+    it("Should fail if a non-admin updates the config", async () => {
+      await airdrop(alice.publicKey)
+      try {
+        await program.methods.updateConfig(alice.publicKey, alice.publicKey, 0)
+          .accounts({
+            admin: alice.publicKey
+          })
+          .signers([alice])
+          .rpc();
+        assert.fail("Reason: unauthorized action");
+      } catch (error) {
+        assert.ok(error)
+      }
+    });
+
+    it("Should fail if the fees exceed the prize pool", async () => {
+      const raffleId = await nextRaffleID()
+      try {
+        await program.methods.createRaffle(raffleId, raffleArgs(100_000_000, 2, { creatorFeeBps: 10_001 }))
+          .accounts({
+            owner: wallet.publicKey
+          })
+          .signers([wallet.payer])
+          .rpc();
+        assert.fail("Reason: creator fee exceeds the pool");
+      } catch (error) {
+        assert.include(error.toString(), "InvalidFee")
+      }
+    });
+
+    it("Should split the prize between winner, creator and treasury", async () => {
+      await updateConfig(wallet.publicKey, treasury.publicKey, 500)
+
+      const raffleId = await nextRaffleID()
+      const rafflePDA = getRafflePDA(wallet.publicKey, raffleId)
+      await program.methods.createRaffle(raffleId, raffleArgs(1_000_000_000, 2, { creatorFeeBps: 1_000 }))
+        .accounts({
+          owner: wallet.publicKey
+        })
+        .signers([wallet.payer])
+        .rpc();
+      await updateConfig(wallet.publicKey, treasury.publicKey, 0)
+
+      await airdrop(alice.publicKey)
+      await buyTicket(raffleId, rafflePDA, alice)
+      await airdrop(bob.publicKey)
+      await buyTicket(raffleId, rafflePDA, bob)
+
+      await program.methods.pickWinner(raffleId)
+        .accounts({
+          caller: wallet.publicKey,
+          raffle: rafflePDA
+        })
+        .signers([wallet.payer])
+        .rpc();
+
+      const raffleAccount = await program.account.raffle.fetch(rafflePDA);
+      assert.equal(raffleAccount.protocolFeeBps, 500)
+      const ticketPDA = getTicketPDA(rafflePDA, raffleAccount.winners[0].ticketId);
+      const ticketAccount = await program.account.ticket.fetch(ticketPDA);
+      const signer = ticketAccount.owner.toBase58() == alice.publicKey.toBase58() ? alice : bob;
+
+      const treasuryBefore = await connection.getBalance(treasury.publicKey)
+      await program.methods.claimPrize(raffleId)
+        .accountsPartial({
+          winner: signer.publicKey,
+          ticket: ticketPDA,
+          raffle: rafflePDA
+        })
+        .signers([signer])
+        .rpc()
+      const treasuryAfter = await connection.getBalance(treasury.publicKey)
+
+      // 5% of the 2 SOL pool
+      assert.equal(treasuryAfter - treasuryBefore, 100_000_000)
+    });
+  });
+
+  describe("Prize Tiers", async () => {
+    it("Should fail if the prize tiers do not add up to the whole pool", async () => {
+      const raffleId = await nextRaffleID()
+      try {
+        await program.methods.createRaffle(raffleId, raffleArgs(100_000_000, 3, { prizeTiers: [5_000, 3_000] }))
+          .accounts({
+            owner: wallet.publicKey
+          })
+          .signers([wallet.payer])
+          .rpc();
+        assert.fail("Reason: prize tiers do not add up to 10000 bps");
+      } catch (error) {
+        assert.include(error.toString(), "InvalidPrizeTiers")
+      }
+    });
+
+    it("Should draw distinct winners and pay each tier its share once", async () => {
+      const raffleId = await nextRaffleID()
+      const rafflePDA = getRafflePDA(wallet.publicKey, raffleId)
+      await program.methods.createRaffle(raffleId, raffleArgs(1_000_000_000, 3, { prizeTiers: [5_000, 3_000, 2_000] }))
+        .accounts({
+          owner: wallet.publicKey
+        })
+        .signers([wallet.payer])
+        .rpc();
+
+      const buyers = [alice, bob, john]
+      for (const buyer of buyers) {
+        await airdrop(buyer.publicKey)
+        await buyTicket(raffleId, rafflePDA, buyer)
+      }
+
+      await program.methods.pickWinner(raffleId)
+        .accounts({
+          caller: wallet.publicKey,
+          raffle: rafflePDA
+        })
+        .signers([wallet.payer])
+        .rpc();
+
+      const raffleAccount = await program.account.raffle.fetch(rafflePDA);
+      const ticketIds = raffleAccount.winners.map((winner) => winner.ticketId)
+      assert.equal(new Set(ticketIds).size, 3)
+
+      // second tier wins 30% of the 3 SOL pool
+      const ticketPDA = getTicketPDA(rafflePDA, ticketIds[1]);
+      const ticketAccount = await program.account.ticket.fetch(ticketPDA);
+      const signer = buyers.find((buyer) => buyer.publicKey.equals(ticketAccount.owner))
+
+      const balanceBefore = await connection.getBalance(signer.publicKey)
+      await claimPrize(raffleId, rafflePDA, ticketPDA, signer)
+      const balanceAfter = await connection.getBalance(signer.publicKey)
+      assert.isTrue(balanceAfter - balanceBefore > 890_000_000)
+
+      const drawnRaffle = await program.account.raffle.fetch(rafflePDA);
+      assert.isTrue(drawnRaffle.winners[1].claimed)
+      assert.deepEqual(drawnRaffle.status, { drawn: {} })
+
+      try {
+        await claimPrize(raffleId, rafflePDA, ticketPDA, signer)
+        assert.fail("Reason: tier already claimed");
+      } catch (error) {
+        assert.include(error.toString(), "TierAlreadyClaimed")
+      }
+    });
+  });
+
+  describe("Raffle Deadline", async () => {
+    it("Should fail to buy a ticket after the end time", async () => {
+      const raffleId = await nextRaffleID()
+      const rafflePDA = await createTimedRaffle(raffleId, 2)
+      await sleep(3000)
+
+      await airdrop(alice.publicKey)
+      try {
+        await buyTicket(raffleId, rafflePDA, alice)
+        assert.fail("Reason: raffle has ended");
+      } catch (error) {
+        assert.include(error.toString(), "RaffleEnded")
+      }
+    });
+
+    it("Should draw from the tickets sold once the end time has passed", async () => {
+      const raffleId = await nextRaffleID()
+      const rafflePDA = await createTimedRaffle(raffleId, 3)
+
+      await airdrop(alice.publicKey)
+      await buyTicket(raffleId, rafflePDA, alice)
+      await sleep(4000)
+
+      await program.methods.pickWinner(raffleId)
+        .accounts({
+          caller: wallet.publicKey,
+          raffle: rafflePDA,
+          registryPage: await getListedRegistryPage(rafflePDA)
+        })
+        .signers([wallet.payer])
+        .rpc();
+
+      const raffleAccount = await program.account.raffle.fetch(rafflePDA);
+      assert.equal(raffleAccount.winners[0].ticketId, 1)
+    });
+
+    it("Should expire a raffle that ended short of its minimum entries when drawn", async () => {
+      const raffleId = await nextRaffleID()
+      const rafflePDA = getRafflePDA(wallet.publicKey, raffleId)
+      const endTime = new anchor.BN(Math.floor(Date.now() / 1000) + 3)
+      await program.methods.createRaffle(raffleId, raffleArgs(100_000_000, 3, { endTime, minEntries: 2 }))
+        .accounts({
+          owner: wallet.publicKey
+        })
+        .signers([wallet.payer])
+        .rpc();
+
+      await airdrop(alice.publicKey)
+      await buyTicket(raffleId, rafflePDA, alice)
+      await sleep(4000)
+
+      await program.methods.pickWinner(raffleId)
+        .accounts({
+          caller: wallet.publicKey,
+          raffle: rafflePDA,
+          registryPage: await getListedRegistryPage(rafflePDA)
+        })
+        .signers([wallet.payer])
+        .rpc();
+
+      const raffleAccount = await program.account.raffle.fetch(rafflePDA);
+      assert.deepEqual(raffleAccount.status, { expired: {} })
+      assert.isEmpty(raffleAccount.winners)
+    });
+
+    it("Should fail to expire a raffle before its end time", async () => {
+      const raffleId = await nextRaffleID()
+      const rafflePDA = await createTimedRaffle(raffleId, 60)
+
+      await airdrop(john.publicKey)
+      try {
+        await expireRaffle(raffleId, rafflePDA)
+        assert.fail("Reason: raffle has not ended");
+      } catch (error) {
+        assert.include(error.toString(), "ExpiryNotReached")
+      }
+    });
+
+    it("Should let anyone expire an ended raffle without entries", async () => {
+      const raffleId = await nextRaffleID()
+      const rafflePDA = await createTimedRaffle(raffleId, 2)
+      await sleep(3000)
+
+      await airdrop(john.publicKey)
+      await expireRaffle(raffleId, rafflePDA)
+
+      const raffleAccount = await program.account.raffle.fetch(rafflePDA);
+      assert.deepEqual(raffleAccount.status, { expired: {} })
+    });
+
+    it("Should refund a ticket of a cancelled raffle exactly once", async () => {
+      const raffleId = await nextRaffleID()
+      const rafflePDA = await createTimedRaffle(raffleId, 3)
+
+      await airdrop(alice.publicKey)
+      await buyTicket(raffleId, rafflePDA, alice)
+      await sleep(4000)
+
+      await cancelRaffle(raffleId, rafflePDA)
+
+      const ticketPDA = getTicketPDA(rafflePDA, 1)
+      const balanceBefore = await connection.getBalance(alice.publicKey)
+      await refundTicket(raffleId, rafflePDA, ticketPDA, alice)
+      const balanceAfter = await connection.getBalance(alice.publicKey)
+      assert.isTrue(balanceAfter - balanceBefore >= 100_000_000)
+
+      try {
+        await refundTicket(raffleId, rafflePDA, ticketPDA, alice)
+        assert.fail("Reason: ticket already refunded");
+      } catch (error) {
+        assert.ok(error)
+      }
+    });
+  });
+
+  describe("Cancel Raffle", async () => {
+    it("Should fail if a non-owner cancels the raffle", async () => {
+      const raffleId = await nextRaffleID()
+      const rafflePDA = await createTimedRaffle(raffleId, 60)
+
+      await airdrop(alice.publicKey)
+      try {
+        await program.methods.cancelRaffle(raffleId)
+          .accountsPartial({
+            owner: alice.publicKey,
+            raffle: rafflePDA
+          })
+          .signers([alice])
+          .rpc();
+        assert.fail("Reason: unauthorized action");
+      } catch (error) {
+        assert.ok(error)
+      }
+    });
+
+    it("Should fail to cancel a committed raffle once it can be drawn", async () => {
+      const raffleId = await nextRaffleID()
+      const rafflePDA = await createCommittedRaffle(raffleId, randomBytes(32))
+
+      try {
+        await cancelRaffle(raffleId, rafflePDA)
+        assert.fail("Reason: the raffle sold out");
+      } catch (error) {
+        assert.include(error.toString(), "RaffleDrawing")
+      }
+    });
+
+    it("Should reject picking a winner for a cancelled raffle", async () => {
+      const raffleId = await nextRaffleID()
+      const rafflePDA = getRafflePDA(wallet.publicKey, raffleId)
+      await program.methods.createRaffle(raffleId, raffleArgs(100_000_000, 2))
+        .accounts({
+          owner: wallet.publicKey
+        })
+        .signers([wallet.payer])
+        .rpc();
+
+      await airdrop(alice.publicKey)
+      await buyTicket(raffleId, rafflePDA, alice)
+      await airdrop(bob.publicKey)
+      await buyTicket(raffleId, rafflePDA, bob)
+      await cancelRaffle(raffleId, rafflePDA)
+
+      try {
+        await program.methods.pickWinner(raffleId)
+          .accounts({
+            caller: wallet.publicKey,
+            raffle: rafflePDA
+          })
+          .signers([wallet.payer])
+          .rpc();
+        assert.fail("Reason: raffle is cancelled");
+      } catch (error) {
+        assert.include(error.toString(), "RaffleCancelled")
+      }
+    });
+
+    it("Should crank refunds for a batch of tickets exactly once", async () => {
+      const raffleId = await nextRaffleID()
+      const rafflePDA = await createTimedRaffle(raffleId, 60)
+
+      await airdrop(alice.publicKey)
+      await buyTicket(raffleId, rafflePDA, alice)
+      await airdrop(bob.publicKey)
+      await buyTicket(raffleId, rafflePDA, bob)
+      await cancelRaffle(raffleId, rafflePDA)
+
+      const remainingAccounts = [
+        { pubkey: getTicketPDA(rafflePDA, 1), isWritable: true, isSigner: false },
+        { pubkey: alice.publicKey, isWritable: true, isSigner: false },
+        { pubkey: getTicketPDA(rafflePDA, 2), isWritable: true, isSigner: false },
+        { pubkey: bob.publicKey, isWritable: true, isSigner: false },
+      ]
+
+      await airdrop(john.publicKey)
+      const bobBalanceBefore = await connection.getBalance(bob.publicKey)
+      await program.methods.crankRefunds(raffleId)
+        .accountsPartial({
+          caller: john.publicKey,
+          raffle: rafflePDA
+        })
+        .remainingAccounts(remainingAccounts)
+        .signers([john])
+        .rpc();
+      const bobBalanceAfter = await connection.getBalance(bob.publicKey)
+      assert.isTrue(bobBalanceAfter - bobBalanceBefore >= 100_000_000)
+      assert.isNull(await connection.getAccountInfo(getTicketPDA(rafflePDA, 1)))
+
+      try {
+        await program.methods.crankRefunds(raffleId)
+          .accountsPartial({
+            caller: john.publicKey,
+            raffle: rafflePDA
+          })
+          .remainingAccounts(remainingAccounts)
+          .signers([john])
+          .rpc();
+        assert.fail("Reason: tickets already refunded");
+      } catch (error) {
+        assert.ok(error)
+      }
+    });
+
+    it("Should let anyone close the accounts left by a settled raffle", async () => {
+      const raffleId = await nextRaffleID()
+      const rafflePDA = await createTimedRaffle(raffleId, 60)
+
+      await airdrop(alice.publicKey)
+      await buyTicket(raffleId, rafflePDA, alice)
+      await cancelRaffle(raffleId, rafflePDA)
+      await refundTicket(raffleId, rafflePDA, getTicketPDA(rafflePDA, 1), alice)
+
+      await airdrop(john.publicKey)
+      await program.methods.crankCloseAccounts(raffleId)
+        .accountsPartial({
+          caller: john.publicKey,
+          raffle: rafflePDA
+        })
+        .remainingAccounts([
+          { pubkey: getParticipantPDA(rafflePDA, alice.publicKey), isWritable: true, isSigner: false },
+          { pubkey: alice.publicKey, isWritable: true, isSigner: false },
+        ])
+        .signers([john])
+        .rpc();
+      assert.isNull(await connection.getAccountInfo(getParticipantPDA(rafflePDA, alice.publicKey)))
+
+      await closeRaffle(raffleId, rafflePDA)
+      assert.isNull(await connection.getAccountInfo(rafflePDA))
+    });
+  });
+
+  describe("Close Accounts", async () => {
+    it("Should only close tickets that no longer back an unclaimed prize", async () => {
+      const raffleId = await nextRaffleID()
+      const rafflePDA = getRafflePDA(wallet.publicKey, raffleId)
+      await program.methods.createRaffle(raffleId, raffleArgs(100_000_000, 2))
+        .accounts({
+          owner: wallet.publicKey
+        })
+        .signers([wallet.payer])
+        .rpc();
+
+      await airdrop(alice.publicKey)
+      await buyTicket(raffleId, rafflePDA, alice)
+      await airdrop(bob.publicKey)
+      await buyTicket(raffleId, rafflePDA, bob)
+      await program.methods.pickWinner(raffleId)
+        .accounts({
+          caller: wallet.publicKey,
+          raffle: rafflePDA
+        })
+        .signers([wallet.payer])
+        .rpc();
+
+      const raffleAccount = await program.account.raffle.fetch(rafflePDA);
+      const winningTicketID = raffleAccount.winners[0].ticketId
+      const losingTicketID = winningTicketID == 1 ? 2 : 1
+      const winner = winningTicketID == 1 ? alice : bob
+      const loser = winningTicketID == 1 ? bob : alice
+
+      try {
+        await closeTicket(raffleId, rafflePDA, getTicketPDA(rafflePDA, winningTicketID), winner)
+        assert.fail("Reason: prize is unclaimed");
+      } catch (error) {
+        assert.include(error.toString(), "PrizeUnclaimed")
+      }
+
+      await closeTicket(raffleId, rafflePDA, getTicketPDA(rafflePDA, losingTicketID), loser)
+      assert.isNull(await connection.getAccountInfo(getTicketPDA(rafflePDA, losingTicketID)))
+
+      await claimPrize(raffleId, rafflePDA, getTicketPDA(rafflePDA, winningTicketID), winner)
+      await closeTicket(raffleId, rafflePDA, getTicketPDA(rafflePDA, winningTicketID), winner)
+      assert.isNull(await connection.getAccountInfo(getTicketPDA(rafflePDA, winningTicketID)))
+    });
+
+    it("Should fail to close a ticket of an open raffle", async () => {
+      const raffleId = await nextRaffleID()
+      const rafflePDA = getRafflePDA(wallet.publicKey, raffleId)
+      await program.methods.createRaffle(raffleId, raffleArgs(100_000_000, 2))
+        .accounts({
+          owner: wallet.publicKey
+        })
+        .signers([wallet.payer])
+        .rpc();
+
+      await airdrop(alice.publicKey)
+      await buyTicket(raffleId, rafflePDA, alice)
+      try {
+        await closeTicket(raffleId, rafflePDA, getTicketPDA(rafflePDA, 1), alice)
+        assert.fail("Reason: raffle is still open");
+      } catch (error) {
+        assert.ok(error)
+      }
+    });
+
+    it("Should only close a raffle once every payout is done", async () => {
+      const raffleId = await nextRaffleID()
+      const rafflePDA = await createTimedRaffle(raffleId, 60)
+
+      await airdrop(alice.publicKey)
+      await buyTicket(raffleId, rafflePDA, alice)
+      await cancelRaffle(raffleId, rafflePDA)
+
+      try {
+        await closeRaffle(raffleId, rafflePDA)
+        assert.fail("Reason: ticket is not refunded yet");
+      } catch (error) {
+        assert.include(error.toString(), "RaffleNotSettled")
+      }
+
+      // closing a ticket of a cancelled raffle refunds it first
+      const aliceBalanceBefore = await connection.getBalance(alice.publicKey)
+      await closeTicket(raffleId, rafflePDA, getTicketPDA(rafflePDA, 1), alice)
+      const aliceBalanceAfter = await connection.getBalance(alice.publicKey)
+      assert.isTrue(aliceBalanceAfter - aliceBalanceBefore >= 100_000_000)
+
+      try {
+        await closeRaffle(raffleId, rafflePDA)
+        assert.fail("Reason: participant is still open");
+      } catch (error) {
+        assert.include(error.toString(), "OpenAccountsRemaining")
+      }
+
+      await closeParticipant(raffleId, rafflePDA, alice)
+      await closeRaffle(raffleId, rafflePDA)
+      assert.isNull(await connection.getAccountInfo(rafflePDA))
+    });
+  });
+
+  describe("Events", async () => {
+    it("Should emit events for the lifecycle of a drawn raffle", async () => {
+      const raffleId = await nextRaffleID()
+      const rafflePDA = getRafflePDA(wallet.publicKey, raffleId)
+      const created = await getEvents(
+        await program.methods.createRaffle(raffleId, raffleArgs(100_000_000, 3))
+          .accounts({
+            owner: wallet.publicKey
+          })
+          .signers([wallet.payer])
+          .rpc()
+      )
+      assert.equal(created[0].name, "raffleCreated")
+      assert.equal(created[0].data.raffle.toBase58(), rafflePDA.toBase58())
+      assert.equal(created[0].data.raffleId, raffleId)
+      assert.equal(created[0].data.ticketPrice.toNumber(), 100_000_000)
+      assert.equal(created[0].data.maxEntries, 3)
+      assert.isAbove(created[0].data.timestamp.toNumber(), 0)
+
+      await airdrop(alice.publicKey)
+      const single = await getEvents(
+        await program.methods.buyTicket(raffleId, [])
+          .accountsPartial({
+            buyer: alice.publicKey,
+            raffle: rafflePDA,
+            ticket: getTicketPDA(rafflePDA, 1),
+          })
+          .signers([alice])
+          .rpc()
+      )
+      assert.equal(single[0].name, "ticketPurchased")
+      assert.equal(single[0].data.buyer.toBase58(), alice.publicKey.toBase58())
+      assert.equal(single[0].data.firstTicketId, 1)
+      assert.equal(single[0].data.quantity, 1)
+      assert.equal(single[0].data.amount.toNumber(), 100_000_000)
+
+      await airdrop(bob.publicKey)
+      const bulk = await getEvents(
+        await program.methods.buyTickets(raffleId, 2, [])
+          .accountsPartial({
+            buyer: bob.publicKey,
+            raffle: rafflePDA,
+            registryPage: await getSellOutRegistryPage(rafflePDA, 2),
+          })
+          .signers([bob])
+          .rpc()
+      )
+      assert.equal(bulk[0].name, "ticketPurchased")
+      assert.equal(bulk[0].data.firstTicketId, 2)
+      assert.equal(bulk[0].data.quantity, 2)
+      assert.equal(bulk[0].data.amount.toNumber(), 200_000_000)
+      assert.equal(bulk[0].data.entries, 3)
+
+      const picked = await getEvents(
+        await program.methods.pickWinner(raffleId)
+          .accounts({
+            caller: wallet.publicKey,
+            raffle: rafflePDA
+          })
+          .signers([wallet.payer])
+          .rpc()
+      )
+      assert.equal(picked[0].name, "winnerPicked")
+      assert.equal(picked[0].data.winningTicketIds.length, 1)
+      assert.equal(picked[0].data.entries, 3)
+
+      const winningTicketID = picked[0].data.winningTicketIds[0]
+      const winner = winningTicketID == 1 ? alice : bob
+      const claimAccounts: Record<string, any> = {
+        winner: winner.publicKey,
+        raffle: rafflePDA,
+      }
+      if (winningTicketID == 1) {
+        claimAccounts.ticket = getTicketPDA(rafflePDA, 1)
+      } else {
+        claimAccounts.ticket = null
+        claimAccounts.entry = getEntryPDA(rafflePDA, bob.publicKey)
+      }
+      const claimed = await getEvents(
+        await program.methods.claimPrize(raffleId)
+          .accountsPartial(claimAccounts)
+          .signers([winner])
+          .rpc()
+      )
+      assert.equal(claimed[0].name, "prizeClaimed")
+      assert.equal(claimed[0].data.ticketId, winningTicketID)
+      assert.equal(claimed[0].data.winner.toBase58(), winner.publicKey.toBase58())
+      assert.equal(claimed[0].data.winnerAmount.toNumber(), 300_000_000)
+    });
+
+    it("Should emit events when a raffle is cancelled and refunded", async () => {
+      const raffleId = await nextRaffleID()
+      const rafflePDA = await createTimedRaffle(raffleId, 60)
+
+      await airdrop(alice.publicKey)
+      await buyTicket(raffleId, rafflePDA, alice)
+      const cancelled = await getEvents(
+        await program.methods.cancelRaffle(raffleId)
+          .accountsPartial({
+            owner: wallet.publicKey,
+            raffle: rafflePDA,
+            registryPage: await getListedRegistryPage(rafflePDA)
+          })
+          .signers([wallet.payer])
+          .rpc()
+      )
+      assert.equal(cancelled[0].name, "raffleCancelled")
+      assert.equal(cancelled[0].data.entries, 1)
+
+      const refunded = await getEvents(
+        await program.methods.refundTicket(raffleId)
+          .accountsPartial({
+            owner: alice.publicKey,
+            raffle: rafflePDA,
+            ticket: getTicketPDA(rafflePDA, 1)
+          })
+          .signers([alice])
+          .rpc()
+      )
+      assert.equal(refunded[0].name, "refunded")
+      assert.equal(refunded[0].data.owner.toBase58(), alice.publicKey.toBase58())
+      assert.equal(refunded[0].data.ticketId, 1)
+      assert.equal(refunded[0].data.quantity, 1)
+      assert.equal(refunded[0].data.amount.toNumber(), 100_000_000)
+    });
+  });
+
+  async function getEvents(signature: string) {
+    await connection.confirmTransaction(signature, "confirmed")
+    const tx = await connection.getTransaction(signature, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0
+    })
+    const parser = new anchor.EventParser(program.programId, program.coder)
+    return Array.from(parser.parseLogs(tx.meta.logMessages))
+  }
+
+  async function closeTicket(
+    raffleID: string,
+    raffle: anchor.web3.PublicKey,
+    ticket: anchor.web3.PublicKey,
+    owner: anchor.web3.Keypair
+  ) {
+    await program.methods.closeTicket(raffleID)
+      .accountsPartial({
+        owner: owner.publicKey,
+        raffle,
+        ticket
+      })
+      .signers([owner])
+      .rpc();
+  }
+
+  async function closeParticipant(
+    raffleID: string,
+    raffle: anchor.web3.PublicKey,
+    owner: anchor.web3.Keypair
+  ) {
+    await program.methods.closeParticipant(raffleID)
+      .accountsPartial({
+        owner: owner.publicKey,
+        raffle
+      })
+      .signers([owner])
+      .rpc();
+  }
+
+  async function closeRaffle(raffleID: string, raffle: anchor.web3.PublicKey) {
+    await program.methods.closeRaffle(raffleID)
+      .accountsPartial({
+        owner: wallet.publicKey,
+        raffle,
+        registryPage: await getListedRegistryPage(raffle)
+      })
+      .signers([wallet.payer])
+      .rpc();
+  }
+
+  async function cancelRaffle(raffleID: string, raffle: anchor.web3.PublicKey) {
+    await program.methods.cancelRaffle(raffleID)
+      .accountsPartial({
+        owner: wallet.publicKey,
+        raffle,
+        registryPage: await getListedRegistryPage(raffle)
+      })
+      .signers([wallet.payer])
+      .rpc();
+  }
+
+  async function createTimedRaffle(raffleId: string, secondsFromNow: number) {
+    const endTime = new anchor.BN(Math.floor(Date.now() / 1000) + secondsFromNow)
+    await program.methods.createRaffle(raffleId, raffleArgs(100_000_000, 2, { endTime }))
+      .accounts({
+        owner: wallet.publicKey
+      })
+      .signers([wallet.payer])
+      .rpc();
+
+    return getRafflePDA(wallet.publicKey, raffleId)
+  }
+
+  async function claimPrize(
+    raffleID: string,
+    raffle: anchor.web3.PublicKey,
+    ticket: anchor.web3.PublicKey,
+    winner: anchor.web3.Keypair
+  ) {
+    await program.methods.claimPrize(raffleID)
+      .accountsPartial({
+        winner: winner.publicKey,
+        ticket,
+        raffle
+      })
+      .signers([winner])
+      .rpc();
+  }
+
+  async function updateConfig(
+    admin: anchor.web3.PublicKey,
+    treasuryKey: anchor.web3.PublicKey,
+    protocolFeeBps: number
+  ) {
+    await program.methods.updateConfig(admin, treasuryKey, protocolFeeBps)
+      .accounts({
+        admin: wallet.publicKey
+      })
+      .signers([wallet.payer])
+      .rpc();
+  }
+
+  async function expireRaffle(raffleID: string, raffle: anchor.web3.PublicKey) {
+    await program.methods.expireRaffle(raffleID)
+      .accountsPartial({
+        caller: john.publicKey,
+        raffle,
+        registryPage: await getListedRegistryPage(raffle)
+      })
+      .signers([john])
+      .rpc();
+  }
+
+  async function refundTicket(
+    raffleID: string,
+    raffle: anchor.web3.PublicKey,
+    ticket: anchor.web3.PublicKey,
+    owner: anchor.web3.Keypair
+  ) {
+    await program.methods.refundTicket(raffleID)
+      .accountsPartial({
+        owner: owner.publicKey,
+        raffle,
+        ticket
+      })
+      .signers([owner])
+      .rpc();
+  }
+
+  describe("Reveal Winner", async () => {
+    it("Should fail if pick winner is used on a commit-reveal raffle", async () => {
+      const raffleId = await nextRaffleID()
+      const secret = randomBytes(32)
+      const rafflePDA = await createCommittedRaffle(raffleId, secret)
+
+      try {
+        await program.methods.pickWinner(raffleId)
+          .accounts({
+            caller: wallet.publicKey,
+            raffle: rafflePDA
+          })
+          .signers([wallet.payer])
+          .rpc();
+
+        assert.fail("Reason: raffle requires a reveal");
+      } catch (error) {
+        assert.include(error.toString(), "RevealRequired")
+      }
+    });
+
+    it("Should fail if the revealed secret does not match the commitment", async () => {
+      const raffleId = await nextRaffleID()
+      const secret = randomBytes(32)
+      const rafflePDA = await createCommittedRaffle(raffleId, secret)
+
+      try {
+        await program.methods.revealWinner(raffleId, Array.from(randomBytes(32)))
+          .accounts({
+            owner: wallet.publicKey,
+            raffle: rafflePDA
+          })
+          .signers([wallet.payer])
+          .rpc();
+
+        assert.fail("Reason: secret does not match commitment");
+      } catch (error) {
+        assert.include(error.toString(), "InvalidReveal")
+      }
+    });
+
+    it("Should fail to expire the reveal before the timeout", async () => {
+      const raffleId = await nextRaffleID()
+      const secret = randomBytes(32)
+      const rafflePDA = await createCommittedRaffle(raffleId, secret)
+
+      try {
+        await program.methods.expireReveal(raffleId)
+          .accountsPartial({
+            caller: alice.publicKey,
+            raffle: rafflePDA
+          })
+          .signers([alice])
+          .rpc();
+
+        assert.fail("Reason: reveal timeout not reached");
+      } catch (error) {
+        assert.include(error.toString(), "RevealTimeoutNotReached")
+      }
+    });
+
+    it("Should pass if the owner reveals the committed secret", async () => {
+      const raffleId = await nextRaffleID()
+      const secret = randomBytes(32)
+      const rafflePDA = await createCommittedRaffle(raffleId, secret)
+
+      await program.methods.revealWinner(raffleId, Array.from(secret))
+        .accounts({
+          owner: wallet.publicKey,
+          raffle: rafflePDA
+        })
+        .signers([wallet.payer])
+        .rpc();
+
+      const raffleAccount = await program.account.raffle.fetch(rafflePDA);
+      assert.deepEqual(raffleAccount.status, { drawn: {} })
+      assert.isTrue(raffleAccount.winners[0].ticketId >= 1 && raffleAccount.winners[0].ticketId <= 2)
+    });
+  });
+
+  describe("Consume Randomness", async () => {
+    it("Should fail if pick winner is used on an oracle raffle", async () => {
+      const raffleId = await nextRaffleID()
+      const oracle = await writeRandomness(Array(32).fill(0))
+      const rafflePDA = await createOracleRaffle(raffleId, oracle.publicKey)
+
+      try {
+        await program.methods.pickWinner(raffleId)
+          .accounts({
+            caller: wallet.publicKey,
+            raffle: rafflePDA
+          })
+          .signers([wallet.payer])
+          .rpc();
+
+        assert.fail("Reason: raffle requires oracle randomness");
+      } catch (error) {
+        assert.include(error.toString(), "OracleRequired")
+      }
+    });
+
+    it("Should fail if the oracle account does not match the raffle config", async () => {
+      const raffleId = await nextRaffleID()
+      const oracle = await writeRandomness(Array(32).fill(0))
+      const otherOracle = await writeRandomness(Array(32).fill(1))
+      const rafflePDA = await createOracleRaffle(raffleId, oracle.publicKey)
+
+      try {
+        await program.methods.consumeRandomness(raffleId)
+          .accountsPartial({
+            caller: wallet.publicKey,
+            raffle: rafflePDA,
+            oracle: otherOracle.publicKey
+          })
+          .signers([wallet.payer])
+          .rpc();
+
+        assert.fail("Reason: oracle account is not the configured one");
+      } catch (error) {
+        assert.include(error.toString(), "InvalidOracleAccount")
+      }
+    });
+
+    it("Should pick the winner from the oracle randomness", async () => {
+      const raffleId = await nextRaffleID()
+      // first 8 bytes decode to 1 -> ticket (1 % 2) + 1
+      const randomness = Array(32).fill(0)
+      randomness[0] = 1
+      // the value has to be published after the last ticket is bought
+      const oracle = anchor.web3.Keypair.generate()
+      const rafflePDA = await createOracleRaffle(raffleId, oracle.publicKey)
+      await writeRandomness(randomness, oracle)
+
+      // consuming the oracle value is permissionless
+      await program.methods.consumeRandomness(raffleId)
+        .accountsPartial({
+          caller: alice.publicKey,
+          raffle: rafflePDA,
+          oracle: oracle.publicKey
+        })
+        .signers([alice])
+        .rpc();
+
+      const raffleAccount = await program.account.raffle.fetch(rafflePDA);
+      assert.deepEqual(raffleAccount.status, { drawn: {} })
+      assert.equal(raffleAccount.winners[0].ticketId, 2)
+    });
+  });
+
+  describe("Token Raffle", async () => {
+    it("Should fail if token accounts are missing when buying a token ticket", async () => {
+      const raffleId = await nextRaffleID()
+      const { mint, rafflePDA } = await createTokenRaffle(raffleId)
+
+      await airdrop(alice.publicKey)
+      try {
+        const raffleAccount = await program.account.raffle.fetch(rafflePDA);
+        await program.methods.buyTicket(raffleId, [])
+          .accountsPartial({
+            buyer: alice.publicKey,
+            raffle: rafflePDA,
+            ticket: getTicketPDA(rafflePDA, raffleAccount.entries + 1),
+            ticketMint: mint,
+            buyerTokenAccount: null,
+            escrow: null,
+            tokenProgram: null,
+          })
+          .signers([alice])
+          .rpc();
+
+        assert.fail("Reason: token accounts are required");
+      } catch (error) {
+        assert.include(error.toString(), "TokenAccountsRequired")
+      }
+    });
+
+    it("Should pay tickets into the escrow and the prize out in tokens", async () => {
+      const raffleId = await nextRaffleID()
+      const { mint, rafflePDA, escrow } = await createTokenRaffle(raffleId)
+
+      await airdrop(alice.publicKey)
+      await airdrop(bob.publicKey)
+      const aliceTokens = await fundTokenAccount(mint, alice.publicKey, 5_000_000)
+      const bobTokens = await fundTokenAccount(mint, bob.publicKey, 5_000_000)
+
+      await buyTokenTicket(raffleId, rafflePDA, mint, alice, aliceTokens)
+      await buyTokenTicket(raffleId, rafflePDA, mint, bob, bobTokens)
+
+      const escrowAccount = await getAccount(connection, escrow)
+      assert.equal(Number(escrowAccount.amount), 2_000_000)
+
+      await program.methods.pickWinner(raffleId)
+        .accounts({
+          caller: wallet.publicKey,
+          raffle: rafflePDA
+        })
+        .signers([wallet.payer])
+        .rpc();
+
+      const raffleAccount = await program.account.raffle.fetch(rafflePDA);
+      const ticketPDA = getTicketPDA(rafflePDA, raffleAccount.winners[0].ticketId);
+      const ticketAccount = await program.account.ticket.fetch(ticketPDA);
+      const isAlice = ticketAccount.owner.toBase58() == alice.publicKey.toBase58();
+      const signer = isAlice ? alice : bob;
+      const winnerTokens = isAlice ? aliceTokens : bobTokens;
+
+      await program.methods.claimPrize(raffleId)
+        .accountsPartial({
+          winner: signer.publicKey,
+          ticket: ticketPDA,
+          raffle: rafflePDA,
+          ticketMint: mint,
+          winnerTokenAccount: winnerTokens,
+          escrow,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([signer])
+        .rpc()
+
+      const winnerAccount = await getAccount(connection, winnerTokens)
+      assert.equal(Number(winnerAccount.amount), 6_000_000)
+    });
+  });
+
+  describe("Prize Asset", async () => {
+    it("Should escrow the prize NFT and send it to the winner", async () => {
+      const raffleId = await nextRaffleID()
+      const { prizeMint, rafflePDA, prizeVault } = await createPrizeRaffle(raffleId)
+
+      const vaultAccount = await getAccount(connection, prizeVault)
+      assert.equal(Number(vaultAccount.amount), 1)
+
+      await airdrop(alice.publicKey)
+      await buyTicket(raffleId, rafflePDA, alice)
+      await airdrop(bob.publicKey)
+      await buyTicket(raffleId, rafflePDA, bob)
+      await program.methods.pickWinner(raffleId)
+        .accounts({
+          caller: wallet.publicKey,
+          raffle: rafflePDA
+        })
+        .signers([wallet.payer])
+        .rpc();
+
+      const raffleAccount = await program.account.raffle.fetch(rafflePDA);
+      const ticketPDA = getTicketPDA(rafflePDA, raffleAccount.winners[0].ticketId);
+      const ticketAccount = await program.account.ticket.fetch(ticketPDA);
+      const signer = ticketAccount.owner.equals(alice.publicKey) ? alice : bob;
+      const winnerPrizeAccount = await fundTokenAccount(prizeMint, signer.publicKey, 0)
+
+      const ownerBalanceBefore = await connection.getBalance(wallet.publicKey)
+      await program.methods.claimPrize(raffleId)
+        .accountsPartial({
+          winner: signer.publicKey,
+          ticket: ticketPDA,
+          raffle: rafflePDA,
+          prizeMint,
+          winnerPrizeAccount,
+          prizeVault,
+          prizeTokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([signer])
+        .rpc();
+      const ownerBalanceAfter = await connection.getBalance(wallet.publicKey)
+
+      const winnerAccount = await getAccount(connection, winnerPrizeAccount)
+      assert.equal(Number(winnerAccount.amount), 1)
+      // ticket proceeds go to the creator
+      assert.isTrue(ownerBalanceAfter - ownerBalanceBefore >= 200_000_000)
+      assert.isNull(await connection.getAccountInfo(prizeVault))
+    });
+
+    it("Should fail to claim an escrowed prize without the prize accounts", async () => {
+      const raffleId = await nextRaffleID()
+      const { rafflePDA } = await createPrizeRaffle(raffleId)
+
+      await airdrop(alice.publicKey)
+      await buyTicket(raffleId, rafflePDA, alice)
+      await airdrop(bob.publicKey)
+      await buyTicket(raffleId, rafflePDA, bob)
+      await program.methods.pickWinner(raffleId)
+        .accounts({
+          caller: wallet.publicKey,
+          raffle: rafflePDA
+        })
+        .signers([wallet.payer])
+        .rpc();
+
+      const raffleAccount = await program.account.raffle.fetch(rafflePDA);
+      const ticketPDA = getTicketPDA(rafflePDA, raffleAccount.winners[0].ticketId);
+      const ticketAccount = await program.account.ticket.fetch(ticketPDA);
+      const signer = ticketAccount.owner.equals(alice.publicKey) ? alice : bob;
+      try {
+        await claimPrize(raffleId, rafflePDA, ticketPDA, signer)
+        assert.fail("Reason: prize accounts are required");
+      } catch (error) {
+        assert.include(error.toString(), "PrizeAccountsRequired")
+      }
+    });
+
+    it("Should let the creator withdraw the proceeds before the winner claims", async () => {
+      const raffleId = await nextRaffleID()
+      const { rafflePDA } = await createPrizeRaffle(raffleId)
+
+      await airdrop(alice.publicKey)
+      await buyTicket(raffleId, rafflePDA, alice)
+      await airdrop(bob.publicKey)
+      await buyTicket(raffleId, rafflePDA, bob)
+      await program.methods.pickWinner(raffleId)
+        .accounts({
+          caller: wallet.publicKey,
+          raffle: rafflePDA
+        })
+        .signers([wallet.payer])
+        .rpc();
+
+      const withdrawProceeds = () => program.methods.withdrawProceeds(raffleId)
+        .accountsPartial({
+          owner: wallet.publicKey,
+          raffle: rafflePDA
+        })
+        .signers([wallet.payer])
+        .rpc();
+
+      const ownerBalanceBefore = await connection.getBalance(wallet.publicKey)
+      await withdrawProceeds()
+      const ownerBalanceAfter = await connection.getBalance(wallet.publicKey)
+      assert.isTrue(ownerBalanceAfter - ownerBalanceBefore >= 190_000_000)
+      assert.isTrue((await program.account.raffle.fetch(rafflePDA)).proceedsWithdrawn)
+
+      try {
+        await withdrawProceeds()
+        assert.fail("Reason: proceeds were already withdrawn");
+      } catch (error) {
+        assert.include(error.toString(), "NoProceedsToWithdraw")
+      }
+    });
+
+    it("Should return the prize to the creator when the raffle is cancelled", async () => {
+      const raffleId = await nextRaffleID()
+      const { prizeMint, rafflePDA, prizeVault, ownerPrizeAccount } = await createPrizeRaffle(raffleId)
+
+      await cancelRaffle(raffleId, rafflePDA)
+      await program.methods.reclaimPrize(raffleId)
+        .accountsPartial({
+          owner: wallet.publicKey,
+          raffle: rafflePDA,
+          prizeMint,
+          ownerPrizeAccount,
+          prizeVault,
+          prizeTokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([wallet.payer])
+        .rpc();
+
+      const ownerAccount = await getAccount(connection, ownerPrizeAccount)
+      assert.equal(Number(ownerAccount.amount), 1)
+      assert.isNull(await connection.getAccountInfo(prizeVault))
+    });
+
+    it("Should fail to reclaim the prize of an open raffle", async () => {
+      const raffleId = await nextRaffleID()
+      const { prizeMint, rafflePDA, prizeVault, ownerPrizeAccount } = await createPrizeRaffle(raffleId)
+
+      try {
+        await program.methods.reclaimPrize(raffleId)
+          .accountsPartial({
+            owner: wallet.publicKey,
+            raffle: rafflePDA,
+            prizeMint,
+            ownerPrizeAccount,
+            prizeVault,
+            prizeTokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .signers([wallet.payer])
+          .rpc();
+        assert.fail("Reason: raffle is still open");
+      } catch (error) {
+        assert.include(error.toString(), "RaffleNotRefundable")
+      }
+    });
+  });
+
+  describe("Raffle Series", async () => {
+    it("Should start rounds from the series template", async () => {
+      const seriesId = generateRaffleID()
+      const seriesPDA = await createSeries(seriesId, seriesArgs({ creatorFeeBps: 500 }))
+
+      await startNextRound(seriesId)
+
+      const roundPDA = getRafflePDA(wallet.publicKey, `${seriesId}-1`)
+      const round = await program.account.raffle.fetch(roundPDA)
+      const series = await program.account.raffleSeries.fetch(seriesPDA)
+      assert.equal(round.raffleId, `${seriesId}-1`)
+      assert.equal(round.ticketPrice.toNumber(), 100_000_000)
+      assert.equal(round.maxEntries, 2)
+      assert.equal(round.creatorFeeBps, 500)
+      assert.isNotNull(round.endTime)
+      assert.deepEqual(round.status, { open: {} })
+      assert.equal(series.rounds, 1)
+    });
+
+    it("Should fail to start a round while the latest round is running", async () => {
+      const seriesId = generateRaffleID()
+      await createSeries(seriesId, seriesArgs())
+      await startNextRound(seriesId)
+
+      try {
+        await startNextRound(seriesId, { previousRound: getRafflePDA(wallet.publicKey, `${seriesId}-1`) })
+        assert.fail("Reason: the first round is still open");
+      } catch (error) {
+        assert.include(error.toString(), "RoundNotFinished")
+      }
+    });
+
+    it("Should start the next round once the latest round is drawn", async () => {
+      const seriesId = generateRaffleID()
+      await createSeries(seriesId, seriesArgs())
+      await startNextRound(seriesId)
+      const firstRound = getRafflePDA(wallet.publicKey, `${seriesId}-1`)
+
+      await airdrop(alice.publicKey)
+      await buyTicket(`${seriesId}-1`, firstRound, alice)
+      await airdrop(bob.publicKey)
+      await buyTicket(`${seriesId}-1`, firstRound, bob)
+      await program.methods.pickWinner(`${seriesId}-1`)
+        .accounts({
+          caller: wallet.publicKey,
+          raffle: firstRound
+        })
+        .signers([wallet.payer])
+        .rpc();
+
+      await startNextRound(seriesId, { previousRound: firstRound })
+
+      const round = await program.account.raffle.fetch(getRafflePDA(wallet.publicKey, `${seriesId}-2`))
+      assert.equal(round.entries, 0)
+      assert.deepEqual(round.status, { open: {} })
+    });
+
+    it("Should roll the prize of a cancelled round into the next round", async () => {
+      const seriesId = generateRaffleID()
+      const prizeMint = await createMint(connection, wallet.payer, wallet.publicKey, null, 0)
+      const ownerPrizeAccount = await fundTokenAccount(prizeMint, wallet.publicKey, 2)
+      await createSeries(seriesId, seriesArgs({ prizeAmount: new anchor.BN(1) }), prizeMint)
+      const firstRound = getRafflePDA(wallet.publicKey, `${seriesId}-1`)
+      const secondRound = getRafflePDA(wallet.publicKey, `${seriesId}-2`)
+      const prizeAccounts = {
+        prizeMint,
+        ownerPrizeAccount,
+        prizeTokenProgram: TOKEN_PROGRAM_ID,
+      }
+
+      await startNextRound(seriesId, { ...prizeAccounts, prizeVault: getPrizeVaultPDA(firstRound) })
+      await cancelRaffle(`${seriesId}-1`, firstRound)
+      await startNextRound(seriesId, {
+        ...prizeAccounts,
+        previousRound: firstRound,
+        previousPrizeVault: getPrizeVaultPDA(firstRound),
+        prizeVault: getPrizeVaultPDA(secondRound),
+      }, true)
+
+      const round = await program.account.raffle.fetch(secondRound)
+      const vault = await getAccount(connection, getPrizeVaultPDA(secondRound))
+      assert.equal(round.prizeAmount.toNumber(), 2)
+      assert.equal(Number(vault.amount), 2)
+      assert.isNull(await connection.getAccountInfo(getPrizeVaultPDA(firstRound)))
+      const previous = await program.account.raffle.fetch(firstRound)
+      assert.equal(previous.prizeAmount.toNumber(), 0)
+    });
+
+    it("Should fail to roll the prize over from a round that was drawn", async () => {
+      const seriesId = generateRaffleID()
+      await createSeries(seriesId, seriesArgs())
+      await startNextRound(seriesId)
+      const firstRound = getRafflePDA(wallet.publicKey, `${seriesId}-1`)
+
+      await airdrop(alice.publicKey)
+      await buyTicket(`${seriesId}-1`, firstRound, alice)
+      await airdrop(bob.publicKey)
+      await buyTicket(`${seriesId}-1`, firstRound, bob)
+      await program.methods.pickWinner(`${seriesId}-1`)
+        .accounts({
+          caller: wallet.publicKey,
+          raffle: firstRound
+        })
+        .signers([wallet.payer])
+        .rpc();
+
+      try {
+        await startNextRound(seriesId, { previousRound: firstRound }, true)
+        assert.fail("Reason: the prize of a drawn round belongs to its winner");
+      } catch (error) {
+        assert.include(error.toString(), "NoPrizeToRoll")
+      }
+    });
+  });
+
+  describe("Pause", async () => {
+    it("Should fail if a non-admin pauses the protocol", async () => {
+      await airdrop(alice.publicKey)
+      try {
+        await program.methods.setProtocolPaused(true)
+          .accounts({
+            admin: alice.publicKey
+          })
+          .signers([alice])
+          .rpc();
+        assert.fail("Reason: unauthorized action");
+      } catch (error) {
+        assert.ok(error)
+      }
+    });
+
+    it("Should halt ticket sales but keep refunds open while the protocol is paused", async () => {
+      const raffleId = await nextRaffleID()
+      const rafflePDA = await createTimedRaffle(raffleId, 60)
+      await airdrop(alice.publicKey)
+      await buyTicket(raffleId, rafflePDA, alice)
+
+      await setProtocolPaused(true)
+      try {
+        try {
+          await airdrop(bob.publicKey)
+          await buyTicket(raffleId, rafflePDA, bob)
+          assert.fail("Reason: protocol is paused");
+        } catch (error) {
+          assert.include(error.toString(), "ProtocolPaused")
+        }
+
+        await cancelRaffle(raffleId, rafflePDA)
+        await refundTicket(raffleId, rafflePDA, getTicketPDA(rafflePDA, 1), alice)
+      } finally {
+        await setProtocolPaused(false)
+      }
+    });
+
+    it("Should halt ticket sales of a raffle paused by its owner", async () => {
+      const raffleId = await nextRaffleID()
+      const rafflePDA = await createTimedRaffle(raffleId, 60)
+
+      await setRafflePaused(raffleId, rafflePDA, true)
+      assert.isTrue((await program.account.raffle.fetch(rafflePDA)).paused)
+
+      await airdrop(alice.publicKey)
+      try {
+        await buyTicket(raffleId, rafflePDA, alice)
+        assert.fail("Reason: raffle is paused");
+      } catch (error) {
+        assert.include(error.toString(), "RafflePaused")
+      }
+
+      await setRafflePaused(raffleId, rafflePDA, false)
+      await buyTicket(raffleId, rafflePDA, alice)
+      const raffle = await program.account.raffle.fetch(rafflePDA)
+      assert.equal(raffle.entries, 1)
+    });
+
+    it("Should fail to pause a raffle once it ended", async () => {
+      const raffleId = await nextRaffleID()
+      const rafflePDA = await createTimedRaffle(raffleId, 3)
+
+      await airdrop(alice.publicKey)
+      await buyTicket(raffleId, rafflePDA, alice)
+      await sleep(4000)
+
+      try {
+        await setRafflePaused(raffleId, rafflePDA, true)
+        assert.fail("Reason: the raffle can be drawn");
+      } catch (error) {
+        assert.include(error.toString(), "RaffleNotActive")
+      }
+    });
+  });
+
+  describe("Ticket Transfer", async () => {
+    it("Should transfer a ticket to a new owner", async () => {
+      const raffleId = await nextRaffleID()
+      const rafflePDA = await createTimedRaffle(raffleId, 60)
+      await airdrop(alice.publicKey)
+      await buyTicket(raffleId, rafflePDA, alice)
+
+      await program.methods.transferTicket(raffleId, bob.publicKey)
+        .accountsPartial({
+          owner: alice.publicKey,
+          raffle: rafflePDA,
+          ticket: getTicketPDA(rafflePDA, 1),
+        })
+        .signers([alice])
+        .rpc();
+
+      const ticket = await program.account.ticket.fetch(getTicketPDA(rafflePDA, 1))
+      assert.equal(ticket.owner.toBase58(), bob.publicKey.toBase58())
+    });
+
+    it("Should fail if a non-owner transfers a ticket", async () => {
+      const raffleId = await nextRaffleID()
+      const rafflePDA = await createTimedRaffle(raffleId, 60)
+      await airdrop(alice.publicKey)
+      await buyTicket(raffleId, rafflePDA, alice)
+
+      await airdrop(bob.publicKey)
+      try {
+        await program.methods.transferTicket(raffleId, bob.publicKey)
+          .accountsPartial({
+            owner: bob.publicKey,
+            raffle: rafflePDA,
+            ticket: getTicketPDA(rafflePDA, 1),
+          })
+          .signers([bob])
+          .rpc();
+        assert.fail("Reason: unauthorized action");
+      } catch (error) {
+        assert.ok(error)
+      }
+    });
+
+    it("Should sell a listed ticket at the listing price", async () => {
+      const raffleId = await nextRaffleID()
+      const rafflePDA = await createTimedRaffle(raffleId, 60)
+      await airdrop(alice.publicKey)
+      await buyTicket(raffleId, rafflePDA, alice)
+      await listTicket(raffleId, rafflePDA, 1, alice, 200_000_000)
+
+      await airdrop(bob.publicKey)
+      try {
+        await buyListedTicket(raffleId, rafflePDA, 1, bob, alice.publicKey, 100_000_000)
+        assert.fail("Reason: listing price does not match");
+      } catch (error) {
+        assert.include(error.toString(), "ListingPriceChanged")
+      }
+
+      const balanceBefore = await connection.getBalance(alice.publicKey)
+      await buyListedTicket(raffleId, rafflePDA, 1, bob, alice.publicKey, 200_000_000)
+      const balanceAfter = await connection.getBalance(alice.publicKey)
+      assert.isTrue(balanceAfter - balanceBefore >= 200_000_000)
+
+      const ticketPDA = getTicketPDA(rafflePDA, 1)
+      const ticket = await program.account.ticket.fetch(ticketPDA)
+      assert.equal(ticket.owner.toBase58(), bob.publicKey.toBase58())
+      assert.isNull(await connection.getAccountInfo(getListingPDA(ticketPDA)))
+    });
+
+    it("Should close the listing of a transferred ticket", async () => {
+      const raffleId = await nextRaffleID()
+      const rafflePDA = await createTimedRaffle(raffleId, 60)
+      await airdrop(alice.publicKey)
+      await buyTicket(raffleId, rafflePDA, alice)
+      await listTicket(raffleId, rafflePDA, 1, alice, 200_000_000)
+
+      const ticketPDA = getTicketPDA(rafflePDA, 1)
+      await program.methods.transferTicket(raffleId, bob.publicKey)
+        .accountsPartial({
+          owner: alice.publicKey,
+          raffle: rafflePDA,
+          ticket: ticketPDA,
+        })
+        .signers([alice])
+        .rpc();
+      assert.isNull(await connection.getAccountInfo(getListingPDA(ticketPDA)))
+
+      // the new holder can list the ticket again
+      await airdrop(bob.publicKey)
+      await listTicket(raffleId, rafflePDA, 1, bob, 300_000_000)
+      const listing = await program.account.listing.fetch(getListingPDA(ticketPDA))
+      assert.equal(listing.seller.toBase58(), bob.publicKey.toBase58())
+    });
+  });
+
+  describe("Registry", async () => {
+    it("Should count and list raffles when they are created", async () => {
+      const registryPDA = getRegistryPDA(wallet.publicKey)
+      const before = await program.account.raffleRegistry.fetch(registryPDA)
+
+      const raffleId = await nextRaffleID()
+      const rafflePDA = await createTimedRaffle(raffleId, 60)
+
+      const registry = await program.account.raffleRegistry.fetch(registryPDA)
+      assert.equal(registry.rafflesCreated, before.rafflesCreated + 1)
+      assert.equal(raffleId, before.rafflesCreated.toString())
+      const raffle = await program.account.raffle.fetch(rafflePDA)
+      const page = await program.account.registryPage.fetch(getRegistryPagePDA(raffle.registryPage))
+      assert.isTrue(page.raffles.some((listed) => listed.equals(rafflePDA)))
+    });
+
+    it("Should fail to create a raffle out of sequence", async () => {
+      const raffleId = (Number(await nextRaffleID()) + 1).toString()
+      try {
+        await program.methods.createRaffle(raffleId, raffleArgs(100_000_000, 2))
+          .accountsPartial({
+            owner: wallet.publicKey,
+            registryPage: await getCurrentRegistryPage()
+          })
+          .signers([wallet.payer])
+          .rpc();
+        assert.fail("Reason: the id skips the registry counter");
+      } catch (error) {
+        assert.include(error.toString(), "InvalidRaffleId")
+      }
+    });
+
+    it("Should unlist a cancelled raffle", async () => {
+      const raffleId = await nextRaffleID()
+      const rafflePDA = await createTimedRaffle(raffleId, 60)
+      const pagePDA = await getListedRegistryPage(rafflePDA)
+
+      await cancelRaffle(raffleId, rafflePDA)
+
+      const raffle = await program.account.raffle.fetch(rafflePDA)
+      assert.isNull(raffle.registryPage)
+      const page = await program.account.registryPage.fetch(pagePDA)
+      assert.isFalse(page.raffles.some((listed) => listed.equals(rafflePDA)))
+    });
+
+    it("Should unlist a raffle once it sells out", async () => {
+      const raffleId = await nextRaffleID()
+      const rafflePDA = await createTimedRaffle(raffleId, 60)
+      const pagePDA = await getListedRegistryPage(rafflePDA)
+
+      await airdrop(alice.publicKey)
+      await buyTicket(raffleId, rafflePDA, alice)
+      await airdrop(bob.publicKey)
+      await buyTicket(raffleId, rafflePDA, bob)
+
+      const raffle = await program.account.raffle.fetch(rafflePDA)
+      assert.isNull(raffle.registryPage)
+      const page = await program.account.registryPage.fetch(pagePDA)
+      assert.isFalse(page.raffles.some((listed) => listed.equals(rafflePDA)))
+    });
+
+    it("Should list series rounds without counting them", async () => {
+      const registryPDA = getRegistryPDA(wallet.publicKey)
+      const before = await program.account.raffleRegistry.fetch(registryPDA)
+
+      const seriesId = generateRaffleID()
+      await createSeries(seriesId, seriesArgs())
+      await startNextRound(seriesId)
+
+      const registry = await program.account.raffleRegistry.fetch(registryPDA)
+      assert.equal(registry.rafflesCreated, before.rafflesCreated)
+      const roundPDA = getRafflePDA(wallet.publicKey, `${seriesId}-1`)
+      const page = await program.account.registryPage.fetch(await getListedRegistryPage(roundPDA))
+      assert.isTrue(page.raffles.some((listed) => listed.equals(roundPDA)))
+    });
+  });
+
+  describe("Metadata", async () => {
+    it("Should store metadata given at creation", async () => {
+      const raffleId = await nextRaffleID()
+      const rafflePDA = getRafflePDA(wallet.publicKey, raffleId)
+      await program.methods.createRaffle(raffleId, raffleArgs(100_000_000, 2, { metadata: metadataArgs() }))
+        .accountsPartial({
+          owner: wallet.publicKey,
+          metadata: getMetadataPDA(rafflePDA)
+        })
+        .signers([wallet.payer])
+        .rpc();
+
+      const metadata = await program.account.raffleMetadata.fetch(getMetadataPDA(rafflePDA))
+      assert.equal(metadata.title, "Weekly draw")
+      assert.deepEqual(metadata.category, { gaming: {} })
+      assert.deepEqual(metadata.tags, ["weekly", "sol"])
+    });
+
+    it("Should close the metadata along with the raffle", async () => {
+      const raffleId = await nextRaffleID()
+      const rafflePDA = getRafflePDA(wallet.publicKey, raffleId)
+      await program.methods.createRaffle(raffleId, raffleArgs(100_000_000, 2, { metadata: metadataArgs() }))
+        .accountsPartial({
+          owner: wallet.publicKey,
+          metadata: getMetadataPDA(rafflePDA)
+        })
+        .signers([wallet.payer])
+        .rpc();
+
+      await cancelRaffle(raffleId, rafflePDA)
+      await closeRaffle(raffleId, rafflePDA)
+      assert.isNull(await connection.getAccountInfo(getMetadataPDA(rafflePDA)))
+    });
+
+    it("Should fail if the title is too long", async () => {
+      const raffleId = await nextRaffleID()
+      const rafflePDA = await createTimedRaffle(raffleId, 60)
+      try {
+        await updateMetadata(raffleId, rafflePDA, metadataArgs({ title: "x".repeat(65) }))
+        assert.fail("Reason: title too long");
+      } catch (error) {
+        assert.include(error.toString(), "TitleTooLong")
+      }
+    });
+
+    it("Should fail to update metadata once a ticket is sold", async () => {
+      const raffleId = await nextRaffleID()
+      const rafflePDA = await createTimedRaffle(raffleId, 60)
+      await updateMetadata(raffleId, rafflePDA, metadataArgs())
+
+      await airdrop(alice.publicKey)
+      await buyTicket(raffleId, rafflePDA, alice)
+      try {
+        await updateMetadata(raffleId, rafflePDA, metadataArgs({ title: "Renamed" }))
+        assert.fail("Reason: tickets already sold");
+      } catch (error) {
+        assert.include(error.toString(), "MetadataLocked")
+      }
+    });
+  });
+
+  describe("Pricing", async () => {
+    it("Should raise the price of each ticket on a linear curve", async () => {
+      const raffleId = await nextRaffleID()
+      const rafflePDA = getRafflePDA(wallet.publicKey, raffleId)
+      const pricing = { linear: { increment: new anchor.BN(10_000_000) } }
+      await program.methods.createRaffle(raffleId, raffleArgs(100_000_000, 3, { pricing }))
+        .accountsPartial({
+          owner: wallet.publicKey,
+          registryPage: await getCurrentRegistryPage()
+        })
+        .signers([wallet.payer])
+        .rpc();
+
+      await airdrop(alice.publicKey)
+      await buyTicket(raffleId, rafflePDA, alice)
+      await buyTicket(raffleId, rafflePDA, alice)
+
+      const ticket = await program.account.ticket.fetch(getTicketPDA(rafflePDA, 2))
+      assert.equal(ticket.pricePaid.toNumber(), 110_000_000)
+      const raffle = await program.account.raffle.fetch(rafflePDA)
+      assert.equal(raffle.ticketSales.toNumber(), 210_000_000)
+    });
+  });
+
+  async function createSeries(
+    seriesId: string,
+    args: ReturnType<typeof seriesArgs>,
+    prizeMint: anchor.web3.PublicKey | null = null
+  ) {
+    const seriesPDA = getSeriesPDA(wallet.publicKey, seriesId)
+
+    await program.methods.createSeries(seriesId, args)
+      .accountsPartial({
+        owner: wallet.publicKey,
+        series: seriesPDA,
+        prizeMint,
+      })
+      .signers([wallet.payer])
+      .rpc();
+
+    return seriesPDA
+  }
+
+  async function startNextRound(
+    seriesId: string,
+    accounts: Record<string, anchor.web3.PublicKey> = {},
+    rollPrize: boolean = false
+  ) {
+    const seriesPDA = getSeriesPDA(wallet.publicKey, seriesId)
+    const series = await program.account.raffleSeries.fetch(seriesPDA)
+
+    await program.methods.startNextRound(seriesId, rollPrize)
+      .accountsPartial({
+        owner: wallet.publicKey,
+        series: seriesPDA,
+        round: getRafflePDA(wallet.publicKey, `${seriesId}-${series.rounds + 1}`),
+        previousRound: null,
+        registryPage: await getCurrentRegistryPage(),
+        ...accounts,
+      })
+      .signers([wallet.payer])
+      .rpc();
+  }
+
+  async function setProtocolPaused(paused: boolean) {
+    await program.methods.setProtocolPaused(paused)
+      .accounts({
+        admin: wallet.publicKey
+      })
+      .signers([wallet.payer])
+      .rpc();
+  }
+
+  async function setRafflePaused(raffleID: string, raffle: anchor.web3.PublicKey, paused: boolean) {
+    await program.methods.setRafflePaused(raffleID, paused)
+      .accountsPartial({
+        owner: wallet.publicKey,
+        raffle
+      })
+      .signers([wallet.payer])
+      .rpc();
+  }
+
+  async function listTicket(
+    raffleID: string,
+    raffle: anchor.web3.PublicKey,
+    ticketID: number,
+    owner: anchor.web3.Keypair,
+    price: number
+  ) {
+    await program.methods.listTicket(raffleID, new anchor.BN(price))
+      .accountsPartial({
+        owner: owner.publicKey,
+        raffle,
+        ticket: getTicketPDA(raffle, ticketID),
+      })
+      .signers([owner])
+      .rpc();
+  }
+
+  async function buyListedTicket(
+    raffleID: string,
+    raffle: anchor.web3.PublicKey,
+    ticketID: number,
+    buyer: anchor.web3.Keypair,
+    seller: anchor.web3.PublicKey,
+    price: number
+  ) {
+    await program.methods.buyListedTicket(raffleID, new anchor.BN(price))
+      .accountsPartial({
+        buyer: buyer.publicKey,
+        seller,
+        raffle,
+        ticket: getTicketPDA(raffle, ticketID),
+      })
+      .signers([buyer])
+      .rpc();
+  }
+
+  function getListingPDA(ticket: anchor.web3.PublicKey) {
+    const [listingPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        anchor.utils.bytes.utf8.encode("listing"),
+        ticket.toBuffer(),
+      ],
+      program.programId
+    );
+    return listingPDA
+  }
+
+  async function createPrizeRaffle(raffleId: string) {
+    // a zero decimal mint with a supply of one stands in for an NFT
+    const prizeMint = await createMint(connection, wallet.payer, wallet.publicKey, null, 0)
+    const ownerPrizeAccount = await fundTokenAccount(prizeMint, wallet.publicKey, 1)
+    const rafflePDA = getRafflePDA(wallet.publicKey, raffleId)
+    const prizeVault = getPrizeVaultPDA(rafflePDA)
+
+    await program.methods.createRaffle(raffleId, raffleArgs(100_000_000, 2, { prizeAmount: new anchor.BN(1) }))
+      .accountsPartial({
+        owner: wallet.publicKey,
+        raffle: rafflePDA,
+        prizeMint,
+        ownerPrizeAccount,
+        prizeVault,
+        prizeTokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([wallet.payer])
+      .rpc();
+
+    return { prizeMint, rafflePDA, prizeVault, ownerPrizeAccount }
+  }
+
+  async function createTokenRaffle(raffleId: string) {
+    const mint = await createMint(connection, wallet.payer, wallet.publicKey, null, 6)
+    const rafflePDA = getRafflePDA(wallet.publicKey, raffleId)
+    const escrow = getEscrowPDA(rafflePDA)
+
+    await program.methods.createRaffle(raffleId, raffleArgs(1_000_000, 2))
+      .accountsPartial({
+        owner: wallet.publicKey,
+        raffle: rafflePDA,
+        ticketMint: mint,
+        escrow,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([wallet.payer])
+      .rpc();
+
+    return { mint, rafflePDA, escrow }
+  }
+
+  async function fundTokenAccount(mint: anchor.web3.PublicKey, owner: anchor.web3.PublicKey, amount: number) {
+    const tokenAccount = await getOrCreateAssociatedTokenAccount(connection, wallet.payer, mint, owner)
+    await mintTo(connection, wallet.payer, mint, tokenAccount.address, wallet.payer, amount)
+    return tokenAccount.address
+  }
+
+  async function buyTokenTicket(
+    raffleID: string,
+    raffle: anchor.web3.PublicKey,
+    mint: anchor.web3.PublicKey,
+    buyer: anchor.web3.Keypair,
+    buyerTokenAccount: anchor.web3.PublicKey
+  ) {
+    const raffleAccount = await program.account.raffle.fetch(raffle);
+    const ticketID = raffleAccount.entries + 1;
+    await program.methods.buyTicket(raffleID, [])
+      .accountsPartial({
+        buyer: buyer.publicKey,
+        raffle,
+        ticket: getTicketPDA(raffle, ticketID),
+        registryPage: await getSellOutRegistryPage(raffle),
+        ticketMint: mint,
+        buyerTokenAccount,
+        escrow: getEscrowPDA(raffle),
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([buyer])
+      .rpc();
+  }
+
+  async function writeRandomness(
+    randomness: number[],
+    randomnessAccount = anchor.web3.Keypair.generate()
+  ) {
+    await mockOracle.methods.writeRandomness(randomness)
+      .accounts({
+        authority: wallet.publicKey,
+        randomnessAccount: randomnessAccount.publicKey
+      })
+      .signers([wallet.payer, randomnessAccount])
+      .rpc();
+
+    return randomnessAccount
+  }
+
+  async function createOracleRaffle(raffleId: string, oracle: anchor.web3.PublicKey) {
+    await program.methods.createRaffle(raffleId, raffleArgs(100_000_000, 2, {
+      randomnessSource: {
+        oracle: {
+          0: {
+            program: mockOracle.programId,
+            account: oracle,
+            randomnessOffset: 8,
+            slotOffset: 40,
+            maxStaleness: new anchor.BN(150)
+          }
+        }
+      }
+    }))
+      .accounts({
+        owner: wallet.publicKey
+      })
+      .signers([wallet.payer])
+      .rpc();
+
+    const rafflePDA = getRafflePDA(wallet.publicKey, raffleId)
+
+    await airdrop(alice.publicKey)
+    await buyTicket(raffleId, rafflePDA, alice)
+
+    await airdrop(bob.publicKey)
+    await buyTicket(raffleId, rafflePDA, bob)
+
+    return rafflePDA
+  }
+
+  async function createCommittedRaffle(raffleId: string, secret: Buffer) {
+    const commitment = Array.from(createHash("sha256").update(secret).digest())
+    await program.methods.createRaffle(raffleId, raffleArgs(100_000_000, 2, { randomnessSource: { commitReveal: { commitment } } }))
+      .accounts({
+        owner: wallet.publicKey
+      })
+      .signers([wallet.payer])
+      .rpc();
+
+    const rafflePDA = getRafflePDA(wallet.publicKey, raffleId)
+
+    await airdrop(alice.publicKey)
+    await buyTicket(raffleId, rafflePDA, alice)
+
+    await airdrop(bob.publicKey)
+    await buyTicket(raffleId, rafflePDA, bob)
+
+    return rafflePDA
+  }
+
+  function raffleArgs(ticketPrice: number, maxEntries: number, overrides: Record<string, any> = {}) {
+    return {
+      ticketPrice: new anchor.BN(ticketPrice),
+      pricing: { fixed: {} },
+      maxEntries,
+      minEntries: 0,
+      endTime: null,
+      randomnessSource: { slotHash: {} },
+      creatorFeeBps: 0,
+      prizeTiers: [10_000],
+      maxTicketsPerWallet: null,
+      allowlistRoot: null,
+      prizeAmount: new anchor.BN(0),
+      crankRewardBps: 0,
+      metadata: null,
+      ...overrides
+    }
+  }
+
+  function metadataArgs(overrides: Record<string, any> = {}) {
+    return {
+      title: "Weekly draw",
+      description: "One winner takes the ticket pool",
+      uri: "https://arweave.net/weekly.png",
+      category: { gaming: {} },
+      tags: ["weekly", "sol"],
+      ...overrides
+    }
+  }
+
+  function seriesArgs(overrides: Record<string, any> = {}) {
+    return {
+      ticketPrice: new anchor.BN(100_000_000),
+      maxEntries: 2,
+      roundDuration: new anchor.BN(7 * 24 * 60 * 60),
+      prizeAmount: new anchor.BN(0),
+      creatorFeeBps: 0,
+      prizeTiers: [10_000],
+      maxTicketsPerWallet: null,
+      crankRewardBps: 0,
+      ...overrides
+    }
+  }
+
+  function getSeriesPDA(owner: anchor.web3.PublicKey, seriesID: string) {
+    return anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("series"),
+        owner.toBuffer(),
+        Buffer.from(seriesID)
+      ],
+      program.programId
+    )[0]
+  }
+
+  // raffle ids follow the owner's registry counter
+  async function nextRaffleID(owner: anchor.web3.PublicKey = wallet.publicKey) {
+    const registry = await program.account.raffleRegistry.fetchNullable(getRegistryPDA(owner))
+    return (registry?.rafflesCreated ?? 0).toString()
+  }
+
+  function getRegistryPDA(owner: anchor.web3.PublicKey) {
+    return anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("registry"),
+        owner.toBuffer()
+      ],
+      program.programId
+    )[0]
+  }
+
+  function getRegistryPagePDA(page: number) {
+    const pageBuffer = Buffer.alloc(4)
+    pageBuffer.writeUInt32LE(page)
+    return anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("registry_page"),
+        pageBuffer
+      ],
+      program.programId
+    )[0]
+  }
+
+  async function getCurrentRegistryPage() {
+    const [globalRegistryPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("global_registry")],
+      program.programId
+    )
+    const globalRegistry = await program.account.globalRegistry.fetchNullable(globalRegistryPDA)
+    return getRegistryPagePDA(globalRegistry ? globalRegistry.currentPage : 0)
+  }
+
+  // registry page still listing the raffle, null once it was unlisted
+  async function getListedRegistryPage(raffle: anchor.web3.PublicKey) {
+    const { registryPage } = await program.account.raffle.fetch(raffle)
+    return registryPage === null ? null : getRegistryPagePDA(registryPage)
+  }
+
+  // only the purchase selling the raffle out takes it off the registry page
+  async function getSellOutRegistryPage(raffle: anchor.web3.PublicKey, quantity: number = 1) {
+    const { entries, maxEntries } = await program.account.raffle.fetch(raffle)
+    return entries + quantity === maxEntries ? getListedRegistryPage(raffle) : null
+  }
+
+  function getMetadataPDA(raffle: anchor.web3.PublicKey) {
+    return anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("metadata"),
+        raffle.toBuffer()
+      ],
+      program.programId
+    )[0]
+  }
+
+  async function updateMetadata(raffleID: string, raffle: anchor.web3.PublicKey, metadata: Record<string, any>) {
+    await program.methods.updateMetadata(raffleID, metadata)
+      .accountsPartial({
+        owner: wallet.publicKey,
+        raffle,
+        metadata: getMetadataPDA(raffle)
+      })
+      .signers([wallet.payer])
+      .rpc();
+  }
+
+  function getRafflePDA(owner: anchor.web3.PublicKey, raffleID: string) {
+    return anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("raffle"),
+        owner.toBuffer(),
+        Buffer.from(raffleID)
+      ],
+      program.programId
+    )[0]
+  }
+
+  function getTicketPDA(raffle: anchor.web3.PublicKey, ticketID: number) {
+    return anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("ticket"),
+        raffle.toBuffer(),
+        new anchor.BN(ticketID).toArrayLike(Buffer, "le", 4)
+      ],
+      program.programId
+    )[0]
+  }
+
+  function getEntryPDA(raffle: anchor.web3.PublicKey, owner: anchor.web3.PublicKey) {
+    return anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("entry"),
+        raffle.toBuffer(),
+        owner.toBuffer()
+      ],
+      program.programId
+    )[0]
+  }
+
+  function getPrizeVaultPDA(raffle: anchor.web3.PublicKey) {
+    return anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("prize_vault"),
+        raffle.toBuffer()
+      ],
+      program.programId
+    )[0]
+  }
+
+  function getParticipantPDA(raffle: anchor.web3.PublicKey, owner: anchor.web3.PublicKey) {
+    return anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("participant"),
+        raffle.toBuffer(),
+        owner.toBuffer()
+      ],
+      program.programId
+    )[0]
+  }
+
+  function getEscrowPDA(raffle: anchor.web3.PublicKey) {
+    return anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("escrow"),
+        raffle.toBuffer()
+      ],
       program.programId
     )[0]
   }
 
-  async function buyTicket(raffleID: string, raffle: anchor.web3.PublicKey, buyer: anchor.web3.Keypair) {
+  async function buyTicket(
+    raffleID: string,
+    raffle: anchor.web3.PublicKey,
+    buyer: anchor.web3.Keypair,
+    proof: number[][] = []
+  ) {
     const raffleAccount = await program.account.raffle.fetch(raffle);
     const ticketID = raffleAccount.entries + 1;
-    await program.methods.buyTicket(raffleID, ticketID)
+    await program.methods.buyTicket(raffleID, proof)
+      .accountsPartial({
+        buyer: buyer.publicKey,
+        raffle,
+        ticket: getTicketPDA(raffle, ticketID),
+        registryPage: await getSellOutRegistryPage(raffle),
+      })
+      .signers([buyer])
+      .rpc();
+  }
+
+  async function buyTickets(
+    raffleID: string,
+    raffle: anchor.web3.PublicKey,
+    buyer: anchor.web3.Keypair,
+    quantity: number,
+    proof: number[][] = []
+  ) {
+    await program.methods.buyTickets(raffleID, quantity, proof)
       .accountsPartial({
         buyer: buyer.publicKey,
         raffle,
+        registryPage: await getSellOutRegistryPage(raffle, quantity),
       })
       .signers([buyer])
       .rpc();
   }
 
+  // mirrors the raffle program's `merkle` module: sha256 leaves of the wallet
+  // keys, pairs hashed in sorted order and odd nodes carried up unchanged
+  function merkleLayers(wallets: anchor.web3.PublicKey[]) {
+    const layers = [wallets.map((key) => createHash("sha256").update(key.toBuffer()).digest())]
+    while (layers[layers.length - 1].length > 1) {
+      const layer = layers[layers.length - 1]
+      const next = []
+      for (let i = 0; i < layer.length; i += 2) {
+        if (i + 1 === layer.length) {
+          next.push(layer[i])
+          continue
+        }
+        const [first, second] = [layer[i], layer[i + 1]].sort(Buffer.compare)
+        next.push(createHash("sha256").update(Buffer.concat([first, second])).digest())
+      }
+      layers.push(next)
+    }
+    return layers
+  }
+
+  function merkleRoot(wallets: anchor.web3.PublicKey[]) {
+    const layers = merkleLayers(wallets)
+    return Array.from(layers[layers.length - 1][0])
+  }
+
+  function merkleProof(wallets: anchor.web3.PublicKey[], wallet: anchor.web3.PublicKey) {
+    const layers = merkleLayers(wallets)
+    let index = wallets.findIndex((key) => key.equals(wallet))
+    const proof = []
+    for (const layer of layers.slice(0, -1)) {
+      const sibling = layer[index ^ 1]
+      if (sibling) {
+        proof.push(Array.from(sibling))
+      }
+      index = Math.floor(index / 2)
+    }
+    return proof
+  }
+
   async function airdrop(to: anchor.web3.PublicKey, lamports: number = 1000e9) {
     await connection.confirmTransaction(
       await connection.requestAirdrop(to, lamports)
     );
   }
 
+  function sleep(ms: number) {
+    return new Promise((resolve) => setTimeout(resolve, ms));
+  }
+
   function generateRaffleID(length: number = 10) {
     const chars = 'ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789';
     let result = '';